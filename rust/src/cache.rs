@@ -0,0 +1,215 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::color::stable_hash;
+use crate::repo::{ScanDepth, find_git_repos_with};
+
+const CACHE_HEADER: &str = "# git-all repo cache v1";
+
+/// Discovered repos plus the directories whose mtimes vouch for them.
+///
+/// Adding or removing an entry in a directory bumps that directory's mtime, so
+/// recording every directory the walk looked inside (the root, each descended
+/// directory, and each repo) lets us detect a new clone anywhere in the tree
+/// with one `stat` per directory instead of a full re-walk. Directories at the
+/// depth limit are recorded too: `git init` in one turns it into a repo.
+#[derive(Debug, PartialEq, Eq)]
+struct RepoCache {
+    fingerprint: Vec<(PathBuf, u128)>,
+    repos: Vec<PathBuf>,
+}
+
+impl RepoCache {
+    fn scan(root: &Path, scan_depth: ScanDepth, include_bare: bool) -> Result<Self> {
+        let mut dirs = vec![root.to_path_buf()];
+        let repos = find_git_repos_with(root, scan_depth, include_bare, &mut |path, _, _| {
+            dirs.push(path.to_path_buf());
+        })?;
+
+        let fingerprint = dirs
+            .into_iter()
+            .filter_map(|dir| mtime_nanos(&dir).map(|mtime| (dir, mtime)))
+            .collect();
+
+        Ok(Self { fingerprint, repos })
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.fingerprint
+            .iter()
+            .all(|(dir, mtime)| mtime_nanos(dir) == Some(*mtime))
+    }
+
+    fn load(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
+        let mut lines = contents.lines();
+        if lines.next()? != CACHE_HEADER {
+            return None;
+        }
+
+        let mut fingerprint = Vec::new();
+        let mut repos = Vec::new();
+        for line in lines {
+            let (kind, rest) = line.split_once(' ')?;
+            match kind {
+                "dir" => {
+                    let (mtime, dir) = rest.split_once(' ')?;
+                    fingerprint.push((PathBuf::from(dir), mtime.parse().ok()?));
+                }
+                "repo" => repos.push(PathBuf::from(rest)),
+                _ => return None,
+            }
+        }
+        Some(Self { fingerprint, repos })
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let mut contents = format!("{}\n", CACHE_HEADER);
+        for (dir, mtime) in &self.fingerprint {
+            contents.push_str(&format!("dir {} {}\n", mtime, dir.display()));
+        }
+        for repo in &self.repos {
+            contents.push_str(&format!("repo {}\n", repo.display()));
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+fn mtime_nanos(path: &Path) -> Option<u128> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+/// Default cache directory: `$XDG_CACHE_HOME/git-all` or `~/.cache/git-all`.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join("git-all"))
+}
+
+fn cache_file(cache_dir: &Path, root: &Path, scan_depth: ScanDepth, include_bare: bool) -> PathBuf {
+    let mut key = format!("{}\0{:?}", root.display(), scan_depth);
+    // Keep keys of existing caches unchanged for the default (no bare repos)
+    if include_bare {
+        key.push_str("\0bare");
+    }
+    cache_dir.join(format!("repos-{:016x}", stable_hash(&key)))
+}
+
+/// Find repos under `root`, reusing the cached list when its fingerprint still
+/// matches the filesystem and rescanning (and rewriting the cache) otherwise.
+///
+/// With `verify`, always rescan and report on stderr whether the cached list
+/// was accurate, refreshing it on mismatch.
+pub fn find_git_repos_cached(
+    cache_dir: &Path,
+    root: &Path,
+    scan_depth: ScanDepth,
+    include_bare: bool,
+    verify: bool,
+) -> Result<Vec<PathBuf>> {
+    let path = cache_file(cache_dir, root, scan_depth, include_bare);
+    let cached = RepoCache::load(&path);
+
+    if let Some(cache) = cached.as_ref().filter(|c| !verify && c.is_fresh()) {
+        return Ok(cache.repos.clone());
+    }
+
+    let fresh = RepoCache::scan(root, scan_depth, include_bare)?;
+    if verify {
+        match cached {
+            Some(ref cache) if cache.repos == fresh.repos => {
+                eprintln!("git-all: repo cache ok ({} repos)", fresh.repos.len())
+            }
+            Some(_) => eprintln!("git-all: repo cache stale, refreshed"),
+            None => eprintln!("git-all: no repo cache, created"),
+        }
+    }
+
+    if cached.as_ref() != Some(&fresh) {
+        fresh.save(&path)?;
+    }
+    Ok(fresh.repos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_repo(path: PathBuf) {
+        fs::create_dir_all(path.join(".git")).expect("create .git dir");
+    }
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let root = temp.path().join("work");
+        create_repo(root.join("repo1"));
+
+        let cache = RepoCache::scan(&root, ScanDepth::Depth(1), false).unwrap();
+        let path = temp.path().join("cache/repos");
+        cache.save(&path).unwrap();
+
+        assert_eq!(RepoCache::load(&path), Some(cache));
+    }
+
+    #[test]
+    fn test_new_nested_repo_invalidates_cache() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let cache_dir = temp.path().join("cache");
+        let root = temp.path().join("work");
+        create_repo(root.join("org/repo1"));
+
+        let first = find_git_repos_cached(&cache_dir, &root, ScanDepth::All, false, false).unwrap();
+        assert_eq!(first, vec![root.join("org/repo1")]);
+
+        // A clone inside an existing subdirectory leaves the root's mtime alone
+        create_repo(root.join("org/repo2"));
+
+        let second =
+            find_git_repos_cached(&cache_dir, &root, ScanDepth::All, false, false).unwrap();
+        assert_eq!(second, vec![root.join("org/repo1"), root.join("org/repo2")]);
+    }
+
+    #[test]
+    fn test_repo_created_at_depth_limit_invalidates_cache() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let cache_dir = temp.path().join("cache");
+        let root = temp.path().join("work");
+        create_repo(root.join("repo1"));
+        fs::create_dir_all(root.join("notes")).expect("create dir");
+
+        let first =
+            find_git_repos_cached(&cache_dir, &root, ScanDepth::Depth(1), false, false).unwrap();
+        assert_eq!(first, vec![root.join("repo1")]);
+
+        // `git init` in an existing directory leaves the root's mtime alone
+        create_repo(root.join("notes"));
+
+        let second =
+            find_git_repos_cached(&cache_dir, &root, ScanDepth::Depth(1), false, false).unwrap();
+        assert_eq!(second, vec![root.join("notes"), root.join("repo1")]);
+    }
+
+    #[test]
+    fn test_fresh_cache_is_reused() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let cache_dir = temp.path().join("cache");
+        let root = temp.path().join("work");
+        create_repo(root.join("repo1"));
+
+        find_git_repos_cached(&cache_dir, &root, ScanDepth::Depth(1), false, false).unwrap();
+        let cache =
+            RepoCache::load(&cache_file(&cache_dir, &root, ScanDepth::Depth(1), false)).unwrap();
+        assert!(cache.is_fresh());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::runner::Category;
+
+/// When to color output, from `--color`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorMode {
+    /// Color when stdout is a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Whether to color. `NO_COLOR` (any non-empty value) turns off `auto`;
+    /// an explicit `always` still wins.
+    pub fn enabled(self, is_terminal: bool, no_color: Option<&str>) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => is_terminal && no_color.is_none_or(str::is_empty),
+        }
+    }
+}
+
+/// ANSI foreground code for a result category: clean green, dirty yellow,
+/// errors red. Other categories keep the terminal's default color.
+fn category_code(category: Category) -> Option<u8> {
+    match category {
+        Category::Clean => Some(32),
+        Category::Dirty => Some(33),
+        Category::Error => Some(31),
+        Category::Updated | Category::Ok | Category::Skipped => None,
+    }
+}
+
+/// Wrap a result summary in its category's color
+pub fn paint_category(text: &str, category: Category) -> String {
+    match category_code(category) {
+        Some(code) => format!("\x1b[{}m{}\x1b[0m", code, text),
+        None => text.to_string(),
+    }
+}
+
+/// What to derive a repository's display color from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorBy {
+    /// Color each repository by its display name
+    Repo,
+    /// Color repositories by the host of their `origin` remote
+    Host,
+}
+
+/// ANSI foreground codes used for hashed colors.
+/// Red (31/91) is left out so hashed colors never read as errors.
+const PALETTE: [u8; 10] = [32, 33, 34, 35, 36, 92, 93, 94, 95, 96];
+
+/// FNV-1a, used instead of std's hasher because its output is stable
+/// across Rust versions, platforms, and runs.
+pub(crate) fn stable_hash(key: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    key.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Palette index for a key; the same key always maps to the same index.
+pub fn color_index(key: &str) -> usize {
+    (stable_hash(key) % PALETTE.len() as u64) as usize
+}
+
+/// Wrap text in the ANSI color assigned to `key`
+pub fn paint_by_key(text: &str, key: &str) -> String {
+    format!("\x1b[{}m{}\x1b[0m", PALETTE[color_index(key)], text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_index_is_deterministic() {
+        assert_eq!(color_index("github.com"), color_index("github.com"));
+        assert_eq!(color_index("api-service"), color_index("api-service"));
+    }
+
+    #[test]
+    fn test_stable_hash_known_value() {
+        // FNV-1a reference values; guards against accidental algorithm changes
+        assert_eq!(stable_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(stable_hash("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_palette_excludes_red() {
+        assert!(!PALETTE.contains(&31));
+        assert!(!PALETTE.contains(&91));
+    }
+
+    #[test]
+    fn test_color_mode_honors_terminal_and_no_color() {
+        assert!(ColorMode::Auto.enabled(true, None));
+        assert!(ColorMode::Auto.enabled(true, Some("")));
+        assert!(!ColorMode::Auto.enabled(false, None));
+        assert!(!ColorMode::Auto.enabled(true, Some("1")));
+        assert!(ColorMode::Always.enabled(false, Some("1")));
+        assert!(!ColorMode::Never.enabled(true, None));
+    }
+
+    #[test]
+    fn test_paint_category() {
+        assert_eq!(
+            paint_category("clean", Category::Clean),
+            "\x1b[32mclean\x1b[0m"
+        );
+        assert_eq!(
+            paint_category("2 modified", Category::Dirty),
+            "\x1b[33m2 modified\x1b[0m"
+        );
+        assert_eq!(
+            paint_category("fatal: x", Category::Error),
+            "\x1b[31mfatal: x\x1b[0m"
+        );
+        assert_eq!(paint_category("pulled", Category::Updated), "pulled");
+    }
+
+    #[test]
+    fn test_paint_by_key_wraps_text() {
+        let painted = paint_by_key("[repo]", "repo");
+        assert!(painted.starts_with("\x1b["));
+        assert!(painted.contains("[repo]"));
+        assert!(painted.ends_with("\x1b[0m"));
+    }
+}
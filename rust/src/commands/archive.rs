@@ -0,0 +1,167 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use crate::repo::{Repo, repo_display_name};
+use crate::runner::{
+    Category, ExecutionContext, GitCommand, OutputFormatter, error_line, run_parallel,
+};
+
+/// Archive file for a repo, laid out like the repos themselves: nested repos
+/// (`org/repo`) become `org/repo.tar.gz`, so no two repos share a file
+fn archive_path(output_dir: &Path, repo: &Path, display_root: &Path) -> PathBuf {
+    output_dir.join(format!("{}.tar.gz", repo_display_name(repo, display_root)))
+}
+
+/// Reported for repos with no commit to archive
+const EMPTY_REPO: &str = "empty repository (no HEAD), skipped";
+
+/// `git archive ... HEAD` in a freshly initialized repo
+fn is_empty_repo(output: &Output) -> bool {
+    String::from_utf8_lossy(&output.stderr).contains("not a valid object name: HEAD")
+}
+
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+struct ArchiveFormatter<'a> {
+    output_dir: &'a Path,
+    display_root: &'a Path,
+}
+
+impl OutputFormatter for ArchiveFormatter<'_> {
+    fn format(&self, output: &Output) -> String {
+        if is_empty_repo(output) {
+            return EMPTY_REPO.to_string();
+        }
+        if !output.status.success() {
+            return error_line(output);
+        }
+        "archived".to_string()
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if is_empty_repo(output) {
+            Category::Skipped
+        } else if output.status.success() {
+            Category::Ok
+        } else {
+            Category::Error
+        }
+    }
+
+    fn format_for_repo(&self, repo: &Path, output: &Output, _width: Option<usize>) -> String {
+        if !output.status.success() {
+            return self.format(output);
+        }
+
+        let path = archive_path(self.output_dir, repo, self.display_root);
+        match std::fs::metadata(&path) {
+            Ok(meta) => format!("archived {} ({})", path.display(), human_size(meta.len())),
+            Err(e) => format!("ERROR: archive missing: {}", e),
+        }
+    }
+}
+
+pub fn run(
+    ctx: &ExecutionContext,
+    repos: &[Repo],
+    output_dir: &Path,
+    extra_args: &[String],
+) -> Result<()> {
+    // git runs with -C <repo>, so the output path must not be relative
+    let output_dir = ctx.display_root().join(output_dir);
+    if !ctx.is_dry_run() {
+        std::fs::create_dir_all(&output_dir)?;
+        for repo in repos {
+            if let Some(parent) = archive_path(&output_dir, repo, ctx.display_root()).parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+    }
+
+    let formatter = ArchiveFormatter {
+        output_dir: &output_dir,
+        display_root: ctx.display_root(),
+    };
+
+    run_parallel(
+        ctx,
+        repos,
+        |repo| {
+            let target = archive_path(&output_dir, repo, ctx.display_root());
+            let mut args = vec![
+                "archive".to_string(),
+                "--format=tar.gz".to_string(),
+                "-o".to_string(),
+                target.to_string_lossy().to_string(),
+            ];
+            args.extend(extra_args.iter().cloned());
+            args.push("HEAD".to_string());
+            // git archive leaves an empty file behind when it fails
+            GitCommand::new(repo.path().to_path_buf(), args).with_followup(Box::new(
+                move |output| {
+                    if !output.status.success() {
+                        let _ = std::fs::remove_file(&target);
+                    }
+                    Ok(output)
+                },
+            ))
+        },
+        &formatter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_path_mirrors_nested_repos() {
+        let root = PathBuf::from("/work");
+        let out = PathBuf::from("/backups");
+        assert_eq!(
+            archive_path(&out, &root.join("org").join("api"), &root),
+            PathBuf::from("/backups/org/api.tar.gz")
+        );
+        // A top-level repo named like the flattened path gets its own file
+        assert_eq!(
+            archive_path(&out, &root.join("org-api"), &root),
+            PathBuf::from("/backups/org-api.tar.gz")
+        );
+    }
+
+    #[test]
+    fn test_empty_repo_is_skipped() {
+        use std::os::unix::process::ExitStatusExt;
+        let output = Output {
+            status: std::process::ExitStatus::from_raw(128 << 8),
+            stdout: Vec::new(),
+            stderr: b"fatal: not a valid object name: HEAD\n".to_vec(),
+        };
+        let formatter = ArchiveFormatter {
+            output_dir: Path::new("/backups"),
+            display_root: Path::new("/work"),
+        };
+        assert_eq!(formatter.format(&output), EMPTY_REPO);
+        assert_eq!(formatter.category(&output), Category::Skipped);
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(2048), "2.0 KB");
+        assert_eq!(human_size(5 * 1024 * 1024 + 512 * 1024), "5.5 MB");
+    }
+}
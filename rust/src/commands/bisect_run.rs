@@ -0,0 +1,102 @@
+use anyhow::{Result, bail};
+use std::process::Output;
+
+use crate::repo::Repo;
+use crate::runner::{
+    Category, ExecutionContext, GitCommand, OutputFormatter, exit_code, run_parallel,
+};
+
+/// Reports whether a test command passed at each repo's current checkout
+struct BisectRunFormatter;
+
+impl OutputFormatter for BisectRunFormatter {
+    fn format(&self, output: &Output) -> String {
+        if output.status.success() {
+            return "pass".to_string();
+        }
+
+        match exit_code(output) {
+            Some(code) => format!("FAIL (exit {})", code),
+            None => "FAIL".to_string(),
+        }
+    }
+}
+
+/// One-line result for the whole run: how many passed, and which repos failed
+fn verdict(passed: usize, failing: &[String]) -> String {
+    let total = passed + failing.len();
+    if failing.is_empty() {
+        format!("{}/{} passed", passed, total)
+    } else {
+        format!(
+            "{}/{} passed; failing: {}",
+            passed,
+            total,
+            failing.join(", ")
+        )
+    }
+}
+
+/// Run `command` (e.g. `./test.sh`) in every repository and report pass/fail.
+/// Fails when any repository's command fails, so it can drive cross-repo bisection.
+pub fn run(ctx: &ExecutionContext, repos: &[Repo], command: &[String]) -> Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        bail!("bisect-run needs a command to run, e.g. git-all bisect-run -- ./test.sh");
+    };
+
+    run_parallel(
+        ctx,
+        repos,
+        |repo| GitCommand::program(repo.path().to_path_buf(), program.clone(), args.to_vec()),
+        &BisectRunFormatter,
+    )?;
+
+    if ctx.is_dry_run() {
+        return Ok(());
+    }
+
+    let failing: Vec<String> = ctx
+        .repos_in(Category::Error)
+        .iter()
+        .map(|repo| ctx.display_name(repo))
+        .collect();
+    let passed = ctx.repos_in(Category::Ok).len();
+
+    println!("{}", verdict(passed, &failing));
+    if !failing.is_empty() {
+        bail!("{} failed in {} repositories", program, failing.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn exited(code: i32) -> Output {
+        Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_pass_and_fail_summaries() {
+        assert_eq!(BisectRunFormatter.format(&exited(0)), "pass");
+        assert_eq!(BisectRunFormatter.format(&exited(3)), "FAIL (exit 3)");
+        assert_eq!(BisectRunFormatter.category(&exited(0)), Category::Ok);
+        assert_eq!(BisectRunFormatter.category(&exited(1)), Category::Error);
+    }
+
+    #[test]
+    fn test_verdict_lists_failing_repos() {
+        assert_eq!(verdict(3, &[]), "3/3 passed");
+        assert_eq!(
+            verdict(1, &["api".to_string(), "web".to_string()]),
+            "1/3 passed; failing: api, web"
+        );
+    }
+}
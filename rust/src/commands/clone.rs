@@ -0,0 +1,189 @@
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use crate::repo::{Repo, repo_name};
+use crate::runner::{
+    Category, ExecutionContext, GitCommand, OutputFormatter, error_line, run_parallel,
+};
+
+/// Reported for repos whose target directory is already there
+const EXISTS: &str = "exists, skipped";
+
+struct CloneFormatter;
+
+impl OutputFormatter for CloneFormatter {
+    fn format(&self, output: &Output) -> String {
+        if !output.status.success() {
+            return error_line(output);
+        }
+
+        "cloned".to_string()
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if output.status.success() {
+            Category::Updated
+        } else {
+            Category::Error
+        }
+    }
+}
+
+/// The directory `git clone` would create for `url`: its last path component
+/// without a `.git` suffix. Handles https and ssh:// URLs, scp-like
+/// `git@host:org/repo.git`, and local paths. None if nothing usable is left.
+pub fn clone_dir_name(url: &str) -> Option<String> {
+    let path = url.trim().trim_end_matches('/');
+    let path = path
+        .strip_suffix(".git")
+        .unwrap_or(path)
+        .trim_end_matches('/');
+    // After the last '/', or after the ':' of an scp-like `host:repo`
+    let name = path.rsplit(['/', ':']).next()?;
+    match name {
+        "" | "." | ".." => None,
+        _ => Some(name.to_string()),
+    }
+}
+
+/// Repository URLs from a manifest file: one per line, blank lines and
+/// `#` comments ignored
+fn read_manifest(path: &Path) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("failed to read manifest {}", path.display()))?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Whether a positional clone argument names a manifest rather than a
+/// repository: an existing file that isn't a URL. Local repositories are
+/// directories, except git bundles, which clone like any other source.
+fn is_manifest(arg: &str) -> bool {
+    // scp-like `host:path` has a ':' before any '/'
+    let is_url = arg.contains("://") || arg.split('/').next().is_some_and(|h| h.contains(':'));
+    !is_url && !arg.ends_with(".bundle") && Path::new(arg).is_file()
+}
+
+/// Clone each URL into `root`, in parallel, naming each directory after its
+/// URL. Arguments naming a manifest file are replaced by the URLs it lists.
+/// Targets that already exist are skipped, so a manifest can be re-run to
+/// clone only what is missing.
+pub fn run(
+    ctx: &ExecutionContext,
+    root: &Path,
+    args: &[String],
+    manifest: Option<&Path>,
+) -> Result<()> {
+    let mut urls = Vec::new();
+    for arg in args {
+        if is_manifest(arg) {
+            urls.extend(read_manifest(Path::new(arg))?);
+        } else {
+            urls.push(arg.clone());
+        }
+    }
+    if let Some(manifest) = manifest {
+        urls.extend(read_manifest(manifest)?);
+    }
+    if urls.is_empty() {
+        bail!("clone needs repository URLs, e.g. git-all clone git@github.com:org/api.git");
+    }
+
+    let mut targets: HashMap<PathBuf, String> = HashMap::new();
+    for url in urls {
+        let Some(name) = clone_dir_name(&url) else {
+            bail!("can't derive a directory name from {}", url);
+        };
+        let target = root.join(&name);
+        if let Some(other) = targets.get(&target) {
+            bail!("{} and {} would both clone into {}", other, url, name);
+        }
+        targets.insert(target, url);
+    }
+
+    let mut repos: Vec<Repo> = targets.keys().cloned().map(Repo::new).collect();
+    repos.sort_by(|a, b| a.path().cmp(b.path()));
+
+    run_parallel(
+        ctx,
+        &repos,
+        |repo| {
+            if repo.exists() {
+                return GitCommand::skipped(repo.path().to_path_buf(), EXISTS);
+            }
+            let args = vec![
+                "clone".to_string(),
+                targets[repo.path()].clone(),
+                repo_name(repo),
+            ];
+            GitCommand::new(root.to_path_buf(), args)
+        },
+        &CloneFormatter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clone_dir_name_across_url_formats() {
+        let cases = [
+            ("git@github.com:org/repo.git", "repo"),
+            ("git@github.com:repo.git", "repo"),
+            ("https://github.com/org/repo.git", "repo"),
+            ("https://github.com/org/repo", "repo"),
+            ("https://github.com/org/repo.git/", "repo"),
+            ("ssh://git@example.com:2222/org/team/repo.git", "repo"),
+            ("file:///srv/git/repo.git", "repo"),
+            ("../mirrors/repo.git", "repo"),
+            ("https://example.com/org/my.project.git", "my.project"),
+        ];
+        for (url, name) in cases {
+            assert_eq!(clone_dir_name(url).as_deref(), Some(name), "{url}");
+        }
+
+        assert_eq!(clone_dir_name(""), None);
+        assert_eq!(clone_dir_name("/"), None);
+    }
+
+    #[test]
+    fn test_only_existing_non_url_files_are_manifests() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let manifest = temp.path().join("repos.txt");
+        let bundle = temp.path().join("api.bundle");
+        fs::write(&manifest, "git@github.com:org/api.git\n").unwrap();
+        fs::write(&bundle, "").unwrap();
+
+        assert!(is_manifest(manifest.to_str().unwrap()));
+        assert!(!is_manifest(bundle.to_str().unwrap()));
+        assert!(!is_manifest(temp.path().to_str().unwrap()));
+        assert!(!is_manifest(
+            temp.path().join("missing.txt").to_str().unwrap()
+        ));
+        assert!(!is_manifest("git@github.com:org/api.git"));
+        assert!(!is_manifest("https://github.com/org/api"));
+    }
+
+    #[test]
+    fn test_read_manifest_skips_comments_and_blanks() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let manifest = temp.path().join("repos.txt");
+        fs::write(
+            &manifest,
+            "# services\ngit@github.com:org/api.git\n\n  https://github.com/org/web  \n",
+        )
+        .unwrap();
+        assert_eq!(
+            read_manifest(&manifest).unwrap(),
+            vec!["git@github.com:org/api.git", "https://github.com/org/web"]
+        );
+    }
+}
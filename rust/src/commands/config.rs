@@ -0,0 +1,149 @@
+use anyhow::Result;
+use std::process::Output;
+
+use crate::repo::Repo;
+use crate::runner::{
+    Category, ExecutionContext, GitCommand, OutputFormatter, error_line, exit_code, run_parallel,
+};
+
+/// `git config <key>` exits 1 with no output when the key is not set
+const UNSET_EXIT_CODE: i32 = 1;
+
+fn is_unset(output: &Output) -> bool {
+    exit_code(output) == Some(UNSET_EXIT_CODE) && output.stderr.iter().all(u8::is_ascii_whitespace)
+}
+
+/// Build git config arguments: a read when `value` is None, otherwise a write
+pub(crate) fn build_args(key: &str, value: Option<&str>) -> Vec<String> {
+    let mut args = vec!["config".to_string(), key.to_string()];
+    if let Some(value) = value {
+        args.push(value.to_string());
+    }
+    args
+}
+
+struct ConfigGetFormatter;
+
+impl OutputFormatter for ConfigGetFormatter {
+    fn format(&self, output: &Output) -> String {
+        if is_unset(output) {
+            return "(unset)".to_string();
+        }
+        if !output.status.success() {
+            return error_line(output);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout.lines().next().unwrap_or("").trim_end().to_string()
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if output.status.success() || is_unset(output) {
+            Category::Ok
+        } else {
+            Category::Error
+        }
+    }
+}
+
+struct ConfigSetFormatter {
+    key: String,
+    value: String,
+}
+
+impl OutputFormatter for ConfigSetFormatter {
+    fn format(&self, output: &Output) -> String {
+        if !output.status.success() {
+            return error_line(output);
+        }
+
+        format!("set {} = {}", self.key, self.value)
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if output.status.success() {
+            Category::Updated
+        } else {
+            Category::Error
+        }
+    }
+}
+
+/// Read a config value in every repository, or write it when `value` is given.
+pub fn run(ctx: &ExecutionContext, repos: &[Repo], key: &str, value: Option<&str>) -> Result<()> {
+    let args = build_args(key, value);
+    let build = |repo: &Repo| GitCommand::new(repo.path().to_path_buf(), args.clone());
+
+    match value {
+        Some(value) => {
+            let formatter = ConfigSetFormatter {
+                key: key.to_string(),
+                value: value.to_string(),
+            };
+            run_parallel(ctx, repos, build, &formatter)
+        }
+        None => run_parallel(ctx, repos, build, &ConfigGetFormatter),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn make_output(stdout: &str, stderr: &str, code: i32) -> Output {
+        Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_get_reports_value() {
+        let output = make_output("true\n", "", 0);
+        assert_eq!(ConfigGetFormatter.format(&output), "true");
+        assert_eq!(ConfigGetFormatter.category(&output), Category::Ok);
+    }
+
+    #[test]
+    fn test_get_reports_unset() {
+        let output = make_output("", "", 1);
+        assert_eq!(ConfigGetFormatter.format(&output), "(unset)");
+        assert_eq!(ConfigGetFormatter.category(&output), Category::Ok);
+    }
+
+    #[test]
+    fn test_get_reports_invalid_key() {
+        let output = make_output("", "error: key does not contain a section: nosection\n", 1);
+        assert_eq!(
+            ConfigGetFormatter.format(&output),
+            "error: key does not contain a section: nosection"
+        );
+        assert_eq!(ConfigGetFormatter.category(&output), Category::Error);
+    }
+
+    #[test]
+    fn test_build_args_get_and_set() {
+        assert_eq!(
+            build_args("pull.rebase", None),
+            vec!["config", "pull.rebase"]
+        );
+        assert_eq!(
+            build_args("pull.rebase", Some("true")),
+            vec!["config", "pull.rebase", "true"]
+        );
+    }
+
+    #[test]
+    fn test_set_formatter() {
+        let formatter = ConfigSetFormatter {
+            key: "pull.rebase".to_string(),
+            value: "true".to_string(),
+        };
+        let output = make_output("", "", 0);
+        assert_eq!(formatter.format(&output), "set pull.rebase = true");
+        assert_eq!(formatter.category(&output), Category::Updated);
+    }
+}
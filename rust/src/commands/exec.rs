@@ -0,0 +1,137 @@
+use anyhow::{Result, bail};
+use std::path::Path;
+use std::process::Output;
+
+use crate::repo::Repo;
+use crate::runner::{
+    ExecutionContext, GitCommand, OutputFormatter, error_line, first_output_line, run_parallel,
+    shell_quote,
+};
+
+/// Placeholders substituted in each argument, per repository
+const REPO_PLACEHOLDER: &str = "{repo}";
+const NAME_PLACEHOLDER: &str = "{name}";
+
+/// Options for the exec command
+#[derive(Default, Clone)]
+pub struct ExecOptions {
+    /// Run the command as a `sh -c` script instead of executing it directly
+    pub shell: bool,
+}
+
+struct ExecFormatter;
+
+impl OutputFormatter for ExecFormatter {
+    fn format(&self, output: &Output) -> String {
+        if !output.status.success() {
+            return format!("ERROR: {}", error_line(output));
+        }
+
+        first_output_line(output).unwrap_or_else(|| "ok".to_string())
+    }
+}
+
+/// Replace `{repo}` with the repository's path and `{name}` with its display
+/// name. Each argument stays one argument, so paths with spaces need no quoting.
+fn substitute(args: &[String], repo: &Path, name: &str) -> Vec<String> {
+    let repo = repo.to_string_lossy();
+    args.iter()
+        .map(|arg| {
+            arg.replace(REPO_PLACEHOLDER, &repo)
+                .replace(NAME_PLACEHOLDER, name)
+        })
+        .collect()
+}
+
+/// The `sh -c` script for `--shell`: the words joined with spaces, as a shell
+/// would have, with `{repo}` and `{name}` replaced by shell-quoted values so
+/// paths with spaces or quotes stay one word and are never run as code.
+fn shell_script(command: &[String], repo: &Path, name: &str) -> String {
+    let repo = shell_quote(&repo.to_string_lossy());
+    let name = shell_quote(name);
+    command
+        .join(" ")
+        .replace(REPO_PLACEHOLDER, &repo)
+        .replace(NAME_PLACEHOLDER, &name)
+}
+
+/// Run an arbitrary command in every repository (with the repo as working directory)
+pub fn run(
+    ctx: &ExecutionContext,
+    repos: &[Repo],
+    opts: &ExecOptions,
+    command: &[String],
+) -> Result<()> {
+    if command.is_empty() {
+        bail!("exec needs a command to run, e.g. git-all exec -- make lint");
+    }
+
+    run_parallel(
+        ctx,
+        repos,
+        |repo| {
+            let name = ctx.display_name(repo);
+            if opts.shell {
+                let script = shell_script(command, repo, &name);
+                let args = vec!["-c".to_string(), script];
+                return GitCommand::program(repo.path().to_path_buf(), "sh".to_string(), args);
+            }
+            let command = substitute(command, repo, &name);
+            let (program, args) = command.split_first().expect("command is not empty");
+            GitCommand::program(repo.path().to_path_buf(), program.clone(), args.to_vec())
+        },
+        &ExecFormatter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_substitute_repo_and_name() {
+        let args = strings(&[
+            "cp",
+            "template.conf",
+            "{repo}/config.conf",
+            "--tag={name}-{name}",
+        ]);
+        assert_eq!(
+            substitute(&args, Path::new("/src/api"), "api"),
+            strings(&[
+                "cp",
+                "template.conf",
+                "/src/api/config.conf",
+                "--tag=api-api"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_substitute_keeps_paths_with_spaces_as_one_arg() {
+        let args = strings(&["touch", "{repo}/marker"]);
+        assert_eq!(
+            substitute(&args, Path::new("/my projects/web app"), "web app"),
+            strings(&["touch", "/my projects/web app/marker"])
+        );
+    }
+
+    #[test]
+    fn test_shell_script_quotes_placeholders() {
+        let command = strings(&["git log -1 | head -3 >", "{repo}/{name}.log"]);
+        assert_eq!(
+            shell_script(&command, Path::new("/my projects/it's"), "it's"),
+            "git log -1 | head -3 > '/my projects/it'\\''s'/'it'\\''s'.log"
+        );
+    }
+
+    #[test]
+    fn test_substitute_without_placeholders() {
+        let args = strings(&["make", "lint"]);
+        assert_eq!(substitute(&args, Path::new("/src/api"), "api"), args);
+    }
+}
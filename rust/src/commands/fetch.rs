@@ -1,8 +1,256 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::Path;
 use std::process::Output;
+use std::time::{Duration, SystemTime};
 
-use crate::runner::{run_parallel, ExecutionContext, GitCommand, OutputFormatter};
+use crate::meta::git_supports;
+use crate::repo::{Repo, git_dir, remotes};
+use crate::runner::{
+    Category, CommandSettings, ExecutionContext, GitCommand, OutputFormatter, error_line,
+    run_parallel,
+};
+
+/// `fetch.writeCommitGraph` was added in git 2.24
+const COMMIT_GRAPH_MIN_GIT: (u32, u32) = (2, 24);
+
+/// Options for the fetch command beyond pass-through git args
+#[derive(Default, Clone)]
+pub struct FetchOptions {
+    /// Write the commit-graph file as a side effect of fetching
+    pub commit_graph: bool,
+    /// Pass `--recurse-submodules[=MODE]` to git fetch
+    pub recurse_submodules: Option<String>,
+    /// Fetch each remote of a multi-remote repo as its own parallel git fetch
+    pub parallel_remotes: bool,
+    /// Pass `--update-head-ok`, letting fetch update the checked-out branch's ref
+    pub update_head_ok: bool,
+    /// Skip repos fetched more recently than this
+    pub min_interval: Option<Duration>,
+    /// Count the commits the fetch brought to the remote's default branch
+    pub report_new_commits: bool,
+    /// Update bare mirrors so every ref matches origin's, pruning the rest
+    pub mirror_update: bool,
+}
+
+/// Reported for repos skipped by `--min-interval`
+const RECENTLY_FETCHED: &str = "recently fetched (skipped)";
+
+/// Whether the repo was fetched within `interval` before `now`, going by the
+/// mtime of `FETCH_HEAD`, which git rewrites on every fetch. Repos never
+/// fetched (or with a FETCH_HEAD from the future) are not skipped.
+fn fetched_within(repo: &Path, interval: Duration, now: SystemTime) -> bool {
+    git_dir(repo)
+        .and_then(|dir| std::fs::metadata(dir.join("FETCH_HEAD")).ok())
+        .and_then(|meta| meta.modified().ok())
+        .and_then(|fetched| now.duration_since(fetched).ok())
+        .is_some_and(|age| age < interval)
+}
+
+/// Output before the first "Fetching submodule ..." line. git prints each
+/// submodule's ref updates after that header; they are not this repo's refs.
+fn superproject_section(text: &str) -> &str {
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.starts_with("Fetching submodule") {
+            return &text[..offset];
+        }
+        offset += line.len();
+    }
+    text
+}
+
+/// Count refs removed by `--prune` (lines like ` - [deleted] (none) -> origin/x`)
+pub(crate) fn count_pruned_refs(text: &str) -> usize {
+    text.lines().filter(|l| l.contains("[deleted]")).count()
+}
+
+/// Count ref updates git marks `(forced update)`: the upstream branch was
+/// rewritten (e.g. force-pushed after a rebase)
+fn count_forced_updates(text: &str) -> usize {
+    text.lines()
+        .filter(|l| l.contains("(forced update)"))
+        .count()
+}
+
+fn forced_summary(count: usize) -> String {
+    format!(
+        "{} forced update{}",
+        count,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+fn pruned_summary(count: usize) -> String {
+    format!("{} pruned", count)
+}
+
+/// A fast-forward ref update from fetch output, e.g.
+/// `   3b7d4f1..42ef2a4  main       -> origin/main`
+#[derive(Debug, PartialEq, Eq)]
+struct FastForward {
+    /// The branch's name on the remote
+    branch: String,
+    old: String,
+    new: String,
+}
+
+/// Parse a fetch output line as a fast-forward. Forced updates (`old...new`)
+/// and new refs have no range to count and give None.
+fn parse_fast_forward(line: &str) -> Option<FastForward> {
+    let mut fields = line.split_whitespace();
+    let (old, new) = fields.next()?.split_once("..")?;
+    let branch = fields.next()?;
+    if fields.next()? != "->" {
+        return None;
+    }
+    let is_sha = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit());
+    if !is_sha(old) || !is_sha(new) {
+        return None;
+    }
+    Some(FastForward {
+        branch: branch.to_string(),
+        old: old.to_string(),
+        new: new.to_string(),
+    })
+}
+
+/// Prepended to stdout with "<branch> <count>" once new commits are counted
+const NEW_COMMITS_MARKER: &str = "git-all: new commits on ";
+
+/// The branch and count from a new-commits marker line, if stdout has one
+fn parse_new_commits(stdout: &str) -> Option<(&str, usize)> {
+    let marker = stdout.lines().next()?.strip_prefix(NEW_COMMITS_MARKER)?;
+    let (branch, count) = marker.rsplit_once(' ')?;
+    Some((branch, count.parse().ok()?))
+}
+
+fn new_commits_summary(branch: &str, count: usize) -> String {
+    format!(
+        "{}: {} new commit{}",
+        branch,
+        count,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+/// The branch `origin/HEAD` points at, e.g. "main"
+fn default_branch(repo: &Path, settings: &CommandSettings) -> std::io::Result<Option<String>> {
+    let args = vec![
+        "symbolic-ref".to_string(),
+        "--quiet".to_string(),
+        "--short".to_string(),
+        "refs/remotes/origin/HEAD".to_string(),
+    ];
+    let output = settings.command(repo, args).run(None)?;
+    Ok(output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .and_then(|head| head.strip_prefix("origin/").map(str::to_string)))
+}
+
+/// After a successful fetch that fast-forwarded the default branch, count its
+/// new commits with `git rev-list --count old..new` and mark them on stdout
+/// for the formatter. Any other result is returned unchanged.
+fn count_new_commits(
+    repo: &Path,
+    settings: &CommandSettings,
+    mut output: Output,
+) -> std::io::Result<Output> {
+    if !output.status.success() {
+        return Ok(output);
+    }
+    let Some(branch) = default_branch(repo, settings)? else {
+        return Ok(output);
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    let Some(update) = superproject_section(&stdout)
+        .lines()
+        .chain(superproject_section(&stderr).lines())
+        .filter_map(parse_fast_forward)
+        .find(|update| update.branch == branch)
+    else {
+        return Ok(output);
+    };
+
+    let args = vec![
+        "rev-list".to_string(),
+        "--count".to_string(),
+        format!("{}..{}", update.old, update.new),
+    ];
+    let counted = settings.command(repo, args).run(None)?;
+    let Ok(count) = String::from_utf8_lossy(&counted.stdout)
+        .trim()
+        .parse::<usize>()
+    else {
+        return Ok(output);
+    };
+    let mut marked = format!("{}{} {}\n", NEW_COMMITS_MARKER, branch, count).into_bytes();
+    marked.append(&mut output.stdout);
+    output.stdout = marked;
+    Ok(output)
+}
+
+/// Maps every ref on origin onto the same ref in the mirror
+const MIRROR_REFSPEC: &str = "+refs/*:refs/*";
+
+/// Reported for non-bare repos by `--mirror-update`
+const NOT_A_MIRROR: &str = "not a bare mirror (skipped)";
+
+/// Reports a mirror update as refs changed: with a `+refs/*:refs/*` refspec
+/// branches and tags land under the same names, so they are counted together.
+struct MirrorFormatter;
+
+impl MirrorFormatter {
+    /// (refs updated or created, refs pruned)
+    fn counts(output: &Output) -> (usize, usize) {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let pruned = count_pruned_refs(&stdout) + count_pruned_refs(&stderr);
+        let updated = stdout
+            .lines()
+            .chain(stderr.lines())
+            .filter(|l| l.contains("->") && !l.contains("[deleted]"))
+            .count();
+        (updated, pruned)
+    }
+}
+
+impl OutputFormatter for MirrorFormatter {
+    /// "3 refs updated, 1 pruned"
+    fn format(&self, output: &Output) -> String {
+        if !output.status.success() {
+            return error_line(output);
+        }
+        let (updated, pruned) = Self::counts(output);
+        let mut summary = Vec::new();
+        if updated > 0 {
+            summary.push(format!(
+                "{} ref{} updated",
+                updated,
+                if updated == 1 { "" } else { "s" }
+            ));
+        }
+        if pruned > 0 {
+            summary.push(pruned_summary(pruned));
+        }
+        if summary.is_empty() {
+            return "up to date".to_string();
+        }
+        summary.join(", ")
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if !output.status.success() {
+            Category::Error
+        } else if Self::counts(output) == (0, 0) {
+            Category::Clean
+        } else {
+            Category::Updated
+        }
+    }
+}
 
 struct FetchFormatter;
 
@@ -12,57 +260,220 @@ impl OutputFormatter for FetchFormatter {
         let stderr = String::from_utf8_lossy(&output.stderr);
 
         if !output.status.success() {
-            return stderr.lines().next().unwrap_or("unknown error").to_string();
+            return error_line(output);
         }
 
-        let has_output = stdout.lines().any(|l| !l.trim().is_empty())
-            || stderr.lines().any(|l| !l.trim().is_empty() && !l.starts_with("From"));
+        let stdout = superproject_section(&stdout);
+        let stderr = superproject_section(&stderr);
+
+        // "From <url>" only names the remote; it turns up on stdout too when
+        // --stderr-merge has folded stderr in
+        let has_output = stdout
+            .lines()
+            .chain(stderr.lines())
+            .any(|l| !l.trim().is_empty() && !l.starts_with("From"));
 
         if !has_output {
             return "no new commits".to_string();
         }
 
+        let pruned_count = count_pruned_refs(stdout) + count_pruned_refs(stderr);
+        let forced_count = count_forced_updates(stdout) + count_forced_updates(stderr);
+
+        // git reports ref updates on stderr; stdout is checked as well.
+        // Forced updates are counted on their own and listed first.
         let (branch_count, tag_count) = stdout
             .lines()
+            .chain(stderr.lines())
+            .filter(|l| !l.contains("[deleted]") && !l.contains("(forced update)"))
             .filter(|l| l.contains("->") || l.contains("[new"))
             .fold((0, 0), |(b, t), l| {
-                if l.contains("[new tag]") { (b, t + 1) } else { (b + 1, t) }
+                if l.contains("[new tag]") {
+                    (b, t + 1)
+                } else {
+                    (b + 1, t)
+                }
             });
 
+        let mut summary = Vec::new();
+        if forced_count > 0 {
+            summary.push(forced_summary(forced_count));
+        }
         if branch_count > 0 || tag_count > 0 {
             let mut parts = Vec::new();
             if branch_count > 0 {
-                parts.push(format!("{} branch{}", branch_count, if branch_count == 1 { "" } else { "es" }));
+                parts.push(format!(
+                    "{} branch{}",
+                    branch_count,
+                    if branch_count == 1 { "" } else { "es" }
+                ));
             }
             if tag_count > 0 {
-                parts.push(format!("{} tag{}", tag_count, if tag_count == 1 { "" } else { "s" }));
+                parts.push(format!(
+                    "{} tag{}",
+                    tag_count,
+                    if tag_count == 1 { "" } else { "s" }
+                ));
             }
-            return format!("{} updated", parts.join(", "));
+            summary.push(format!("{} updated", parts.join(", ")));
+        }
+        if let Some((branch, count)) = parse_new_commits(stdout) {
+            summary.push(new_commits_summary(branch, count));
         }
+        if pruned_count > 0 {
+            summary.push(pruned_summary(pruned_count));
+        }
+
+        if summary.is_empty() {
+            return "fetched".to_string();
+        }
+        summary.join(", ")
+    }
 
-        "fetched".to_string()
+    fn category(&self, output: &Output) -> Category {
+        if !output.status.success() {
+            Category::Error
+        } else if self.format(output) == "no new commits" {
+            Category::Clean
+        } else {
+            Category::Updated
+        }
+    }
+}
+
+/// Build the git args for a fetch, including any `-c` config injections
+fn build_args(opts: &FetchOptions, extra_args: &[String]) -> Vec<String> {
+    let mut args = Vec::new();
+    if opts.commit_graph {
+        args.push("-c".to_string());
+        args.push("fetch.writeCommitGraph=true".to_string());
+    }
+    args.push("fetch".to_string());
+    if opts.update_head_ok {
+        args.push("--update-head-ok".to_string());
+    }
+    match opts.recurse_submodules.as_deref() {
+        Some("") => args.push("--recurse-submodules".to_string()),
+        Some(mode) => args.push(format!("--recurse-submodules={}", mode)),
+        None => {}
     }
+    args.extend(extra_args.iter().cloned());
+    args
 }
 
-pub fn run(ctx: &ExecutionContext, repos: &[PathBuf], extra_args: &[String]) -> Result<()> {
-    let formatter = FetchFormatter;
+/// One fetch per remote, for repos fetched with `--parallel-remotes`
+fn per_remote_args(
+    opts: &FetchOptions,
+    extra_args: &[String],
+    remotes: &[String],
+) -> Vec<Vec<String>> {
+    remotes
+        .iter()
+        .map(|remote| {
+            let mut args = build_args(opts, extra_args);
+            args.push(remote.clone());
+            args
+        })
+        .collect()
+}
+
+/// `git fetch --prune origin +refs/*:refs/*`, with `--update-head-ok` since
+/// a mirror's HEAD branch is among the refs being overwritten
+fn mirror_args(opts: &FetchOptions, extra_args: &[String]) -> Vec<String> {
+    let opts = FetchOptions {
+        update_head_ok: true,
+        ..opts.clone()
+    };
+    let mut args = build_args(&opts, extra_args);
+    args.push("--prune".to_string());
+    args.push("origin".to_string());
+    args.push(MIRROR_REFSPEC.to_string());
+    args
+}
+
+/// The mirror refspec would overwrite a working repo's local branches, so
+/// only bare repos are updated
+fn mirror_command(opts: &FetchOptions, extra_args: &[String], repo: &Repo) -> GitCommand {
+    if !repo.is_bare() {
+        return GitCommand::skipped(repo.to_path_buf(), NOT_A_MIRROR);
+    }
+    GitCommand::new(repo.to_path_buf(), mirror_args(opts, extra_args))
+}
+
+fn build_command(opts: &FetchOptions, extra_args: &[String], repo: &Repo) -> GitCommand {
+    // A mirror's refspec (+refs/*:refs/*) covers the branch HEAD points at,
+    // which git refuses to fetch into without --update-head-ok
+    let bare_opts;
+    let opts = if repo.is_bare() && !opts.update_head_ok {
+        bare_opts = FetchOptions {
+            update_head_ok: true,
+            ..opts.clone()
+        };
+        &bare_opts
+    } else {
+        opts
+    };
+    if opts.parallel_remotes {
+        let remotes = remotes(repo);
+        if remotes.len() > 1 {
+            let mut per_remote = per_remote_args(opts, extra_args, &remotes);
+            let first = per_remote.remove(0);
+            return GitCommand::new(repo.to_path_buf(), first).with_fan_out(per_remote);
+        }
+    }
+    GitCommand::new(repo.to_path_buf(), build_args(opts, extra_args))
+}
 
+pub fn run(
+    ctx: &ExecutionContext,
+    repos: &[Repo],
+    opts: &FetchOptions,
+    extra_args: &[String],
+) -> Result<()> {
+    let formatter: &dyn OutputFormatter = if opts.mirror_update {
+        &MirrorFormatter
+    } else {
+        &FetchFormatter
+    };
+
+    let mut opts = opts.clone();
+    if opts.commit_graph && !git_supports(COMMIT_GRAPH_MIN_GIT) {
+        eprintln!("git-all: --commit-graph requires git 2.24+, ignoring");
+        opts.commit_graph = false;
+    }
+
+    let now = SystemTime::now();
+    let settings = ctx.command_settings();
     run_parallel(
         ctx,
         repos,
-        |repo| {
-            let mut args = vec!["fetch".to_string()];
-            args.extend(extra_args.iter().cloned());
-            GitCommand::new(repo.clone(), args)
+        |repo| match opts.min_interval {
+            Some(interval) if fetched_within(repo, interval, now) => {
+                GitCommand::skipped(repo.path().to_path_buf(), RECENTLY_FETCHED)
+            }
+            _ if opts.mirror_update => mirror_command(&opts, extra_args, repo),
+            _ => {
+                let cmd = build_command(&opts, extra_args, repo);
+                if !opts.report_new_commits {
+                    return cmd;
+                }
+                let repo = repo.to_path_buf();
+                let settings = settings.clone();
+                cmd.with_followup(Box::new(move |output| {
+                    count_new_commits(&repo, &settings, output)
+                }))
+            }
         },
-        &formatter,
+        formatter,
     )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::runner::combine_outputs;
     use std::os::unix::process::ExitStatusExt;
+    use std::path::PathBuf;
     use std::process::ExitStatus;
 
     fn make_output(stdout: &str, stderr: &str, success: bool) -> Output {
@@ -94,6 +505,16 @@ mod tests {
         assert_eq!(formatter.format(&output), "no new commits");
     }
 
+    #[test]
+    fn test_only_from_line_merged_into_stdout_returns_no_new_commits() {
+        let formatter = FetchFormatter;
+        let output =
+            crate::runner::merge_stderr(make_output("", "From github.com:user/repo\n", true));
+        assert!(output.stderr.is_empty());
+        assert_eq!(formatter.format(&output), "no new commits");
+        assert_eq!(formatter.category(&output), Category::Clean);
+    }
+
     #[test]
     fn test_single_branch_update() {
         let formatter = FetchFormatter;
@@ -132,6 +553,267 @@ mod tests {
         assert_eq!(formatter.format(&output), "1 branch, 1 tag updated");
     }
 
+    #[test]
+    fn test_pruned_refs_counted_separately() {
+        let formatter = FetchFormatter;
+        let stderr = "From github.com:user/repo\n - [deleted]         (none)     -> origin/old\n";
+        let output = make_output(
+            "   abc123..def456  main       -> origin/main\n",
+            stderr,
+            true,
+        );
+        assert_eq!(formatter.format(&output), "1 branch updated, 1 pruned");
+    }
+
+    #[test]
+    fn test_forced_updates_reported_first() {
+        let formatter = FetchFormatter;
+        let stderr = "From github.com:user/repo\n + 1a2b3c4...5d6e7f8 feature    -> origin/feature  (forced update)\n   abc123..def456  main       -> origin/main\n";
+        let output = make_output("", stderr, true);
+        assert_eq!(
+            formatter.format(&output),
+            "1 forced update, 1 branch updated"
+        );
+        assert_eq!(formatter.category(&output), Category::Updated);
+    }
+
+    #[test]
+    fn test_multiple_forced_updates_only() {
+        let formatter = FetchFormatter;
+        let stderr = "From github.com:user/repo\n + 1a2b3c4...5d6e7f8 feature    -> origin/feature  (forced update)\n + 9a8b7c6...5f4e3d2 wip        -> origin/wip  (forced update)\n";
+        let output = make_output("", stderr, true);
+        assert_eq!(formatter.format(&output), "2 forced updates");
+    }
+
+    #[test]
+    fn test_parse_fast_forward() {
+        assert_eq!(
+            parse_fast_forward("   3b7d4f1..42ef2a4  main       -> origin/main"),
+            Some(FastForward {
+                branch: "main".to_string(),
+                old: "3b7d4f1".to_string(),
+                new: "42ef2a4".to_string(),
+            })
+        );
+        for line in [
+            " + 1a2b3c4...5d6e7f8 feature    -> origin/feature  (forced update)",
+            " * [new branch]      topic      -> origin/topic",
+            " - [deleted]         (none)     -> origin/old",
+            "From github.com:user/repo",
+            "   abc..xyz  main -> origin/main",
+        ] {
+            assert_eq!(parse_fast_forward(line), None, "{line}");
+        }
+    }
+
+    #[test]
+    fn test_new_commits_reported_after_branch_updates() {
+        let stdout = format!("{}main 12\n", NEW_COMMITS_MARKER);
+        let stderr = "From github.com:user/repo\n   3b7d4f1..42ef2a4  main       -> origin/main\n";
+        let output = make_output(&stdout, stderr, true);
+        assert_eq!(
+            FetchFormatter.format(&output),
+            "1 branch updated, main: 12 new commits"
+        );
+
+        let one = make_output(
+            &format!("{}release/2.x 1\n", NEW_COMMITS_MARKER),
+            stderr,
+            true,
+        );
+        assert_eq!(
+            FetchFormatter.format(&one),
+            "1 branch updated, release/2.x: 1 new commit"
+        );
+    }
+
+    #[test]
+    fn test_category() {
+        let formatter = FetchFormatter;
+        assert_eq!(
+            formatter.category(&make_output("", "", true)),
+            Category::Clean
+        );
+        let updated = make_output("   abc123..def456  main       -> origin/main\n", "", true);
+        assert_eq!(formatter.category(&updated), Category::Updated);
+        assert_eq!(
+            formatter.category(&make_output("", "fatal: x", false)),
+            Category::Error
+        );
+    }
+
+    #[test]
+    fn test_stderr_ref_updates_counted() {
+        let formatter = FetchFormatter;
+        let stderr = "From github.com:user/repo\n   abc123..def456  main       -> origin/main\n";
+        let output = make_output("", stderr, true);
+        assert_eq!(formatter.format(&output), "1 branch updated");
+    }
+
+    #[test]
+    fn test_submodule_updates_not_counted() {
+        let formatter = FetchFormatter;
+        let stderr = "From github.com:user/super\n   185cd59..042974a  main       -> origin/main\nFetching submodule libs/sub\nFrom github.com:user/sub\n   eed06ef..0d4e7fe  main       -> origin/main\n * [new tag]         v2.0       -> v2.0\n";
+        let output = make_output("", stderr, true);
+        assert_eq!(formatter.format(&output), "1 branch updated");
+    }
+
+    #[test]
+    fn test_submodule_only_updates_report_no_new_commits() {
+        let formatter = FetchFormatter;
+        let stderr = "Fetching submodule libs/sub\nFrom github.com:user/sub\n   eed06ef..0d4e7fe  main       -> origin/main\n";
+        let output = make_output("", stderr, true);
+        assert_eq!(formatter.format(&output), "no new commits");
+    }
+
+    #[test]
+    fn test_fetched_within_uses_fetch_head_mtime() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let repo = temp.path();
+        std::fs::create_dir(repo.join(".git")).unwrap();
+        let now = SystemTime::now();
+        let interval = Duration::from_secs(300);
+
+        // Never fetched
+        assert!(!fetched_within(repo, interval, now));
+
+        let fetch_head = std::fs::File::create(repo.join(".git/FETCH_HEAD")).unwrap();
+        fetch_head
+            .set_modified(now - Duration::from_secs(60))
+            .unwrap();
+        assert!(fetched_within(repo, interval, now));
+
+        fetch_head
+            .set_modified(now - Duration::from_secs(600))
+            .unwrap();
+        assert!(!fetched_within(repo, interval, now));
+    }
+
+    #[test]
+    fn test_update_head_ok_flag() {
+        let opts = FetchOptions {
+            update_head_ok: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            build_args(&opts, &["--prune".to_string()]),
+            vec!["fetch", "--update-head-ok", "--prune"]
+        );
+    }
+
+    #[test]
+    fn test_recurse_submodules_flag() {
+        let bare = FetchOptions {
+            recurse_submodules: Some(String::new()),
+            ..Default::default()
+        };
+        assert_eq!(
+            build_args(&bare, &[]),
+            vec!["fetch", "--recurse-submodules"]
+        );
+
+        let on_demand = FetchOptions {
+            recurse_submodules: Some("on-demand".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            build_args(&on_demand, &[]),
+            vec!["fetch", "--recurse-submodules=on-demand"]
+        );
+    }
+
+    #[test]
+    fn test_commit_graph_injects_config() {
+        let opts = FetchOptions {
+            commit_graph: true,
+            ..Default::default()
+        };
+        let cmd = GitCommand::new(PathBuf::from("/repos/app"), build_args(&opts, &[]));
+        assert_eq!(
+            cmd.command_string_with_scheme(None),
+            "git -C /repos/app -c fetch.writeCommitGraph=true fetch"
+        );
+    }
+
+    #[test]
+    fn test_per_remote_args_fan_out() {
+        let opts = FetchOptions {
+            parallel_remotes: true,
+            ..FetchOptions::default()
+        };
+        let remotes = vec!["origin".to_string(), "upstream".to_string()];
+        assert_eq!(
+            per_remote_args(&opts, &["--prune".to_string()], &remotes),
+            vec![
+                vec!["fetch", "--prune", "origin"],
+                vec!["fetch", "--prune", "upstream"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_per_remote_results_aggregate_into_one_summary() {
+        let origin = make_output(
+            "",
+            "From github.com:me/repo\n   abc123..def456  main       -> origin/main\n",
+            true,
+        );
+        let upstream = make_output(
+            "",
+            "From github.com:org/repo\n   111111..222222  main       -> upstream/main\n * [new tag]         v1.2       -> v1.2\n",
+            true,
+        );
+        let combined = combine_outputs(vec![Ok(origin), Ok(upstream)]).unwrap();
+        assert_eq!(
+            FetchFormatter.format(&combined),
+            "2 branches, 1 tag updated"
+        );
+    }
+
+    #[test]
+    fn test_per_remote_failure_reported() {
+        let origin = make_output("", "From github.com:me/repo\n", true);
+        let upstream = make_output("", "fatal: could not read from remote repository\n", false);
+        let combined = combine_outputs(vec![Ok(origin), Ok(upstream)]).unwrap();
+        assert_eq!(
+            FetchFormatter.format(&combined),
+            "fatal: could not read from remote repository"
+        );
+        assert_eq!(FetchFormatter.category(&combined), Category::Error);
+    }
+
+    #[test]
+    fn test_default_options_plain_fetch() {
+        let args = build_args(&FetchOptions::default(), &["--prune".to_string()]);
+        assert_eq!(args, vec!["fetch", "--prune"]);
+    }
+
+    #[test]
+    fn test_mirror_update_counts_refs() {
+        let stderr = "From /srv/upstream/app\n - [deleted]         (none)     -> gone\n   ee12fad..7ae2f3a  main       -> main\n * [new branch]      new        -> new\n * [new tag]         v1         -> v1\n";
+        let output = make_output("", stderr, true);
+        assert_eq!(MirrorFormatter.format(&output), "3 refs updated, 1 pruned");
+        assert_eq!(MirrorFormatter.category(&output), Category::Updated);
+
+        let current = make_output("", "", true);
+        assert_eq!(MirrorFormatter.format(&current), "up to date");
+        assert_eq!(MirrorFormatter.category(&current), Category::Clean);
+    }
+
+    #[test]
+    fn test_mirror_args() {
+        assert_eq!(
+            mirror_args(&FetchOptions::default(), &[]),
+            vec![
+                "fetch",
+                "--update-head-ok",
+                "--prune",
+                "origin",
+                "+refs/*:refs/*"
+            ]
+        );
+    }
+
     #[test]
     fn test_fallback_to_fetched() {
         let formatter = FetchFormatter;
@@ -1,4 +1,17 @@
+pub mod archive;
+pub mod bisect_run;
+pub mod clone;
+pub mod config;
+pub mod exec;
 pub mod fetch;
 pub mod passthrough;
+pub mod prune_worktrees;
 pub mod pull;
+pub mod push;
+pub mod rebase;
 pub mod status;
+pub mod switch;
+pub mod sync;
+pub mod timeline;
+pub mod unshallow;
+pub mod verify_remotes;
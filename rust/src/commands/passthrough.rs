@@ -1,45 +1,159 @@
 use anyhow::Result;
-use std::path::PathBuf;
 use std::process::Output;
 
-use crate::runner::{run_parallel, ExecutionContext, GitCommand, OutputFormatter};
+use crate::repo::Repo;
+use crate::runner::{
+    ExecutionContext, GitCommand, OutputFormatter, error_line, first_output_line, run_parallel,
+};
 
 struct PassthroughFormatter;
 
 impl OutputFormatter for PassthroughFormatter {
     fn format(&self, output: &Output) -> String {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-
         if !output.status.success() {
-            let error_line = stderr
-                .lines()
-                .find(|l| !l.trim().is_empty())
-                .unwrap_or("unknown error");
-            return format!("ERROR: {}", error_line);
+            return format!("ERROR: {}", error_line(output));
         }
 
-        stdout
-            .lines()
-            .chain(stderr.lines())
-            .find(|l| !l.trim().is_empty())
-            .unwrap_or("ok")
-            .trim()
-            .to_string()
+        first_output_line(output).unwrap_or_else(|| "ok".to_string())
+    }
+}
+
+/// Argument lists past this size (per git invocation) get a warning, since they
+/// are replicated to every repository.
+const LARGE_ARGS_BYTES: usize = 256 * 1024;
+
+/// Warning for unusually large argument lists, if `args` is large enough to need one
+fn large_args_warning(args: &[String], repo_count: usize) -> Option<String> {
+    let bytes: usize = args.iter().map(|a| a.len() + 1).sum();
+    if bytes < LARGE_ARGS_BYTES {
+        return None;
+    }
+    Some(format!(
+        "git-all: warning: passing {} KB of arguments ({} args) to each of {} repositories",
+        bytes / 1024,
+        args.len(),
+        repo_count
+    ))
+}
+
+/// Whether passthrough `args` run a git command that throws away uncommitted
+/// work: `reset`, `restore`, or `clean` with `--force`
+pub fn is_destructive(args: &[String]) -> bool {
+    let mut args = args.iter().map(String::as_str);
+    // Global options come before the subcommand; -c and -C take a value
+    let subcommand = loop {
+        match args.next() {
+            Some("-c" | "-C") => {
+                args.next();
+            }
+            Some(arg) if arg.starts_with('-') => {}
+            other => break other,
+        }
+    };
+    match subcommand {
+        Some("reset" | "restore") => true,
+        Some("clean") => args.any(|arg| {
+            arg == "--force"
+                || (arg.starts_with('-') && !arg.starts_with("--") && arg.contains('f'))
+        }),
+        _ => false,
     }
 }
 
-pub fn run(ctx: &ExecutionContext, repos: &[PathBuf], args: &[String]) -> Result<()> {
+pub fn run(ctx: &ExecutionContext, repos: &[Repo], args: &[String]) -> Result<()> {
     if args.is_empty() {
         anyhow::bail!("No git command specified");
     }
 
+    if let Some(warning) = large_args_warning(args, repos.len()) {
+        eprintln!("{}", warning);
+    }
+
     let formatter = PassthroughFormatter;
 
     run_parallel(
         ctx,
         repos,
-        |repo| GitCommand::new(repo.clone(), args.to_vec()),
+        |repo| GitCommand::new(repo.path().to_path_buf(), args.to_vec()),
         &formatter,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::merge_stderr;
+    use std::os::unix::process::ExitStatusExt;
+    use std::path::PathBuf;
+    use std::process::ExitStatus;
+
+    #[test]
+    fn test_stderr_carried_success_message() {
+        let output = Output {
+            status: ExitStatus::from_raw(0),
+            stdout: Vec::new(),
+            stderr: b"To github.com:user/repo.git\n   abc1234..def5678  main -> main\n".to_vec(),
+        };
+        assert_eq!(
+            PassthroughFormatter.format(&output),
+            "To github.com:user/repo.git"
+        );
+        assert_eq!(
+            PassthroughFormatter.format(&merge_stderr(output)),
+            "To github.com:user/repo.git"
+        );
+    }
+
+    #[test]
+    fn test_large_args_warning_threshold() {
+        let small = vec!["log".to_string(), "--oneline".to_string()];
+        assert!(large_args_warning(&small, 100).is_none());
+
+        let pathspecs: Vec<String> = (0..20_000).map(|i| format!("src/file-{i:05}.rs")).collect();
+        let warning = large_args_warning(&pathspecs, 12).expect("should warn");
+        assert!(warning.contains("20000 args"));
+        assert!(warning.contains("12 repositories"));
+    }
+
+    #[test]
+    fn test_is_destructive() {
+        let args = |s: &str| -> Vec<String> { s.split_whitespace().map(String::from).collect() };
+        for destructive in [
+            "reset --hard",
+            "reset HEAD~1",
+            "restore .",
+            "clean -f",
+            "clean -xdf",
+            "clean --force -d",
+            "-c core.quotepath=off reset --hard",
+            "-C sub --no-pager restore src",
+        ] {
+            assert!(is_destructive(&args(destructive)), "{destructive}");
+        }
+        for safe in [
+            "clean",
+            "clean -n -d",
+            "status",
+            "log --oneline",
+            "-c reset.quiet=true log",
+            "",
+        ] {
+            assert!(!is_destructive(&args(safe)), "{safe}");
+        }
+    }
+
+    #[test]
+    fn test_args_with_spaces_stay_single_args() {
+        let args = vec![
+            "commit".to_string(),
+            "-m".to_string(),
+            "two words".to_string(),
+        ];
+        let cmd = GitCommand::new(PathBuf::from("/repos/app"), args.clone());
+        assert_eq!(cmd.args, args);
+        assert!(
+            cmd.command_string_with_scheme(None)
+                .ends_with("commit -m 'two words'")
+        );
+    }
+}
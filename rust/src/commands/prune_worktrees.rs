@@ -0,0 +1,105 @@
+use anyhow::Result;
+use std::process::Output;
+
+use crate::repo::Repo;
+use crate::runner::{
+    Category, ExecutionContext, GitCommand, OutputFormatter, error_line, run_parallel,
+};
+
+/// Count "Removing worktrees/<name>: <reason>" lines from `git worktree prune -v`.
+/// git prints these on stderr; stdout is checked as well.
+fn count_pruned_worktrees(output: &str) -> usize {
+    output
+        .lines()
+        .filter(|l| l.trim_start().starts_with("Removing worktrees/"))
+        .count()
+}
+
+struct PruneWorktreesFormatter;
+
+impl PruneWorktreesFormatter {
+    fn pruned(&self, output: &Output) -> usize {
+        count_pruned_worktrees(&String::from_utf8_lossy(&output.stdout))
+            + count_pruned_worktrees(&String::from_utf8_lossy(&output.stderr))
+    }
+}
+
+impl OutputFormatter for PruneWorktreesFormatter {
+    fn format(&self, output: &Output) -> String {
+        if !output.status.success() {
+            return error_line(output);
+        }
+
+        match self.pruned(output) {
+            0 => "none".to_string(),
+            1 => "1 stale worktree pruned".to_string(),
+            n => format!("{} stale worktrees pruned", n),
+        }
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if !output.status.success() {
+            Category::Error
+        } else if self.pruned(output) == 0 {
+            Category::Clean
+        } else {
+            Category::Updated
+        }
+    }
+}
+
+/// Remove administrative files for worktrees whose directories no longer exist.
+pub fn run(ctx: &ExecutionContext, repos: &[Repo], extra_args: &[String]) -> Result<()> {
+    let formatter = PruneWorktreesFormatter;
+
+    run_parallel(
+        ctx,
+        repos,
+        |repo| {
+            // -v is needed to see what was pruned
+            let mut args = vec![
+                "worktree".to_string(),
+                "prune".to_string(),
+                "-v".to_string(),
+            ];
+            args.extend(extra_args.iter().cloned());
+            GitCommand::new(repo.path().to_path_buf(), args)
+        },
+        &formatter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn make_output(stderr: &str) -> Output {
+        Output {
+            status: ExitStatus::from_raw(0),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_count_pruned_worktrees() {
+        let stderr = "Removing worktrees/wt2: gitdir file points to non-existent location\n\
+                      Removing worktrees/wt1: gitdir file points to non-existent location\n";
+        assert_eq!(count_pruned_worktrees(stderr), 2);
+        assert_eq!(count_pruned_worktrees(""), 0);
+        assert_eq!(count_pruned_worktrees("warning: something else\n"), 0);
+    }
+
+    #[test]
+    fn test_format_reports_count_or_none() {
+        let formatter = PruneWorktreesFormatter;
+        assert_eq!(formatter.format(&make_output("")), "none");
+        assert_eq!(formatter.category(&make_output("")), Category::Clean);
+
+        let one = make_output("Removing worktrees/old: not a valid directory\n");
+        assert_eq!(formatter.format(&one), "1 stale worktree pruned");
+        assert_eq!(formatter.category(&one), Category::Updated);
+    }
+}
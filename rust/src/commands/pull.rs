@@ -1,60 +1,672 @@
 use anyhow::Result;
-use std::path::PathBuf;
 use std::process::Output;
 
-use crate::runner::{run_parallel, ExecutionContext, GitCommand, OutputFormatter};
+use crate::commands::fetch::count_pruned_refs;
+use crate::repo::Repo;
+use crate::runner::{
+    Category, CommandSettings, ExecutionContext, GitCommand, OutputFormatter, UrlRewrite,
+    error_line, run_parallel,
+};
+
+/// Options for the pull command beyond pass-through git args
+#[derive(Default, Clone)]
+pub struct PullOptions {
+    /// Prune stale remote-tracking branches while pulling
+    pub prune: bool,
+    /// When a branch has no upstream, track origin/<branch> and pull again
+    pub set_upstream_if_missing: bool,
+    /// Ask the upstream's remote for its tip first, and pull only if HEAD lacks it
+    pub only_behind: bool,
+    /// Rebase local commits onto the upstream instead of merging (`git pull --rebase`)
+    pub rebase: bool,
+}
+
+/// Stdout of an `--only-behind` check that found HEAD already has the upstream tip
+const CURRENT_MARKER: &str = "git-all: current with upstream\n";
+
+/// Reported for repos `--only-behind` did not pull
+const CURRENT: &str = "current (skipped)";
+
+/// `git for-each-ref` format naming the current branch's upstream: "<remote>\t<ref>"
+const UPSTREAM_FORMAT: &str = "--format=%(upstream:remotename)\t%(upstream:remoteref)";
+
+/// Parse the `UPSTREAM_FORMAT` line for a branch; None when it has no upstream
+fn parse_upstream(stdout: &str) -> Option<(&str, &str)> {
+    let (remote, remote_ref) = stdout.lines().next()?.split_once('\t')?;
+    (!remote.is_empty() && !remote_ref.is_empty()).then_some((remote, remote_ref))
+}
+
+/// The object id `git ls-remote` advertises for a single ref, if any
+fn parse_ls_remote_tip(stdout: &str) -> Option<&str> {
+    stdout.lines().next()?.split_whitespace().next()
+}
+
+/// Names the checked-out branch's ref; fails when HEAD is detached
+fn head_ref_args() -> Vec<String> {
+    vec![
+        "symbolic-ref".to_string(),
+        "--quiet".to_string(),
+        "HEAD".to_string(),
+    ]
+}
+
+/// Given the branch `head_ref_args` found in `output`, check whether HEAD
+/// already contains the tip of its upstream branch on the remote. If it does,
+/// report the repo as current without pulling; otherwise (or when the branch
+/// or its upstream is unknown, so git can explain) run `pull_args`. A remote
+/// that cannot be reached is reported as the failure.
+fn pull_if_behind(
+    repo: &Repo,
+    settings: &CommandSettings,
+    output: Output,
+    pull_args: Vec<String>,
+    url_rewrite: Option<&UrlRewrite>,
+) -> std::io::Result<Output> {
+    let pull = || settings.command(repo, pull_args.clone()).run(url_rewrite);
+    if !output.status.success() {
+        return pull();
+    }
+    let head_ref = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let lookup = vec![
+        "for-each-ref".to_string(),
+        UPSTREAM_FORMAT.to_string(),
+        head_ref,
+    ];
+    let lookup = settings.command(repo, lookup).run(None)?;
+    let stdout = String::from_utf8_lossy(&lookup.stdout).into_owned();
+    let upstream = if lookup.status.success() {
+        parse_upstream(&stdout)
+    } else {
+        None
+    };
+    let Some((remote, remote_ref)) = upstream else {
+        return pull();
+    };
+
+    let ls_remote = vec![
+        "ls-remote".to_string(),
+        remote.to_string(),
+        remote_ref.to_string(),
+    ];
+    let advertised = settings.command(repo, ls_remote).run(url_rewrite)?;
+    if !advertised.status.success() {
+        return Ok(advertised);
+    }
+    let advertised_stdout = String::from_utf8_lossy(&advertised.stdout);
+    let Some(tip) = parse_ls_remote_tip(&advertised_stdout) else {
+        return pull();
+    };
+
+    // Fails when the tip is missing locally or not yet merged into HEAD
+    let contains = vec![
+        "merge-base".to_string(),
+        "--is-ancestor".to_string(),
+        tip.to_string(),
+        "HEAD".to_string(),
+    ];
+    if settings.command(repo, contains).run(None)?.status.success() {
+        return Ok(Output {
+            stdout: CURRENT_MARKER.as_bytes().to_vec(),
+            stderr: Vec::new(),
+            ..output
+        });
+    }
+    pull()
+}
+
+/// Prepended to stdout when a pull was retried after setting the upstream
+const UPSTREAM_SET_MARKER: &str = "git-all: upstream set to ";
+
+/// `git pull` on a branch with no upstream configured
+pub(crate) fn is_no_tracking_error(stderr: &str) -> bool {
+    stderr.contains("There is no tracking information for the current branch")
+}
+
+/// If `output` is a pull that failed only because the branch has no upstream,
+/// set the upstream to `origin/<branch>` (when that remote branch exists) and
+/// pull again. Any other result is returned unchanged.
+fn set_upstream_and_retry(
+    repo: &Repo,
+    settings: &CommandSettings,
+    output: Output,
+    pull_args: Vec<String>,
+    url_rewrite: Option<&UrlRewrite>,
+) -> std::io::Result<Output> {
+    if output.status.success() || !is_no_tracking_error(&String::from_utf8_lossy(&output.stderr)) {
+        return Ok(output);
+    }
+    let Some(branch) = repo.current_branch() else {
+        return Ok(output);
+    };
+
+    let upstream = format!("origin/{}", branch);
+    let verify = vec![
+        "rev-parse".to_string(),
+        "--verify".to_string(),
+        "--quiet".to_string(),
+        format!("refs/remotes/{}", upstream),
+    ];
+    if !settings.command(repo, verify).run(None)?.status.success() {
+        return Ok(output);
+    }
+
+    let set_upstream = vec![
+        "branch".to_string(),
+        format!("--set-upstream-to={}", upstream),
+    ];
+    let set = settings.command(repo, set_upstream).run(None)?;
+    if !set.status.success() {
+        return Ok(set);
+    }
+
+    let mut retried = settings.command(repo, pull_args).run(url_rewrite)?;
+    let mut stdout = format!("{}{}\n", UPSTREAM_SET_MARKER, upstream).into_bytes();
+    stdout.append(&mut retried.stdout);
+    retried.stdout = stdout;
+    Ok(retried)
+}
+
+/// Printed when `pull --autostash` could not re-apply local changes. git exits 0
+/// and leaves conflict markers in the worktree, with the changes still stashed.
+const AUTOSTASH_CONFLICT_MESSAGE: &str = "Applying autostash resulted in conflicts";
+
+fn is_autostash_conflict(output: &Output) -> bool {
+    String::from_utf8_lossy(&output.stderr).contains(AUTOSTASH_CONFLICT_MESSAGE)
+        || String::from_utf8_lossy(&output.stdout).contains(AUTOSTASH_CONFLICT_MESSAGE)
+}
+
+/// Prepended to stdout after a successful rebase: "git-all: rebased onto <upstream>\n"
+const REBASED_MARKER: &str = "git-all: rebased onto ";
+
+/// Printed by `git rebase` once every local commit has been replayed
+fn is_rebased(output: &Output) -> bool {
+    String::from_utf8_lossy(&output.stderr).contains("Successfully rebased")
+        || String::from_utf8_lossy(&output.stdout).contains("Successfully rebased")
+}
+
+/// A rebase stopped on a conflicting commit. A conflicted merge also prints
+/// CONFLICT lines, but never "could not apply".
+fn is_rebase_conflict(output: &Output) -> bool {
+    String::from_utf8_lossy(&output.stdout).contains("CONFLICT")
+        && String::from_utf8_lossy(&output.stderr).contains("could not apply")
+}
+
+/// `git pull --rebase` says "Current branch main is up to date." instead
+fn is_up_to_date(stdout: &str) -> bool {
+    stdout.contains("Already up to date") || stdout.contains(" is up to date.")
+}
+
+/// If `output` is a pull that rebased, prepend the upstream it rebased onto.
+/// Rebase mode may come from `--rebase`, trailing args or `pull.rebase`, so
+/// this goes by what git printed rather than how it was asked.
+fn note_rebase_onto(
+    repo: &Repo,
+    settings: &CommandSettings,
+    mut output: Output,
+) -> std::io::Result<Output> {
+    if !output.status.success() || !is_rebased(&output) {
+        return Ok(output);
+    }
+    let lookup = vec![
+        "rev-parse".to_string(),
+        "--abbrev-ref".to_string(),
+        "--symbolic-full-name".to_string(),
+        "@{upstream}".to_string(),
+    ];
+    let upstream = settings.command(repo, lookup).run(None)?;
+    if upstream.status.success() {
+        let upstream = String::from_utf8_lossy(&upstream.stdout).trim().to_string();
+        let mut stdout = format!("{}{}\n", REBASED_MARKER, upstream).into_bytes();
+        stdout.append(&mut output.stdout);
+        output.stdout = stdout;
+    }
+    Ok(output)
+}
 
 struct PullFormatter;
 
 impl OutputFormatter for PullFormatter {
     fn format(&self, output: &Output) -> String {
+        self.format_for_width(output, None)
+    }
+
+    /// Narrow terminals keep only the leading clause, e.g. "3 files changed"
+    fn format_for_width(&self, output: &Output, width: Option<usize>) -> String {
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
 
+        if output.status.success() && stdout == CURRENT_MARKER {
+            return CURRENT.to_string();
+        }
+        if is_autostash_conflict(output) {
+            return "AUTOSTASH CONFLICT - resolve, then git stash drop (changes are still stashed)"
+                .to_string();
+        }
+        if is_rebase_conflict(output) {
+            return "rebase conflict".to_string();
+        }
         if !output.status.success() {
-            return stderr.lines().next().unwrap_or("unknown error").to_string();
+            return error_line(output);
         }
 
-        // Check for "Already up to date"
-        if stdout.contains("Already up to date") {
-            return "Already up to date".to_string();
+        let upstream_set = stdout
+            .lines()
+            .any(|line| line.starts_with(UPSTREAM_SET_MARKER));
+        let summary = if is_rebased(output) {
+            match stdout
+                .lines()
+                .find_map(|line| line.strip_prefix(REBASED_MARKER))
+            {
+                Some(upstream) => format!("rebased onto {}", upstream),
+                None => "rebased".to_string(),
+            }
+        } else {
+            summarize(&stdout, &stderr)
+        };
+        let pruned = count_pruned_refs(&stdout) + count_pruned_refs(&stderr);
+        let mut full = if pruned > 0 {
+            format!("{}, {} pruned", summary, pruned)
+        } else {
+            summary
+        };
+        if upstream_set {
+            full = format!("set upstream and pulled: {}", full);
         }
 
-        // Try to extract summary from stdout (e.g., "3 files changed, 10 insertions(+), 5 deletions(-)")
-        if let Some(summary_line) = stdout.lines().find(|l| l.contains("files changed")) {
-            return summary_line.trim().to_string();
+        match width {
+            Some(width) if full.len() > width => {
+                full.split(", ").next().unwrap_or(&full).to_string()
+            }
+            _ => full,
         }
+    }
 
-        // Check for fast-forward or merge info in stdout
-        if let Some(line) = stdout
-            .lines()
-            .find(|l| l.contains("..") || l.contains("Updating"))
-        {
-            return line.trim().to_string();
+    fn category(&self, output: &Output) -> Category {
+        if !output.status.success() || is_autostash_conflict(output) {
+            Category::Error
+        } else if output.stdout == CURRENT_MARKER.as_bytes() {
+            Category::Skipped
+        } else if is_up_to_date(&String::from_utf8_lossy(&output.stdout)) {
+            Category::Clean
+        } else {
+            Category::Updated
         }
+    }
+}
 
-        // Fallback: first non-empty line of stdout, or stderr
-        stdout
-            .lines()
-            .chain(stderr.lines())
-            .find(|l| !l.trim().is_empty())
-            .unwrap_or("completed")
-            .trim()
-            .to_string()
+/// Summarize merge/fast-forward output, ignoring any prune lines
+fn summarize(stdout: &str, stderr: &str) -> String {
+    // Check for "Already up to date"
+    if is_up_to_date(stdout) {
+        return "Already up to date".to_string();
     }
+
+    // Try to extract summary from stdout (e.g., "3 files changed, 10 insertions(+), 5 deletions(-)")
+    if let Some(summary_line) = stdout
+        .lines()
+        .find(|l| l.contains("files changed") || l.contains("file changed"))
+    {
+        return summary_line.trim().to_string();
+    }
+
+    // Check for fast-forward or merge info in stdout
+    if let Some(line) = stdout
+        .lines()
+        .find(|l| l.contains("..") || l.contains("Updating"))
+    {
+        return line.trim().to_string();
+    }
+
+    // Fallback: first non-empty line of stdout, or stderr
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .find(|l| {
+            !l.trim().is_empty() && !l.contains("[deleted]") && !l.starts_with(UPSTREAM_SET_MARKER)
+        })
+        .unwrap_or("completed")
+        .trim()
+        .to_string()
+}
+
+fn build_args(opts: &PullOptions, extra_args: &[String]) -> Vec<String> {
+    let mut args = vec!["pull".to_string()];
+    if opts.prune {
+        args.push("--prune".to_string());
+    }
+    if opts.rebase {
+        args.push("--rebase".to_string());
+    }
+    args.extend(extra_args.iter().cloned());
+    args
 }
 
-pub fn run(ctx: &ExecutionContext, repos: &[PathBuf], extra_args: &[String]) -> Result<()> {
+pub fn run(
+    ctx: &ExecutionContext,
+    repos: &[Repo],
+    opts: &PullOptions,
+    extra_args: &[String],
+) -> Result<()> {
     let formatter = PullFormatter;
+    let url_rewrite = ctx.url_rewrite();
+    let settings = ctx.command_settings();
 
     run_parallel(
         ctx,
         repos,
         |repo| {
-            let mut args = vec!["pull".to_string()];
-            args.extend(extra_args.iter().cloned());
-            GitCommand::new(repo.clone(), args)
+            let args = build_args(opts, extra_args);
+            // --only-behind starts by naming the checked-out branch; the
+            // followup looks up its upstream and decides whether to pull
+            let mut cmd = if opts.only_behind {
+                GitCommand::new(repo.path().to_path_buf(), head_ref_args())
+                    .with_planned(args.clone())
+            } else {
+                GitCommand::new(repo.path().to_path_buf(), args.clone())
+            };
+            // Concurrent pulls into one object store (worktrees, alternates) can race
+            if let Some(store) = repo.object_store() {
+                cmd = cmd.with_lock_group(store.to_path_buf());
+            }
+            let gated = opts.only_behind;
+            let repo = repo.clone();
+            let set_upstream = opts.set_upstream_if_missing;
+            let settings = settings.clone();
+            let url_rewrite = url_rewrite.clone();
+            cmd.with_followup(Box::new(move |mut output| {
+                let url_rewrite = url_rewrite.as_ref();
+                if gated {
+                    output = pull_if_behind(&repo, &settings, output, args.clone(), url_rewrite)?;
+                }
+                if set_upstream {
+                    output = set_upstream_and_retry(&repo, &settings, output, args, url_rewrite)?;
+                }
+                note_rebase_onto(&repo, &settings, output)
+            }))
         },
         &formatter,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::tests::git;
+    use std::os::unix::process::ExitStatusExt;
+    use std::path::{Path, PathBuf};
+    use std::process::ExitStatus;
+
+    fn make_output(stdout: &str, stderr: &str, success: bool) -> Output {
+        Output {
+            status: ExitStatus::from_raw(if success { 0 } else { 256 }),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    const FAST_FORWARD: &str = "Updating abc123..def456\nFast-forward\n src/lib.rs | 2 +-\n 1 file changed, 1 insertion(+), 1 deletion(-)\n";
+
+    #[test]
+    fn test_already_up_to_date() {
+        let output = make_output("Already up to date.\n", "", true);
+        assert_eq!(PullFormatter.format(&output), "Already up to date");
+    }
+
+    #[test]
+    fn test_autostash_conflict_flagged() {
+        let stdout = "Updating 3b7d4f1..42ef2a4\nCreated autostash: a08b0d0\nFast-forward\n f | 2 +-\n 1 file changed, 1 insertion(+), 1 deletion(-)\n";
+        let stderr = "From /tmp/up\n   3b7d4f1..42ef2a4  master     -> origin/master\nApplying autostash resulted in conflicts.\nYour changes are safe in the stash.\nYou can run \"git stash pop\" or \"git stash drop\" at any time.\n";
+        // git exits 0 here; the conflict must still be reported as a failure
+        let output = make_output(stdout, stderr, true);
+        assert!(
+            PullFormatter
+                .format(&output)
+                .starts_with("AUTOSTASH CONFLICT")
+        );
+        assert_eq!(PullFormatter.category(&output), Category::Error);
+    }
+
+    #[test]
+    fn test_prune_with_fast_forward() {
+        let stderr = "From github.com:user/repo\n - [deleted]         (none)     -> origin/old\n - [deleted]         (none)     -> origin/older\n";
+        let output = make_output(FAST_FORWARD, stderr, true);
+        assert_eq!(
+            PullFormatter.format(&output),
+            "1 file changed, 1 insertion(+), 1 deletion(-), 2 pruned"
+        );
+    }
+
+    #[test]
+    fn test_prune_only_output() {
+        let stderr = " - [deleted]         (none)     -> origin/old\n";
+        let output = make_output("", stderr, true);
+        assert_eq!(PullFormatter.format(&output), "completed, 1 pruned");
+    }
+
+    #[test]
+    fn test_format_for_width_drops_detail_when_narrow() {
+        let output = make_output(FAST_FORWARD, "", true);
+        assert_eq!(
+            PullFormatter.format_for_width(&output, Some(80)),
+            "1 file changed, 1 insertion(+), 1 deletion(-)"
+        );
+        assert_eq!(
+            PullFormatter.format_for_width(&output, Some(20)),
+            "1 file changed"
+        );
+    }
+
+    #[test]
+    fn test_detects_no_tracking_error() {
+        let stderr = "There is no tracking information for the current branch.\nPlease specify which branch you want to merge with.\n";
+        assert!(is_no_tracking_error(stderr));
+        assert!(!is_no_tracking_error(
+            "fatal: couldn't find remote ref main\n"
+        ));
+    }
+
+    #[test]
+    fn test_upstream_set_summary() {
+        let stdout = format!("{}origin/feature\n{}", UPSTREAM_SET_MARKER, FAST_FORWARD);
+        let output = make_output(&stdout, "", true);
+        assert_eq!(
+            PullFormatter.format(&output),
+            "set upstream and pulled: 1 file changed, 1 insertion(+), 1 deletion(-)"
+        );
+    }
+
+    /// A clone `work` of a bare remote that `seed` has pushed one commit to
+    /// on `main`, for tests that move the upstream on by pushing from `seed`.
+    /// Returns `(seed, work)`.
+    fn seeded_clone(root: &Path) -> (PathBuf, PathBuf) {
+        let remote = root.join("remote.git");
+        let seed = root.join("seed");
+        let work = root.join("work");
+        git(
+            root,
+            &[
+                "init",
+                "-q",
+                "--bare",
+                "-b",
+                "main",
+                remote.to_str().unwrap(),
+            ],
+        );
+        git(root, &["init", "-q", "-b", "main", seed.to_str().unwrap()]);
+        git(&seed, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(
+            &seed,
+            &["remote", "add", "origin", remote.to_str().unwrap()],
+        );
+        git(&seed, &["push", "-q", "origin", "main"]);
+        git(
+            root,
+            &[
+                "clone",
+                "-q",
+                remote.to_str().unwrap(),
+                work.to_str().unwrap(),
+            ],
+        );
+        (seed, work)
+    }
+
+    #[test]
+    fn test_set_upstream_and_retry_on_untracked_branch() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let remote = temp.path().join("remote.git");
+        let work = temp.path().join("work");
+        git(
+            temp.path(),
+            &["init", "-q", "--bare", remote.to_str().unwrap()],
+        );
+        git(
+            temp.path(),
+            &[
+                "clone",
+                "-q",
+                remote.to_str().unwrap(),
+                work.to_str().unwrap(),
+            ],
+        );
+        git(&work, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(&work, &["checkout", "-q", "-b", "feature"]);
+        git(&work, &["push", "-q", "origin", "feature"]);
+
+        let pull_args = vec!["pull".to_string()];
+        let first = GitCommand::new(work.clone(), pull_args.clone())
+            .run(None)
+            .unwrap();
+        assert!(!first.status.success());
+
+        let settings = CommandSettings::default();
+        let retried =
+            set_upstream_and_retry(&Repo::new(work), &settings, first, pull_args, None).unwrap();
+        assert!(retried.status.success());
+        assert!(
+            PullFormatter
+                .format(&retried)
+                .starts_with("set upstream and pulled")
+        );
+    }
+
+    #[test]
+    fn test_parse_upstream() {
+        assert_eq!(
+            parse_upstream("origin\trefs/heads/main\n"),
+            Some(("origin", "refs/heads/main"))
+        );
+        // A branch without an upstream prints empty fields
+        assert_eq!(parse_upstream("\t\n"), None);
+        assert_eq!(parse_upstream(""), None);
+        assert_eq!(
+            parse_ls_remote_tip("42ef2a4\trefs/heads/main\n"),
+            Some("42ef2a4")
+        );
+        assert_eq!(parse_ls_remote_tip(""), None);
+    }
+
+    #[test]
+    fn test_current_reported_as_skipped() {
+        let output = make_output(CURRENT_MARKER, "", true);
+        assert_eq!(PullFormatter.format(&output), "current (skipped)");
+        assert_eq!(PullFormatter.category(&output), Category::Skipped);
+    }
+
+    #[test]
+    fn test_pull_if_behind_gates_on_upstream_tip() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let (seed, work) = seeded_clone(temp.path());
+
+        let check = || {
+            let output = GitCommand::new(work.clone(), head_ref_args())
+                .run(None)
+                .unwrap();
+            let settings = CommandSettings::default();
+            let pull_args = vec!["pull".to_string()];
+            pull_if_behind(&Repo::new(work.clone()), &settings, output, pull_args, None).unwrap()
+        };
+        assert_eq!(PullFormatter.format(&check()), "current (skipped)");
+
+        git(&seed, &["commit", "-q", "--allow-empty", "-m", "upstream"]);
+        git(&seed, &["push", "-q", "origin", "main"]);
+        let pulled = check();
+        assert!(pulled.status.success());
+        assert_eq!(PullFormatter.category(&pulled), Category::Updated);
+        assert_eq!(PullFormatter.format(&check()), "current (skipped)");
+
+        // Local commits the remote lacks don't make the repo behind
+        git(&work, &["commit", "-q", "--allow-empty", "-m", "local"]);
+        assert_eq!(PullFormatter.category(&check()), Category::Skipped);
+    }
+
+    #[test]
+    fn test_rebase_results() {
+        let stdout = format!("{}origin/main\n", REBASED_MARKER);
+        let rebased = make_output(
+            &stdout,
+            "Successfully rebased and updated refs/heads/main.\n",
+            true,
+        );
+        assert_eq!(PullFormatter.format(&rebased), "rebased onto origin/main");
+        assert_eq!(PullFormatter.category(&rebased), Category::Updated);
+
+        let current = make_output("Current branch main is up to date.\n", "", true);
+        assert_eq!(PullFormatter.format(&current), "Already up to date");
+        assert_eq!(PullFormatter.category(&current), Category::Clean);
+
+        let conflict = make_output(
+            "Auto-merging f\nCONFLICT (content): Merge conflict in f\n",
+            "error: could not apply 010c3c8... w\nhint: Resolve all conflicts manually\n",
+            false,
+        );
+        assert_eq!(PullFormatter.format(&conflict), "rebase conflict");
+        assert_eq!(PullFormatter.category(&conflict), Category::Error);
+
+        // A conflicted merge is not reported as a rebase
+        let merge = make_output(
+            "Auto-merging f\nCONFLICT (content): Merge conflict in f\n",
+            "Automatic merge failed; fix conflicts and then commit the result.\n",
+            false,
+        );
+        assert_ne!(PullFormatter.format(&merge), "rebase conflict");
+    }
+
+    #[test]
+    fn test_rebase_from_trailing_args_notes_upstream() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let (seed, work) = seeded_clone(temp.path());
+        git(&seed, &["commit", "-q", "--allow-empty", "-m", "upstream"]);
+        git(&seed, &["push", "-q", "origin", "main"]);
+        git(&work, &["commit", "-q", "--allow-empty", "-m", "local"]);
+
+        // --rebase passed by hand, not through PullOptions
+        let args = build_args(&PullOptions::default(), &["--rebase".to_string()]);
+        let pulled = GitCommand::new(work.clone(), args)
+            .with_env("GIT_COMMITTER_NAME", "test")
+            .with_env("GIT_COMMITTER_EMAIL", "test@example.com")
+            .run(None)
+            .unwrap();
+        let settings = CommandSettings::default();
+        let output = note_rebase_onto(&Repo::new(work), &settings, pulled).unwrap();
+        assert_eq!(PullFormatter.format(&output), "rebased onto origin/main");
+    }
+
+    #[test]
+    fn test_prune_flag_injected() {
+        let opts = PullOptions {
+            prune: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            build_args(&opts, &["--ff-only".to_string()]),
+            vec!["pull", "--prune", "--ff-only"]
+        );
+        let opts = PullOptions {
+            rebase: true,
+            ..Default::default()
+        };
+        assert_eq!(build_args(&opts, &[]), vec!["pull", "--rebase"]);
+    }
+}
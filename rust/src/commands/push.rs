@@ -0,0 +1,209 @@
+use anyhow::Result;
+use std::path::Path;
+use std::process::Output;
+
+use crate::repo::Repo;
+use crate::runner::{
+    Category, CommandSettings, ExecutionContext, GitCommand, OutputFormatter, UrlRewrite,
+    error_line, run_parallel,
+};
+
+/// Reported for repos whose branch has no commits its upstream lacks
+const NOTHING_TO_PUSH: &str = "nothing to push";
+
+/// Reported for repos whose branch has no upstream (or HEAD is detached)
+const NO_UPSTREAM: &str = "(no upstream)";
+
+/// Prepended to a push's stdout: "git-all: pushing <count>\n"
+const PUSHING_MARKER: &str = "git-all: pushing ";
+
+/// Counts the commits on HEAD that its upstream lacks
+fn ahead_args() -> Vec<String> {
+    vec![
+        "rev-list".to_string(),
+        "--count".to_string(),
+        "@{upstream}..HEAD".to_string(),
+    ]
+}
+
+fn push_args(extra_args: &[String]) -> Vec<String> {
+    let mut args = vec!["push".to_string()];
+    args.extend(extra_args.iter().cloned());
+    args
+}
+
+/// The count from a successful `ahead_args` run
+fn commits_ahead(output: &Output) -> Option<usize> {
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+fn is_no_upstream_error(stderr: &str) -> bool {
+    stderr.contains("no upstream configured") || stderr.contains("HEAD does not point to a branch")
+}
+
+/// Given the ahead count in `output`, push only if there is something to
+/// push. The push's stdout is prefixed with the count for the formatter.
+fn push_if_ahead(
+    repo: &Path,
+    settings: &CommandSettings,
+    output: Output,
+    push_args: Vec<String>,
+    url_rewrite: Option<&UrlRewrite>,
+) -> std::io::Result<Output> {
+    match commits_ahead(&output) {
+        None | Some(0) => Ok(output),
+        Some(count) => {
+            let mut pushed = settings.command(repo, push_args).run(url_rewrite)?;
+            let mut stdout = format!("{}{}\n", PUSHING_MARKER, count).into_bytes();
+            stdout.append(&mut pushed.stdout);
+            pushed.stdout = stdout;
+            Ok(pushed)
+        }
+    }
+}
+
+/// The ref git refused ("! [rejected] main -> main (fetch first)"), which
+/// says more than the "To <remote>" line git prints first
+fn push_error(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .find(|line| line.trim_start().starts_with("! "))
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_else(|| error_line(output))
+}
+
+struct PushFormatter;
+
+impl PushFormatter {
+    /// Commits a push sent, from its marker; None when nothing was pushed
+    fn pushed(output: &Output) -> Option<usize> {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .lines()
+            .next()?
+            .strip_prefix(PUSHING_MARKER)?
+            .parse()
+            .ok()
+    }
+}
+
+impl OutputFormatter for PushFormatter {
+    fn format(&self, output: &Output) -> String {
+        if !output.status.success() {
+            if is_no_upstream_error(&String::from_utf8_lossy(&output.stderr)) {
+                return NO_UPSTREAM.to_string();
+            }
+            return push_error(output);
+        }
+        match Self::pushed(output) {
+            Some(1) => "pushed 1 commit".to_string(),
+            Some(count) => format!("pushed {} commits", count),
+            None => NOTHING_TO_PUSH.to_string(),
+        }
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if !output.status.success() {
+            if is_no_upstream_error(&String::from_utf8_lossy(&output.stderr)) {
+                return Category::Skipped;
+            }
+            return Category::Error;
+        }
+        match Self::pushed(output) {
+            Some(_) => Category::Updated,
+            None => Category::Clean,
+        }
+    }
+}
+
+/// Push each repo's current branch to its upstream, touching only repos with
+/// commits the upstream lacks. A dry run lists the push each repo would get
+/// if it turns out to be ahead.
+pub fn run(ctx: &ExecutionContext, repos: &[Repo], extra_args: &[String]) -> Result<()> {
+    let url_rewrite = ctx.url_rewrite();
+    let settings = ctx.command_settings();
+
+    run_parallel(
+        ctx,
+        repos,
+        |repo| {
+            let path = repo.path().to_path_buf();
+            let push = push_args(extra_args);
+            let settings = settings.clone();
+            let url_rewrite = url_rewrite.clone();
+            GitCommand::new(path.clone(), ahead_args())
+                .with_planned(push.clone())
+                .with_followup(Box::new(move |output| {
+                    push_if_ahead(&path, &settings, output, push, url_rewrite.as_ref())
+                }))
+        },
+        &PushFormatter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn make_output(stdout: &str, stderr: &str, code: i32) -> Output {
+        Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_pushed_commits() {
+        let output = make_output(&format!("{}3\n", PUSHING_MARKER), "", 0);
+        assert_eq!(PushFormatter.format(&output), "pushed 3 commits");
+        assert_eq!(PushFormatter.category(&output), Category::Updated);
+
+        let single = make_output(&format!("{}1\n", PUSHING_MARKER), "", 0);
+        assert_eq!(PushFormatter.format(&single), "pushed 1 commit");
+    }
+
+    #[test]
+    fn test_nothing_to_push() {
+        // The ahead check's own output, reported when no push ran
+        let output = make_output("0\n", "", 0);
+        assert_eq!(commits_ahead(&output), Some(0));
+        assert_eq!(PushFormatter.format(&output), "nothing to push");
+        assert_eq!(PushFormatter.category(&output), Category::Clean);
+    }
+
+    #[test]
+    fn test_no_upstream_skipped() {
+        let output = make_output(
+            "",
+            "fatal: no upstream configured for branch 'feature'\n",
+            128,
+        );
+        assert_eq!(PushFormatter.format(&output), "(no upstream)");
+        assert_eq!(PushFormatter.category(&output), Category::Skipped);
+    }
+
+    #[test]
+    fn test_rejected_push_is_error() {
+        let stderr = "To github.com:org/api.git\n ! [rejected]        main -> main (fetch first)\nerror: failed to push some refs to 'github.com:org/api.git'\n";
+        let output = make_output(&format!("{}2\n", PUSHING_MARKER), stderr, 1);
+        assert_eq!(
+            PushFormatter.format(&output),
+            "! [rejected] main -> main (fetch first)"
+        );
+        assert_eq!(PushFormatter.category(&output), Category::Error);
+    }
+
+    #[test]
+    fn test_push_args_pass_through() {
+        assert_eq!(
+            push_args(&["--force-with-lease".to_string()]),
+            vec!["push", "--force-with-lease"]
+        );
+    }
+}
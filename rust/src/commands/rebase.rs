@@ -0,0 +1,112 @@
+use anyhow::Result;
+use std::process::Output;
+
+use crate::repo::Repo;
+use crate::runner::{
+    Category, ExecutionContext, GitCommand, OutputFormatter, error_line, run_parallel,
+};
+
+/// A rebase that stopped on conflicts leaves the repo mid-rebase.
+pub(crate) fn is_rebase_conflict(stdout: &str, stderr: &str) -> bool {
+    stdout.contains("CONFLICT") || stderr.contains("CONFLICT") || stderr.contains("could not apply")
+}
+
+struct RebaseFormatter;
+
+impl OutputFormatter for RebaseFormatter {
+    fn format(&self, output: &Output) -> String {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if is_rebase_conflict(&stdout, &stderr) {
+            return "CONFLICT (stopped) - resolve, then git rebase --continue or --abort"
+                .to_string();
+        }
+
+        if !output.status.success() {
+            return error_line(output);
+        }
+
+        if stdout.contains("is up to date") || stderr.contains("is up to date") {
+            return "already up to date".to_string();
+        }
+
+        "rebased".to_string()
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        match self.format(output).as_str() {
+            "rebased" => Category::Updated,
+            "already up to date" => Category::Clean,
+            _ => Category::Error,
+        }
+    }
+}
+
+pub fn run(ctx: &ExecutionContext, repos: &[Repo], extra_args: &[String]) -> Result<()> {
+    let formatter = RebaseFormatter;
+
+    run_parallel(
+        ctx,
+        repos,
+        |repo| {
+            let mut args = vec!["rebase".to_string()];
+            args.extend(extra_args.iter().cloned());
+            GitCommand::new(repo.path().to_path_buf(), args)
+        },
+        &formatter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn make_output(stdout: &str, stderr: &str, success: bool) -> Output {
+        Output {
+            status: ExitStatus::from_raw(if success { 0 } else { 256 }),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_successful_rebase() {
+        let output = make_output(
+            "",
+            "Successfully rebased and updated refs/heads/feature.\n",
+            true,
+        );
+        assert_eq!(RebaseFormatter.format(&output), "rebased");
+    }
+
+    #[test]
+    fn test_already_up_to_date() {
+        let output = make_output("Current branch feature is up to date.\n", "", true);
+        assert_eq!(RebaseFormatter.format(&output), "already up to date");
+    }
+
+    #[test]
+    fn test_conflict_is_flagged() {
+        let stdout = "Auto-merging src/lib.rs\nCONFLICT (content): Merge conflict in src/lib.rs\n";
+        let stderr =
+            "error: could not apply abc1234... Change lib\nhint: Resolve all conflicts manually\n";
+        let output = make_output(stdout, stderr, false);
+        assert!(
+            RebaseFormatter
+                .format(&output)
+                .starts_with("CONFLICT (stopped)")
+        );
+    }
+
+    #[test]
+    fn test_other_error_returns_first_line() {
+        let output = make_output("", "fatal: invalid upstream 'nope'\n", false);
+        assert_eq!(
+            RebaseFormatter.format(&output),
+            "fatal: invalid upstream 'nope'"
+        );
+    }
+}
@@ -1,27 +1,42 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::Path;
 use std::process::Output;
 
-use crate::runner::{run_parallel, ExecutionContext, GitCommand, OutputFormatter};
+use crate::repo::{Repo, glob_matches, head_branch};
+use crate::runner::{
+    Category, CommandSettings, ExecutionContext, GitCommand, OutputFormatter, error_line,
+    run_parallel,
+};
 
-struct StatusFormatter;
-
-impl OutputFormatter for StatusFormatter {
-    fn format(&self, output: &Output) -> String {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+/// Which side of each porcelain line `status` counts
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeScope {
+    /// Each changed path once, by its staged status if it has one
+    #[default]
+    All,
+    /// Only index changes (the first porcelain column)
+    Staged,
+    /// Only worktree changes (the second column) and untracked files
+    Unstaged,
+}
 
-        if !output.status.success() {
-            return stderr.lines().next().unwrap_or("unknown error").to_string();
-        }
+/// Per-category file counts parsed from v1-style porcelain entries (`XY path`)
+#[derive(Debug, Default, PartialEq, Eq)]
+struct StatusCounts {
+    modified: usize,
+    added: usize,
+    deleted: usize,
+    renamed: usize,
+    copied: usize,
+    untracked: usize,
+    conflicted: usize,
+}
 
-        let mut modified = 0;
-        let mut added = 0;
-        let mut deleted = 0;
-        let mut untracked = 0;
-        let mut renamed = 0;
+impl StatusCounts {
+    fn parse<'a>(entries: impl IntoIterator<Item = &'a str>, scope: ChangeScope) -> Self {
+        let mut counts = StatusCounts::default();
 
-        for line in stdout.lines() {
+        for line in entries {
             if line.len() < 2 {
                 continue;
             }
@@ -30,66 +45,1307 @@ impl OutputFormatter for StatusFormatter {
             let worktree_status = line.chars().nth(1).unwrap_or(' ');
 
             if index_status == '?' {
-                untracked += 1;
+                if scope != ChangeScope::Staged {
+                    counts.untracked += 1;
+                }
                 continue;
             }
 
-            match index_status {
-                'M' => modified += 1,
-                'A' => added += 1,
-                'D' => deleted += 1,
-                'R' => renamed += 1,
-                _ => {}
+            // Unmerged paths: either side 'U', or both sides added/deleted
+            if index_status == 'U'
+                || worktree_status == 'U'
+                || (index_status == 'A' && worktree_status == 'A')
+                || (index_status == 'D' && worktree_status == 'D')
+            {
+                counts.conflicted += 1;
+                continue;
             }
 
-            // Check worktree status (unstaged changes) - only if not already counted
-            if index_status == ' ' {
-                match worktree_status {
-                    'M' => modified += 1,
-                    'D' => deleted += 1,
-                    _ => {}
-                }
+            match scope {
+                ChangeScope::All if index_status == ' ' => counts.tally(worktree_status),
+                ChangeScope::All | ChangeScope::Staged => counts.tally(index_status),
+                ChangeScope::Unstaged => counts.tally(worktree_status),
             }
         }
 
-        if modified == 0 && added == 0 && deleted == 0 && untracked == 0 && renamed == 0 {
+        counts
+    }
+
+    /// Count one porcelain status letter; blanks and others are ignored
+    fn tally(&mut self, status: char) {
+        match status {
+            'M' => self.modified += 1,
+            'A' => self.added += 1,
+            'D' => self.deleted += 1,
+            'R' => self.renamed += 1,
+            'C' => self.copied += 1,
+            _ => {}
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.modified
+            + self.added
+            + self.deleted
+            + self.renamed
+            + self.copied
+            + self.untracked
+            + self.conflicted
+    }
+
+    fn is_clean(&self) -> bool {
+        self.total() == 0
+    }
+
+    fn summary(&self) -> String {
+        if self.is_clean() {
             return "clean".to_string();
         }
 
         let mut parts = Vec::new();
 
-        if modified > 0 {
-            parts.push(format!("{} modified", modified));
+        if self.conflicted > 0 {
+            parts.push(format!("{} conflicted", self.conflicted));
+        }
+        if self.modified > 0 {
+            parts.push(format!("{} modified", self.modified));
         }
-        if added > 0 {
-            parts.push(format!("{} added", added));
+        if self.added > 0 {
+            parts.push(format!("{} added", self.added));
         }
-        if deleted > 0 {
-            parts.push(format!("{} deleted", deleted));
+        if self.deleted > 0 {
+            parts.push(format!("{} deleted", self.deleted));
         }
-        if renamed > 0 {
-            parts.push(format!("{} renamed", renamed));
+        if self.renamed > 0 {
+            parts.push(format!("{} renamed", self.renamed));
         }
-        if untracked > 0 {
-            parts.push(format!("{} untracked", untracked));
+        if self.copied > 0 {
+            parts.push(format!("{} copied", self.copied));
+        }
+        if self.untracked > 0 {
+            parts.push(format!("{} untracked", self.untracked));
         }
 
         parts.join(", ")
     }
+
+    /// Single-number summary for when the full breakdown doesn't fit
+    fn compact_summary(&self) -> String {
+        if self.is_clean() {
+            "clean".to_string()
+        } else {
+            format!("{} changed", self.total())
+        }
+    }
+}
+
+/// Records of `git status --porcelain=v2 -z` output, split on NUL, each with
+/// the original path that follows a rename or copy record. Paths may contain
+/// any character, newlines included.
+fn porcelain_records(porcelain_v2: &str) -> Vec<(&str, Option<&str>)> {
+    let mut fields = porcelain_v2.split('\0');
+    let mut records = Vec::new();
+    while let Some(record) = fields.next() {
+        let orig_path = if record.starts_with("2 ") {
+            fields.next()
+        } else {
+            None
+        };
+        records.push((record, orig_path));
+    }
+    records
+}
+
+/// Convert porcelain v2 output to v1-style `XY path` entries, the form the
+/// counts and path matching are parsed from. Renames and copies become
+/// `XY old -> new`; `#` headers are dropped.
+fn v1_entries(porcelain_v2: &str) -> Vec<String> {
+    porcelain_records(porcelain_v2)
+        .into_iter()
+        .filter_map(|(record, orig_path)| v1_entry(record, orig_path))
+        .collect()
+}
+
+fn v1_entry(record: &str, orig_path: Option<&str>) -> Option<String> {
+    let (kind, rest) = record.split_once(' ')?;
+    // Fields between XY and the path: submodule state, modes, hashes, and
+    // for renames the similarity score
+    let skipped = match kind {
+        "?" => return Some(format!("?? {}", rest)),
+        "!" => return Some(format!("!! {}", rest)),
+        "1" => 6,
+        "2" => 7,
+        "u" => 8,
+        _ => return None,
+    };
+    let mut fields = rest.splitn(skipped + 2, ' ');
+    let xy = fields.next()?.replace('.', " ");
+    let path = fields.nth(skipped)?;
+    Some(match orig_path {
+        Some(old) => format!("{} {} -> {}", xy, old, path),
+        None => format!("{} {}", xy, path),
+    })
+}
+
+/// The value of a v2 `# <name> <value>` header
+fn porcelain_header<'a>(porcelain_v2: &'a str, name: &str) -> Option<&'a str> {
+    porcelain_v2.split('\0').find_map(|record| {
+        record
+            .strip_prefix("# ")?
+            .strip_prefix(name)?
+            .strip_prefix(' ')
+    })
+}
+
+/// The branch name from v2's `# branch.head` header, or "(detached)"
+fn parse_branch_head(porcelain_v2: &str) -> Option<&str> {
+    porcelain_header(porcelain_v2, "branch.head")
+}
+
+/// `(main)` for a branch; a detached HEAD is already reported as `(detached)`
+fn branch_label(head: &str) -> String {
+    if head == DETACHED_HEAD {
+        head.to_string()
+    } else {
+        format!("({})", head)
+    }
+}
+
+/// What v2's `# branch.head` reports when HEAD is detached
+const DETACHED_HEAD: &str = "(detached)";
+
+/// Commits ahead of and behind upstream, from v2's `# branch.ab +A -B` header.
+/// None when detached or without an upstream, where git omits the header.
+fn parse_branch_ab(porcelain_v2: &str) -> Option<(usize, usize)> {
+    let ab = porcelain_header(porcelain_v2, "branch.ab")?;
+    let (ahead, behind) = ab.split_once(' ')?;
+    Some((
+        ahead.strip_prefix('+')?.parse().ok()?,
+        behind.strip_prefix('-')?.parse().ok()?,
+    ))
+}
+
+/// `↑3 ↓1` for a branch that has diverged from upstream, omitting zero sides
+fn ahead_behind_marks(ahead: usize, behind: usize) -> Option<String> {
+    let mut marks = Vec::new();
+    if ahead > 0 {
+        marks.push(format!("↑{}", ahead));
+    }
+    if behind > 0 {
+        marks.push(format!("↓{}", behind));
+    }
+    (!marks.is_empty()).then(|| marks.join(" "))
+}
+
+/// One changed path from `git status --porcelain`
+#[derive(Debug, PartialEq, Eq)]
+struct FileStatus {
+    /// The path, or a rename's new path
+    path: String,
+    /// A rename's old path
+    orig_path: Option<String>,
+    index_status: char,
+    worktree_status: char,
+}
+
+impl FileStatus {
+    /// Changed paths among v1-style `entries` that count toward `scope`
+    fn parse<'a>(entries: impl IntoIterator<Item = &'a str>, scope: ChangeScope) -> Vec<Self> {
+        entries
+            .into_iter()
+            .filter_map(|line| {
+                let mut chars = line.chars();
+                let index_status = chars.next()?;
+                let worktree_status = chars.next()?;
+                let path = line.get(3..).filter(|p| !p.is_empty())?;
+                let (orig_path, path) = match path.split_once(" -> ") {
+                    Some((old, new)) => (Some(old.to_string()), new.to_string()),
+                    None => (None, path.to_string()),
+                };
+                Some(FileStatus {
+                    path,
+                    orig_path,
+                    index_status,
+                    worktree_status,
+                })
+            })
+            .filter(|file| match scope {
+                ChangeScope::All => true,
+                ChangeScope::Staged => !matches!(file.index_status, ' ' | '?'),
+                ChangeScope::Unstaged => file.worktree_status != ' ',
+            })
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut file = serde_json::json!({
+            "path": self.path,
+            "index_status": self.index_status.to_string(),
+            "worktree_status": self.worktree_status.to_string(),
+        });
+        if let Some(ref orig_path) = self.orig_path {
+            file["orig_path"] = orig_path.clone().into();
+        }
+        file
+    }
+}
+
+#[derive(Default)]
+struct StatusFormatter {
+    scope: ChangeScope,
+    /// Add a `files` array of changed paths to JSON output
+    files: bool,
+    /// Show git's advisory messages after the summary
+    verbose: bool,
+}
+
+/// Lines of a successful status that are not porcelain: warnings git prints
+/// on stderr (e.g. about .gitattributes), which `--stderr-merge` moves into
+/// stdout. They never affect the counts.
+fn advisory_lines(output: &Output) -> Vec<String> {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    porcelain_records(&stdout)
+        .into_iter()
+        .filter(|(record, orig_path)| {
+            !record.starts_with("# ") && v1_entry(record, *orig_path).is_none()
+        })
+        .flat_map(|(record, _)| record.lines())
+        .chain(stderr.lines())
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl OutputFormatter for StatusFormatter {
+    fn format(&self, output: &Output) -> String {
+        self.format_for_width(output, None)
+    }
+
+    fn format_for_width(&self, output: &Output, width: Option<usize>) -> String {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        if !output.status.success() {
+            return error_line(output);
+        }
+
+        let counts =
+            StatusCounts::parse(v1_entries(&stdout).iter().map(String::as_str), self.scope);
+        let branch = parse_branch_head(&stdout).map(branch_label);
+        let marks =
+            parse_branch_ab(&stdout).and_then(|(ahead, behind)| ahead_behind_marks(ahead, behind));
+        let advisories = Some(advisory_lines(output))
+            .filter(|lines| self.verbose && !lines.is_empty())
+            .map(|lines| format!("(git: {})", lines.join("; ")));
+        // "(main) 1 modified ↑2": branch first, upstream divergence last
+        let decorate = |summary: String| {
+            let parts = [
+                branch.clone(),
+                Some(summary),
+                marks.clone(),
+                advisories.clone(),
+            ];
+            parts.into_iter().flatten().collect::<Vec<_>>().join(" ")
+        };
+        let summary = decorate(counts.summary());
+        match width {
+            Some(width) if summary.chars().count() > width => decorate(counts.compact_summary()),
+            _ => summary,
+        }
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if !output.status.success() {
+            return Category::Error;
+        }
+        let entries = v1_entries(&String::from_utf8_lossy(&output.stdout));
+        if StatusCounts::parse(entries.iter().map(String::as_str), self.scope).is_clean() {
+            Category::Clean
+        } else {
+            Category::Dirty
+        }
+    }
+
+    fn json_fields(&self, output: &Output) -> serde_json::Map<String, serde_json::Value> {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let entries = v1_entries(&stdout);
+        let counts = StatusCounts::parse(entries.iter().map(String::as_str), self.scope);
+        let mut fields: serde_json::Map<String, serde_json::Value> = [
+            ("modified", counts.modified),
+            ("added", counts.added),
+            ("deleted", counts.deleted),
+            ("renamed", counts.renamed),
+            ("copied", counts.copied),
+            ("untracked", counts.untracked),
+            ("conflicted", counts.conflicted),
+        ]
+        .into_iter()
+        .map(|(key, count)| (key.to_string(), count.into()))
+        .collect();
+        fields.insert("clean".to_string(), counts.is_clean().into());
+        if let Some(head) = parse_branch_head(&stdout) {
+            let branch = (head != DETACHED_HEAD).then(|| head.to_string());
+            fields.insert("branch".to_string(), branch.into());
+        }
+        if let Some((ahead, behind)) = parse_branch_ab(&stdout) {
+            fields.insert("ahead".to_string(), ahead.into());
+            fields.insert("behind".to_string(), behind.into());
+        }
+        if self.files {
+            let files = FileStatus::parse(entries.iter().map(String::as_str), self.scope)
+                .iter()
+                .map(FileStatus::to_json)
+                .collect();
+            fields.insert("files".to_string(), serde_json::Value::Array(files));
+        }
+        fields
+    }
+}
+
+/// How `--find` and `--changed-files-match` select changed paths
+#[derive(Debug, Clone)]
+pub enum PathMatch {
+    /// The path contains this text
+    Contains(String),
+    /// A shell-style glob (`*`, `?`). Without a `/` it matches the file name
+    /// in any directory, so `*.proto` finds `api/v1/user.proto`.
+    Glob(String),
+}
+
+impl PathMatch {
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            PathMatch::Contains(text) => path.contains(text.as_str()),
+            PathMatch::Glob(glob) if glob.contains('/') => glob_matches(glob, path),
+            PathMatch::Glob(glob) => glob_matches(glob, path.rsplit('/').next().unwrap_or(path)),
+        }
+    }
+}
+
+/// Porcelain entries whose path matches. Renames ("old -> new") match on either side.
+fn matching_lines<'a>(
+    entries: impl IntoIterator<Item = &'a str>,
+    path_match: &PathMatch,
+) -> Vec<&'a str> {
+    entries
+        .into_iter()
+        .filter(|line| {
+            line.get(3..).is_some_and(|path| match path_match {
+                PathMatch::Contains(_) => path_match.matches(path),
+                PathMatch::Glob(_) => path.split(" -> ").any(|side| path_match.matches(side)),
+            })
+        })
+        .collect()
+}
+
+/// Shows only repos with a changed path matching, listing the matching lines
+struct FindFormatter {
+    path_match: PathMatch,
+}
+
+impl FindFormatter {
+    fn matches(&self, output: &Output) -> Vec<String> {
+        let entries = v1_entries(&String::from_utf8_lossy(&output.stdout));
+        matching_lines(entries.iter().map(String::as_str), &self.path_match)
+            .into_iter()
+            .map(|line| line.trim_start().to_string())
+            .collect()
+    }
+}
+
+impl OutputFormatter for FindFormatter {
+    fn format(&self, output: &Output) -> String {
+        if !output.status.success() {
+            return error_line(output);
+        }
+
+        self.matches(output).join(", ")
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if !output.status.success() {
+            Category::Error
+        } else if self.matches(output).is_empty() {
+            Category::Clean
+        } else {
+            Category::Dirty
+        }
+    }
+
+    fn is_visible(&self, output: &Output) -> bool {
+        !output.status.success() || !self.matches(output).is_empty()
+    }
+}
+
+/// Options for the status command beyond pass-through git args
+#[derive(Default, Clone)]
+pub struct StatusOptions {
+    /// Report divergence of HEAD from this ref instead of working tree changes
+    pub compare: Option<String>,
+    /// Only report repos with a changed path containing this text or matching this glob
+    pub find: Option<PathMatch>,
+    /// Passed to `git status --ignore-submodules=WHEN` (none, untracked, dirty, all)
+    pub ignore_submodules: Option<String>,
+    /// Only report repos currently on one of these branches
+    pub on_branch: Vec<String>,
+    /// Only report repos not on any of these branches (detached HEAD included)
+    pub not_on_branch: Vec<String>,
+    /// Count staged changes, unstaged changes, or both
+    pub scope: ChangeScope,
+    /// List each changed path in JSON output
+    pub files: bool,
+    /// Show git's advisory messages (warnings on a successful status)
+    pub verbose: bool,
+    /// Report modified files hidden by assume-unchanged or skip-worktree instead
+    pub hidden_changes: bool,
+}
+
+impl StatusOptions {
+    /// Whether a repo on `branch` (None when detached) passes the branch filters
+    fn branch_matches(&self, branch: Option<&str>) -> bool {
+        let listed = |names: &[String]| branch.is_some_and(|b| names.iter().any(|n| n == b));
+        (self.on_branch.is_empty() || listed(&self.on_branch))
+            && (self.not_on_branch.is_empty() || !listed(&self.not_on_branch))
+    }
+}
+
+/// The first of `extra_args` that would change `git status` output away from
+/// the `--porcelain` format the counts are parsed from
+fn output_format_arg(extra_args: &[String]) -> Option<&str> {
+    extra_args.iter().map(String::as_str).find(|arg| {
+        matches!(*arg, "-s" | "--short" | "--long" | "-z" | "--null")
+            || arg.starts_with("--porcelain")
+            // Bundled short flags such as -sb
+            || (arg.len() > 2
+                && arg.starts_with('-')
+                && arg[1..].chars().all(|c| "sbz".contains(c))
+                && arg[1..].contains(['s', 'z']))
+    })
+}
+
+fn build_args(opts: &StatusOptions, extra_args: &[String]) -> Vec<String> {
+    // Always use porcelain v2 for machine-readable output; --branch adds the
+    // ahead/behind header
+    let mut args = vec![
+        "status".to_string(),
+        "--porcelain=v2".to_string(),
+        "--branch".to_string(),
+        "-z".to_string(),
+    ];
+    if let Some(ref when) = opts.ignore_submodules {
+        args.push(format!("--ignore-submodules={}", when));
+    }
+    args.extend(extra_args.iter().cloned());
+    args
 }
 
-pub fn run(ctx: &ExecutionContext, repos: &[PathBuf], extra_args: &[String]) -> Result<()> {
-    let formatter = StatusFormatter;
+/// Parse `git rev-list --left-right --count HEAD...REF` output: "<ahead>\t<behind>"
+fn parse_left_right_count(stdout: &str) -> Option<(usize, usize)> {
+    let mut counts = stdout.split_whitespace();
+    let ahead = counts.next()?.parse().ok()?;
+    let behind = counts.next()?.parse().ok()?;
+    Some((ahead, behind))
+}
+
+struct CompareFormatter {
+    reference: String,
+}
+
+impl OutputFormatter for CompareFormatter {
+    fn format(&self, output: &Output) -> String {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if !output.status.success() {
+            if stderr.contains("unknown revision") || stderr.contains("bad revision") {
+                return format!("(no {})", self.reference);
+            }
+            return error_line(output);
+        }
+
+        match parse_left_right_count(&String::from_utf8_lossy(&output.stdout)) {
+            Some((0, 0)) => format!("even with {}", self.reference),
+            Some((ahead, behind)) => {
+                format!("{} ahead, {} behind {}", ahead, behind, self.reference)
+            }
+            None => "unexpected rev-list output".to_string(),
+        }
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if !output.status.success() {
+            return Category::Error;
+        }
+        match parse_left_right_count(&String::from_utf8_lossy(&output.stdout)) {
+            Some((0, 0)) => Category::Clean,
+            _ => Category::Dirty,
+        }
+    }
+}
+
+fn run_compare(ctx: &ExecutionContext, repos: &[Repo], reference: &str) -> Result<()> {
+    let formatter = CompareFormatter {
+        reference: reference.to_string(),
+    };
 
     run_parallel(
         ctx,
         repos,
         |repo| {
-            // Always use --porcelain for machine-readable output
-            let mut args = vec!["status".to_string(), "--porcelain".to_string()];
-            args.extend(extra_args.iter().cloned());
-            GitCommand::new(repo.clone(), args)
+            let args = vec![
+                "rev-list".to_string(),
+                "--left-right".to_string(),
+                "--count".to_string(),
+                format!("HEAD...{}", reference),
+            ];
+            GitCommand::new(repo.path().to_path_buf(), args)
         },
         &formatter,
     )
 }
+
+/// Regular files `git ls-files -v -s -z` tags as assume-unchanged (lowercase
+/// tag) or skip-worktree (`S`), as (index object, path) pairs
+fn parse_hidden_entries(ls_files: &str) -> Vec<(&str, &str)> {
+    ls_files
+        .split('\0')
+        .filter_map(|record| {
+            let (meta, path) = record.split_once('\t')?;
+            let mut fields = meta.split_whitespace();
+            let tag = fields.next()?;
+            let mode = fields.next()?;
+            let object = fields.next()?;
+            let hidden = tag == "S" || tag.chars().all(|c| c.is_ascii_lowercase());
+            // Symlinks and submodules can't be compared by hashing the path
+            (hidden && matches!(mode, "100644" | "100755")).then_some((object, path))
+        })
+        .collect()
+}
+
+/// Given `git ls-files -v -s -z` output, hash the hidden files' worktree
+/// contents and report the paths that no longer match the index (deleted
+/// files included), one per line
+fn hidden_changes(
+    repo: &Path,
+    settings: &CommandSettings,
+    output: Output,
+) -> std::io::Result<Output> {
+    if !output.status.success() {
+        return Ok(output);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let (present, missing): (Vec<_>, Vec<_>) = parse_hidden_entries(&stdout)
+        .into_iter()
+        .partition(|(_, path)| repo.join(path).symlink_metadata().is_ok());
+    let mut changed: Vec<&str> = missing.iter().map(|(_, path)| *path).collect();
+
+    if !present.is_empty() {
+        let mut args = vec!["hash-object".to_string(), "--".to_string()];
+        args.extend(present.iter().map(|(_, path)| path.to_string()));
+        let hashed = settings.command(repo, args).run(None)?;
+        if !hashed.status.success() {
+            return Ok(hashed);
+        }
+        let hashes = String::from_utf8_lossy(&hashed.stdout).into_owned();
+        changed.extend(
+            present
+                .iter()
+                .zip(hashes.lines())
+                .filter(|((object, _), hash)| object != hash)
+                .map(|((_, path), _)| *path),
+        );
+    }
+
+    changed.sort_unstable();
+    let stdout = changed
+        .iter()
+        .map(|path| format!("{}\n", path))
+        .collect::<String>();
+    Ok(Output {
+        stdout: stdout.into_bytes(),
+        stderr: Vec::new(),
+        ..output
+    })
+}
+
+/// Summarizes the modified hidden files found by `hidden_changes`
+struct HiddenChangesFormatter;
+
+impl HiddenChangesFormatter {
+    fn count(output: &Output) -> usize {
+        String::from_utf8_lossy(&output.stdout).lines().count()
+    }
+}
+
+impl OutputFormatter for HiddenChangesFormatter {
+    fn format(&self, output: &Output) -> String {
+        if !output.status.success() {
+            return error_line(output);
+        }
+        match Self::count(output) {
+            0 => "no hidden changes".to_string(),
+            1 => "1 hidden change".to_string(),
+            n => format!("{} hidden changes", n),
+        }
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if !output.status.success() {
+            Category::Error
+        } else if Self::count(output) == 0 {
+            Category::Clean
+        } else {
+            Category::Dirty
+        }
+    }
+}
+
+fn run_hidden_changes(ctx: &ExecutionContext, repos: &[Repo]) -> Result<()> {
+    let settings = ctx.command_settings();
+    run_parallel(
+        ctx,
+        repos,
+        |repo| {
+            let args = vec![
+                "ls-files".to_string(),
+                "-v".to_string(),
+                "-s".to_string(),
+                "-z".to_string(),
+            ];
+            let path = repo.path().to_path_buf();
+            let settings = settings.clone();
+            GitCommand::new(path.clone(), args).with_followup(Box::new(move |output| {
+                hidden_changes(&path, &settings, output)
+            }))
+        },
+        &HiddenChangesFormatter,
+    )
+}
+
+pub fn run(
+    ctx: &ExecutionContext,
+    repos: &[Repo],
+    opts: &StatusOptions,
+    extra_args: &[String],
+) -> Result<()> {
+    let filtered: Vec<Repo>;
+    let repos = if opts.on_branch.is_empty() && opts.not_on_branch.is_empty() {
+        repos
+    } else {
+        filtered = repos
+            .iter()
+            .filter(|repo| opts.branch_matches(head_branch(repo.path()).as_deref()))
+            .cloned()
+            .collect();
+        &filtered
+    };
+
+    if let Some(ref reference) = opts.compare {
+        return run_compare(ctx, repos, reference);
+    }
+    if opts.hidden_changes {
+        return run_hidden_changes(ctx, repos);
+    }
+
+    if let Some(arg) = output_format_arg(extra_args) {
+        anyhow::bail!("status output format is managed by git-all; remove {}", arg);
+    }
+
+    let args = build_args(opts, extra_args);
+    let build = |repo: &Repo| GitCommand::new(repo.path().to_path_buf(), args.clone());
+
+    match opts.find {
+        Some(ref path_match) => {
+            let formatter = FindFormatter {
+                path_match: path_match.clone(),
+            };
+            run_parallel(ctx, repos, build, &formatter)
+        }
+        None => {
+            let formatter = StatusFormatter {
+                scope: opts.scope,
+                files: opts.files,
+                verbose: opts.verbose,
+            };
+            run_parallel(ctx, repos, build, &formatter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn make_output(stdout: &str) -> Output {
+        Output {
+            status: ExitStatus::from_raw(0),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: Vec::new(),
+        }
+    }
+
+    const MIXED: &str = " M src/main.rs\nA  src/new.rs\n D old.rs\n?? notes.txt\n";
+
+    /// MIXED as `git status --porcelain=v2 --branch -z` reports it
+    const MIXED_V2: &str = concat!(
+        "# branch.oid 3b7d4f1e2a9c8b6d5f4e3d2c1b0a9f8e7d6c5b4a\0",
+        "# branch.head main\0",
+        "1 .M N... 100644 100644 100644 e69de29 e69de29 src/main.rs\0",
+        "1 A. N... 000000 100644 100644 0000000 e69de29 src/new.rs\0",
+        "1 .D N... 100644 100644 000000 e69de29 e69de29 old.rs\0",
+        "? notes.txt\0",
+    );
+
+    #[test]
+    fn test_v1_entries_from_v2() {
+        assert_eq!(v1_entries(MIXED_V2), MIXED.lines().collect::<Vec<_>>());
+
+        let v2 = concat!(
+            "# branch.ab +0 -0\0",
+            "2 R. N... 100644 100644 100644 e69de29 e69de29 R100 docs/new name.md\0docs/old name.md\0",
+            "u UU N... 100644 100644 100644 100644 e69de29 3b7d4f1 42ef2a4 conflict.rs\0",
+            "! target/\0",
+        );
+        assert_eq!(
+            v1_entries(v2),
+            [
+                "R  docs/old name.md -> docs/new name.md",
+                "UU conflict.rs",
+                "!! target/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paths_with_newlines() {
+        let v2 = concat!(
+            "# branch.head main\0",
+            "? line\nbreak.txt\0",
+            "2 R. N... 100644 100644 100644 e69de29 e69de29 R100 new\nname.rs\0old.rs\0",
+        );
+        assert_eq!(
+            v1_entries(v2),
+            ["?? line\nbreak.txt", "R  old.rs -> new\nname.rs"]
+        );
+        let formatter = StatusFormatter {
+            files: true,
+            verbose: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            formatter.format(&make_output(v2)),
+            "(main) 1 renamed, 1 untracked"
+        );
+        let fields = formatter.json_fields(&make_output(v2));
+        assert_eq!(fields["files"][0]["path"], "line\nbreak.txt");
+        assert_eq!(fields["files"][1]["path"], "new\nname.rs");
+    }
+
+    #[test]
+    fn test_advisory_stderr_does_not_change_clean_summary() {
+        let warning = "warning: in the working copy of 'notes.txt', LF will be replaced by CRLF the next time Git touches it\n";
+        let output = Output {
+            status: ExitStatus::from_raw(0),
+            stdout: b"# branch.oid 3b7d4f1\0# branch.head main\0".to_vec(),
+            stderr: warning.as_bytes().to_vec(),
+        };
+        let quiet = StatusFormatter::default();
+        assert_eq!(quiet.format(&output), "(main) clean");
+        assert_eq!(quiet.category(&output), Category::Clean);
+        // --stderr-merge puts the warning among the porcelain lines
+        let merged = crate::runner::merge_stderr(output.clone());
+        assert_eq!(quiet.format(&merged), "(main) clean");
+        assert_eq!(quiet.category(&merged), Category::Clean);
+
+        let verbose = StatusFormatter {
+            verbose: true,
+            ..Default::default()
+        };
+        let shown = format!("(main) clean (git: {})", warning.trim());
+        assert_eq!(verbose.format(&output), shown);
+        assert_eq!(verbose.format(&merged), shown);
+        let no_warnings = make_output(MIXED_V2);
+        assert_eq!(
+            verbose.format(&no_warnings),
+            "(main) 1 modified, 1 added, 1 deleted, 1 untracked"
+        );
+    }
+
+    #[test]
+    fn test_branch_prefixes_summary() {
+        let formatter = StatusFormatter::default();
+        let on = |head: &str| make_output(&format!("# branch.oid 3b7d4f1\0# branch.head {head}\0"));
+        assert_eq!(formatter.format(&on("main")), "(main) clean");
+        assert_eq!(
+            formatter.format(&on("feature/login")),
+            "(feature/login) clean"
+        );
+        assert_eq!(formatter.format(&on("(detached)")), "(detached) clean");
+
+        assert_eq!(formatter.json_fields(&on("main"))["branch"], "main");
+        assert!(formatter.json_fields(&on("(detached)"))["branch"].is_null());
+    }
+
+    #[test]
+    fn test_parse_branch_ab() {
+        let header = "# branch.oid 3b7d4f1\0# branch.head main\0# branch.upstream origin/main\0";
+        assert_eq!(
+            parse_branch_ab(&format!("{header}# branch.ab +3 -1\0")),
+            Some((3, 1))
+        );
+        // Detached HEAD or no upstream: git omits branch.ab
+        assert_eq!(
+            parse_branch_ab("# branch.oid 3b7d4f1\0# branch.head (detached)\0"),
+            None
+        );
+        assert_eq!(parse_branch_ab(header), None);
+    }
+
+    #[test]
+    fn test_ahead_behind_appended_to_summary() {
+        let with_ab =
+            |ab: &str, entries: &str| make_output(&format!("# branch.ab {ab}\0{entries}"));
+        let formatter = StatusFormatter::default();
+        assert_eq!(formatter.format(&with_ab("+3 -0", "")), "clean ↑3");
+        assert_eq!(formatter.format(&with_ab("+3 -1", "")), "clean ↑3 ↓1");
+        assert_eq!(
+            formatter.format(&with_ab("+0 -2", "? notes.txt\0")),
+            "1 untracked ↓2"
+        );
+        assert_eq!(formatter.format(&with_ab("+0 -0", "")), "clean");
+        assert_eq!(formatter.category(&with_ab("+3 -0", "")), Category::Clean);
+
+        let output = with_ab("+3 -1", MIXED_V2);
+        assert_eq!(
+            formatter.format_for_width(&output, Some(20)),
+            "(main) 4 changed ↑3 ↓1"
+        );
+
+        let fields = formatter.json_fields(&output);
+        assert_eq!(
+            (fields["ahead"].as_u64(), fields["behind"].as_u64()),
+            (Some(3), Some(1))
+        );
+        assert!(
+            !formatter
+                .json_fields(&make_output(MIXED_V2))
+                .contains_key("ahead")
+        );
+    }
+
+    #[test]
+    fn test_counts_mixed_changes() {
+        let counts = StatusCounts::parse(MIXED.lines(), ChangeScope::All);
+        assert_eq!(counts.modified, 1);
+        assert_eq!(counts.added, 1);
+        assert_eq!(counts.deleted, 1);
+        assert_eq!(counts.untracked, 1);
+    }
+
+    #[test]
+    fn test_counts_copies() {
+        let counts = StatusCounts::parse(
+            "C  src/lib.rs -> src/lib_v2.rs\nR  a.rs -> b.rs\n".lines(),
+            ChangeScope::All,
+        );
+        assert_eq!(counts.copied, 1);
+        assert_eq!(counts.renamed, 1);
+        assert_eq!(counts.summary(), "1 renamed, 1 copied");
+
+        // Porcelain v2 reports copies as `2` records with a C score
+        let v2 = "2 C. N... 100644 100644 100644 e69de29 e69de29 C75 src/lib_v2.rs\0src/lib.rs\0";
+        assert_eq!(v1_entries(v2), ["C  src/lib.rs -> src/lib_v2.rs"]);
+        let fields = StatusFormatter::default().json_fields(&make_output(v2));
+        assert_eq!(fields["copied"], 1);
+        assert_eq!(
+            StatusFormatter::default().format(&make_output(v2)),
+            "1 copied"
+        );
+    }
+
+    #[test]
+    fn test_counts_by_scope() {
+        let porcelain = "M  staged.rs\n M unstaged.rs\nMM both.rs\nAM new.rs\n?? notes.txt\n";
+
+        let all = StatusCounts::parse(porcelain.lines(), ChangeScope::All);
+        assert_eq!(all.summary(), "3 modified, 1 added, 1 untracked");
+
+        let staged = StatusCounts::parse(porcelain.lines(), ChangeScope::Staged);
+        assert_eq!(staged.summary(), "2 modified, 1 added");
+
+        let unstaged = StatusCounts::parse(porcelain.lines(), ChangeScope::Unstaged);
+        assert_eq!(unstaged.summary(), "3 modified, 1 untracked");
+
+        let worktree_only = StatusCounts::parse(" M a.rs\n?? b.rs\n".lines(), ChangeScope::Staged);
+        assert!(worktree_only.is_clean());
+    }
+
+    #[test]
+    fn test_branch_filters() {
+        let on_main = StatusOptions {
+            on_branch: vec!["main".to_string()],
+            ..Default::default()
+        };
+        assert!(on_main.branch_matches(Some("main")));
+        assert!(!on_main.branch_matches(Some("feature")));
+        assert!(!on_main.branch_matches(None));
+
+        let off_main = StatusOptions {
+            not_on_branch: vec!["main".to_string(), "master".to_string()],
+            ..Default::default()
+        };
+        assert!(!off_main.branch_matches(Some("master")));
+        assert!(off_main.branch_matches(Some("feature")));
+        assert!(off_main.branch_matches(None));
+
+        assert!(StatusOptions::default().branch_matches(None));
+    }
+
+    #[test]
+    fn test_build_args_ignore_submodules() {
+        let opts = StatusOptions {
+            ignore_submodules: Some("dirty".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            build_args(&opts, &["-uno".to_string()]),
+            vec![
+                "status",
+                "--porcelain=v2",
+                "--branch",
+                "-z",
+                "--ignore-submodules=dirty",
+                "-uno"
+            ]
+        );
+        assert_eq!(
+            build_args(&StatusOptions::default(), &[]),
+            vec!["status", "--porcelain=v2", "--branch", "-z"]
+        );
+    }
+
+    #[test]
+    fn test_conflicted_paths_not_clean() {
+        // Left behind by e.g. a conflicting autostash pop after pull
+        let counts = StatusCounts::parse(
+            "UU f\nAA both-added.rs\n M other.rs\n".lines(),
+            ChangeScope::All,
+        );
+        assert_eq!(counts.conflicted, 2);
+        assert_eq!(counts.modified, 1);
+        assert_eq!(counts.summary(), "2 conflicted, 1 modified");
+        let unmerged =
+            make_output("u UU N... 100644 100644 100644 100644 e69de29 3b7d4f1 42ef2a4 f\0");
+        assert_eq!(
+            StatusFormatter::default().category(&unmerged),
+            Category::Dirty
+        );
+    }
+
+    #[test]
+    fn test_each_unmerged_combination_is_conflicted() {
+        for xy in ["UU", "AA", "DD", "AU", "UA", "DU", "UD"] {
+            let counts = StatusCounts::parse([format!("{xy} f.rs").as_str()], ChangeScope::All);
+            assert_eq!(counts.summary(), "1 conflicted", "{xy}");
+
+            // As porcelain v2 reports it, in a `u` record
+            let v2 =
+                format!("u {xy} N... 100644 100644 100644 100644 e69de29 3b7d4f1 42ef2a4 f.rs\0");
+            let output = make_output(&v2);
+            assert_eq!(
+                StatusFormatter::default().format(&output),
+                "1 conflicted",
+                "{xy}"
+            );
+            assert_eq!(
+                StatusFormatter::default().category(&output),
+                Category::Dirty,
+                "{xy}"
+            );
+        }
+
+        // Conflicts count once and are listed first, alongside other changes
+        let counts = StatusCounts::parse(
+            "UU a.rs\nDU b.rs\nM  c.rs\n?? d.rs\n".lines(),
+            ChangeScope::All,
+        );
+        assert_eq!(counts.summary(), "2 conflicted, 1 modified, 1 untracked");
+        // Not ordinary staged or unstaged changes in either scope
+        assert_eq!(
+            StatusCounts::parse("AA f.rs\n".lines(), ChangeScope::Staged).added,
+            0
+        );
+        assert_eq!(
+            StatusCounts::parse("UD f.rs\n".lines(), ChangeScope::Unstaged).deleted,
+            0
+        );
+    }
+
+    #[test]
+    fn test_json_fields() {
+        let fields = StatusFormatter::default().json_fields(&make_output(MIXED_V2));
+        assert_eq!(
+            serde_json::Value::Object(fields).to_string(),
+            r#"{"modified":1,"added":1,"deleted":1,"renamed":0,"copied":0,"untracked":1,"conflicted":0,"clean":false,"branch":"main"}"#
+        );
+    }
+
+    #[test]
+    fn test_json_files() {
+        let porcelain = concat!(
+            "1 MM N... 100644 100644 100644 e69de29 3b7d4f1 src/main.rs\0",
+            "2 R. N... 100644 100644 100644 e69de29 e69de29 R100 new.rs\0old.rs\0",
+            "? notes.txt\0",
+        );
+        let formatter = StatusFormatter {
+            files: true,
+            ..Default::default()
+        };
+        let fields = formatter.json_fields(&make_output(porcelain));
+        assert_eq!(
+            fields["files"].to_string(),
+            concat!(
+                r#"[{"path":"src/main.rs","index_status":"M","worktree_status":"M"},"#,
+                r#"{"path":"new.rs","index_status":"R","worktree_status":" ","orig_path":"old.rs"},"#,
+                r#"{"path":"notes.txt","index_status":"?","worktree_status":"?"}]"#
+            )
+        );
+
+        assert!(
+            !StatusFormatter::default()
+                .json_fields(&make_output(porcelain))
+                .contains_key("files")
+        );
+        assert_eq!(
+            formatter.json_fields(&make_output(""))["files"],
+            serde_json::json!([])
+        );
+    }
+
+    #[test]
+    fn test_file_status_by_scope() {
+        let porcelain = "M  staged.rs\n M unstaged.rs\n?? notes.txt\n";
+        let paths = |scope| -> Vec<String> {
+            FileStatus::parse(porcelain.lines(), scope)
+                .into_iter()
+                .map(|f| f.path)
+                .collect()
+        };
+        assert_eq!(
+            paths(ChangeScope::All),
+            ["staged.rs", "unstaged.rs", "notes.txt"]
+        );
+        assert_eq!(paths(ChangeScope::Staged), ["staged.rs"]);
+        assert_eq!(paths(ChangeScope::Unstaged), ["unstaged.rs", "notes.txt"]);
+    }
+
+    #[test]
+    fn test_clean_output() {
+        assert_eq!(StatusFormatter::default().format(&make_output("")), "clean");
+    }
+
+    #[test]
+    fn test_category_clean_and_dirty() {
+        assert_eq!(
+            StatusFormatter::default().category(&make_output("")),
+            Category::Clean
+        );
+        assert_eq!(
+            StatusFormatter::default().category(&make_output(MIXED_V2)),
+            Category::Dirty
+        );
+    }
+
+    #[test]
+    fn test_output_format_arg() {
+        let args = |s: &str| -> Vec<String> { s.split_whitespace().map(String::from).collect() };
+        for (extra, conflict) in [
+            ("-s", "-s"),
+            ("--short", "--short"),
+            ("--long", "--long"),
+            ("--porcelain=v2", "--porcelain=v2"),
+            ("--porcelain", "--porcelain"),
+            ("-uno -z", "-z"),
+            ("-sb", "-sb"),
+        ] {
+            assert_eq!(output_format_arg(&args(extra)), Some(conflict), "{extra}");
+        }
+        for extra in [
+            "",
+            "-uno",
+            "--untracked-files=all",
+            "-b",
+            "--ignored",
+            "-- src",
+        ] {
+            assert_eq!(output_format_arg(&args(extra)), None, "{extra}");
+        }
+    }
+
+    #[test]
+    fn test_parse_hidden_entries() {
+        let ls_files = concat!(
+            "h 100644 78981922613b2afb6025042ff6bd878ac1994e85 0\tconfig/local.yml\0",
+            "S 100644 61780798228d17af2d34fce4cfbdf35556832472 0\tvendor/big file.bin\0",
+            "s 100755 f2ad6c76f0115a6ba5b00456a849810e7ec0af20 0\tbin/both\0",
+            "H 100644 f2ad6c76f0115a6ba5b00456a849810e7ec0af20 0\tsrc/main.rs\0",
+            "h 120000 e69de29bb2d1d6434b8b29ae775ad8c2e48c5391 0\tlink\0",
+            "h 160000 3b7d4f1e2a9c8b6d5f4e3d2c1b0a9f8e7d6c5b4a 0\tsubmodule\0",
+        );
+        assert_eq!(
+            parse_hidden_entries(ls_files),
+            vec![
+                (
+                    "78981922613b2afb6025042ff6bd878ac1994e85",
+                    "config/local.yml"
+                ),
+                (
+                    "61780798228d17af2d34fce4cfbdf35556832472",
+                    "vendor/big file.bin"
+                ),
+                ("f2ad6c76f0115a6ba5b00456a849810e7ec0af20", "bin/both"),
+            ]
+        );
+        assert!(parse_hidden_entries("").is_empty());
+    }
+
+    #[test]
+    fn test_hidden_changes_formatter() {
+        assert_eq!(
+            HiddenChangesFormatter.format(&make_output("")),
+            "no hidden changes"
+        );
+        assert_eq!(
+            HiddenChangesFormatter.category(&make_output("")),
+            Category::Clean
+        );
+        let two = make_output("config/local.yml\nvendor/big file.bin\n");
+        assert_eq!(HiddenChangesFormatter.format(&two), "2 hidden changes");
+        assert_eq!(HiddenChangesFormatter.category(&two), Category::Dirty);
+        assert_eq!(
+            HiddenChangesFormatter.format(&make_output("a\n")),
+            "1 hidden change"
+        );
+    }
+
+    #[test]
+    fn test_parse_left_right_count() {
+        assert_eq!(parse_left_right_count("5\t2\n"), Some((5, 2)));
+        assert_eq!(parse_left_right_count("0\t0\n"), Some((0, 0)));
+        assert_eq!(parse_left_right_count(""), None);
+    }
+
+    #[test]
+    fn test_compare_formatter() {
+        let formatter = CompareFormatter {
+            reference: "origin/main".to_string(),
+        };
+        assert_eq!(
+            formatter.format(&make_output("5\t2\n")),
+            "5 ahead, 2 behind origin/main"
+        );
+        assert_eq!(
+            formatter.format(&make_output("0\t0\n")),
+            "even with origin/main"
+        );
+
+        let missing = Output {
+            status: ExitStatus::from_raw(128 << 8),
+            stdout: Vec::new(),
+            stderr: b"fatal: ambiguous argument 'HEAD...origin/main': unknown revision or path not in the working tree.\n".to_vec(),
+        };
+        assert_eq!(formatter.format(&missing), "(no origin/main)");
+    }
+
+    #[test]
+    fn test_format_for_width_keeps_detail_when_it_fits() {
+        let output = make_output(MIXED_V2);
+        let full = "(main) 1 modified, 1 added, 1 deleted, 1 untracked";
+        assert_eq!(
+            StatusFormatter::default().format_for_width(&output, None),
+            full
+        );
+        assert_eq!(
+            StatusFormatter::default().format_for_width(&output, Some(80)),
+            full
+        );
+    }
+
+    #[test]
+    fn test_format_for_width_compacts_when_narrow() {
+        let output = make_output(MIXED_V2);
+        assert_eq!(
+            StatusFormatter::default().format_for_width(&output, Some(20)),
+            "(main) 4 changed"
+        );
+    }
+
+    fn contains(text: &str) -> PathMatch {
+        PathMatch::Contains(text.to_string())
+    }
+
+    #[test]
+    fn test_matching_lines_by_glob() {
+        let porcelain = concat!(
+            " M api/v1/user.proto\n",
+            "M  api/v1/user.pb.go\n",
+            "?? protos.txt\n",
+            "R  old.proto -> proto/renamed.txt\n",
+            " M user.proto.bak\n",
+        );
+        let glob = |pattern: &str| PathMatch::Glob(pattern.to_string());
+        assert_eq!(
+            matching_lines(porcelain.lines(), &glob("*.proto")),
+            vec![" M api/v1/user.proto", "R  old.proto -> proto/renamed.txt"]
+        );
+        // With a slash, the glob covers the whole path
+        assert_eq!(
+            matching_lines(porcelain.lines(), &glob("api/*.go")),
+            vec!["M  api/v1/user.pb.go"]
+        );
+        assert!(matching_lines(porcelain.lines(), &glob("v1/*.proto")).is_empty());
+        assert_eq!(
+            matching_lines(porcelain.lines(), &glob("proto?.txt")),
+            vec!["?? protos.txt"]
+        );
+    }
+
+    #[test]
+    fn test_matching_lines_filters_on_path() {
+        let porcelain =
+            " M config.yml\nM  deploy/config.yml.bak\n?? src/config.rs\nR  a.yml -> b.yml\n";
+        assert_eq!(
+            matching_lines(porcelain.lines(), &contains("config.yml")),
+            vec![" M config.yml", "M  deploy/config.yml.bak"]
+        );
+        assert_eq!(
+            matching_lines(porcelain.lines(), &contains("a.yml")),
+            vec!["R  a.yml -> b.yml"]
+        );
+        assert!(matching_lines(porcelain.lines(), &contains("README")).is_empty());
+    }
+
+    #[test]
+    fn test_matching_lines_ignores_status_columns() {
+        // "M" is a status code here, not part of any path
+        assert!(matching_lines([" M src/main.rs"], &contains("M")).is_empty());
+    }
+
+    #[test]
+    fn test_find_formatter_hides_repos_without_match() {
+        let formatter = FindFormatter {
+            path_match: contains("config.yml"),
+        };
+        let hit =
+            make_output("1 .M N... 100644 100644 100644 e69de29 e69de29 config.yml\0? notes.txt\0");
+        assert!(formatter.is_visible(&hit));
+        assert_eq!(formatter.format(&hit), "M config.yml");
+        assert_eq!(formatter.category(&hit), Category::Dirty);
+
+        let miss = make_output("? notes.txt\0");
+        assert!(!formatter.is_visible(&miss));
+        assert_eq!(formatter.category(&miss), Category::Clean);
+    }
+}
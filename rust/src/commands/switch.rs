@@ -0,0 +1,182 @@
+use anyhow::Result;
+use std::process::Output;
+
+use crate::repo::Repo;
+use crate::runner::{
+    Category, ExecutionContext, GitCommand, OutputFormatter, error_line, run_parallel,
+};
+
+/// Options for the switch command beyond pass-through git args
+#[derive(Default, Clone)]
+pub struct SwitchOptions {
+    /// Create the branch first (`git switch -c`)
+    pub create: bool,
+}
+
+/// What `git switch` did in one repo, read from its stderr
+#[derive(Debug, PartialEq, Eq)]
+enum SwitchResult {
+    Switched,
+    Created,
+    AlreadyOn,
+    NoSuchBranch,
+    /// Local changes would be overwritten; git refused and changed nothing
+    Dirty,
+    Failed,
+}
+
+impl SwitchResult {
+    fn parse(output: &Output) -> Self {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() {
+            if stderr.contains("Already on ") {
+                SwitchResult::AlreadyOn
+            } else if stderr.contains("Switched to a new branch") {
+                SwitchResult::Created
+            } else {
+                SwitchResult::Switched
+            }
+        } else if stderr.contains("invalid reference:") {
+            SwitchResult::NoSuchBranch
+        } else if stderr.contains("Your local changes to the following files would be overwritten")
+        {
+            SwitchResult::Dirty
+        } else {
+            SwitchResult::Failed
+        }
+    }
+}
+
+struct SwitchFormatter {
+    branch: String,
+}
+
+impl OutputFormatter for SwitchFormatter {
+    fn format(&self, output: &Output) -> String {
+        match SwitchResult::parse(output) {
+            SwitchResult::Switched => format!("switched to {}", self.branch),
+            SwitchResult::Created => format!("switched to new branch {}", self.branch),
+            SwitchResult::AlreadyOn => format!("already on {}", self.branch),
+            SwitchResult::NoSuchBranch => "no such branch".to_string(),
+            SwitchResult::Dirty => "dirty, skipped".to_string(),
+            SwitchResult::Failed => error_line(output),
+        }
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        match SwitchResult::parse(output) {
+            SwitchResult::Switched | SwitchResult::Created => Category::Updated,
+            SwitchResult::AlreadyOn => Category::Clean,
+            SwitchResult::NoSuchBranch | SwitchResult::Dirty => Category::Skipped,
+            SwitchResult::Failed => Category::Error,
+        }
+    }
+}
+
+fn build_args(opts: &SwitchOptions, branch: &str, extra_args: &[String]) -> Vec<String> {
+    let mut args = vec!["switch".to_string()];
+    if opts.create {
+        args.push("-c".to_string());
+    }
+    args.push(branch.to_string());
+    args.extend(extra_args.iter().cloned());
+    args
+}
+
+/// Switch every repo to `branch`. Repos without it, or whose local changes
+/// would be overwritten, are left as they are.
+pub fn run(
+    ctx: &ExecutionContext,
+    repos: &[Repo],
+    opts: &SwitchOptions,
+    branch: &str,
+    extra_args: &[String],
+) -> Result<()> {
+    let args = build_args(opts, branch, extra_args);
+    let formatter = SwitchFormatter {
+        branch: branch.to_string(),
+    };
+
+    run_parallel(
+        ctx,
+        repos,
+        |repo| GitCommand::new(repo.path().to_path_buf(), args.clone()),
+        &formatter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn make_output(stderr: &str, code: i32) -> Output {
+        Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    fn formatter() -> SwitchFormatter {
+        SwitchFormatter {
+            branch: "main".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_switched_and_already_on() {
+        let switched = make_output("Switched to branch 'main'\n", 0);
+        assert_eq!(formatter().format(&switched), "switched to main");
+        assert_eq!(formatter().category(&switched), Category::Updated);
+
+        let already = make_output("Already on 'main'\n", 0);
+        assert_eq!(formatter().format(&already), "already on main");
+        assert_eq!(formatter().category(&already), Category::Clean);
+
+        let created = make_output("Switched to a new branch 'main'\n", 0);
+        assert_eq!(formatter().format(&created), "switched to new branch main");
+    }
+
+    #[test]
+    fn test_missing_branch_and_dirty_are_skipped() {
+        let missing = make_output("fatal: invalid reference: main\n", 128);
+        assert_eq!(formatter().format(&missing), "no such branch");
+        assert_eq!(formatter().category(&missing), Category::Skipped);
+
+        let dirty = make_output(
+            "error: Your local changes to the following files would be overwritten by checkout:\n\ta\nPlease commit your changes or stash them before you switch branches.\nAborting\n",
+            1,
+        );
+        assert_eq!(formatter().format(&dirty), "dirty, skipped");
+        assert_eq!(formatter().category(&dirty), Category::Skipped);
+    }
+
+    #[test]
+    fn test_other_failures_are_errors() {
+        let exists = make_output("fatal: a branch named 'main' already exists\n", 128);
+        assert_eq!(
+            formatter().format(&exists),
+            "fatal: a branch named 'main' already exists"
+        );
+        assert_eq!(formatter().category(&exists), Category::Error);
+    }
+
+    #[test]
+    fn test_build_args() {
+        let create = SwitchOptions { create: true };
+        assert_eq!(
+            build_args(&create, "release", &[]),
+            vec!["switch", "-c", "release"]
+        );
+        assert_eq!(
+            build_args(
+                &SwitchOptions::default(),
+                "main",
+                &["--discard-changes".to_string()]
+            ),
+            vec!["switch", "main", "--discard-changes"]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::Result;
+use std::path::Path;
+use std::process::Output;
+
+use crate::commands::fetch::count_pruned_refs;
+use crate::commands::pull::is_no_tracking_error;
+use crate::repo::Repo;
+use crate::runner::{
+    Category, CommandSettings, ExecutionContext, GitCommand, OutputFormatter, UrlRewrite,
+    error_line, run_parallel,
+};
+
+/// Prepended to stdout after a fast-forward: "git-all: fast-forwarded <count>\n"
+const FAST_FORWARD_MARKER: &str = "git-all: fast-forwarded ";
+
+/// The "Updating <old>..<new>" range `git pull` prints before fast-forwarding
+fn fast_forward_range(stdout: &str) -> Option<&str> {
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Updating "))
+}
+
+/// Why `git pull --ff-only` stopped short: local edits in the way, or local
+/// commits the upstream doesn't have
+fn is_blocked_fast_forward(stderr: &str) -> bool {
+    stderr.contains("Your local changes to the following files would be overwritten")
+        || stderr.contains("Not possible to fast-forward")
+}
+
+/// After `git fetch --prune` succeeds, fast-forward the current branch with
+/// `git pull --ff-only`, counting the commits it brought in. The reported
+/// output is the pull's, with the fetch's output appended so pruned refs are
+/// still counted.
+fn fast_forward_after_fetch(
+    repo: &Path,
+    settings: &CommandSettings,
+    fetch: Output,
+    url_rewrite: Option<&UrlRewrite>,
+) -> std::io::Result<Output> {
+    if !fetch.status.success() {
+        return Ok(fetch);
+    }
+    let pull_args = vec!["pull".to_string(), "--ff-only".to_string()];
+    let mut pull = settings.command(repo, pull_args).run(url_rewrite)?;
+
+    let pulled = String::from_utf8_lossy(&pull.stdout).into_owned();
+    if pull.status.success()
+        && let Some(range) = fast_forward_range(&pulled)
+    {
+        let count_args = vec![
+            "rev-list".to_string(),
+            "--count".to_string(),
+            range.to_string(),
+        ];
+        let count = settings.command(repo, count_args).run(None)?;
+        if count.status.success() {
+            let count = String::from_utf8_lossy(&count.stdout).trim().to_string();
+            let mut stdout = format!("{}{}\n", FAST_FORWARD_MARKER, count).into_bytes();
+            stdout.append(&mut pull.stdout);
+            pull.stdout = stdout;
+        }
+    }
+    pull.stdout.extend(fetch.stdout);
+    pull.stderr.extend(fetch.stderr);
+    Ok(pull)
+}
+
+struct SyncFormatter;
+
+impl SyncFormatter {
+    fn fast_forwarded(stdout: &str) -> Option<&str> {
+        stdout.lines().next()?.strip_prefix(FAST_FORWARD_MARKER)
+    }
+
+    fn pruned(output: &Output) -> usize {
+        count_pruned_refs(&String::from_utf8_lossy(&output.stdout))
+            + count_pruned_refs(&String::from_utf8_lossy(&output.stderr))
+    }
+}
+
+impl OutputFormatter for SyncFormatter {
+    /// "fetched, 2 stale pruned, fast-forwarded 4"
+    fn format(&self, output: &Output) -> String {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        let pull = if output.status.success() {
+            match Self::fast_forwarded(&stdout) {
+                Some(count) => format!("fast-forwarded {}", count),
+                None => "up to date".to_string(),
+            }
+        } else if is_blocked_fast_forward(&stderr) {
+            "local changes, skipped ff".to_string()
+        } else if is_no_tracking_error(&stderr) {
+            "no upstream".to_string()
+        } else {
+            return error_line(output);
+        };
+
+        let mut parts = vec!["fetched".to_string()];
+        let pruned = Self::pruned(output);
+        if pruned > 0 {
+            parts.push(format!("{} stale pruned", pruned));
+        }
+        parts.push(pull);
+        parts.join(", ")
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            if is_blocked_fast_forward(&stderr) {
+                return Category::Dirty;
+            }
+            if is_no_tracking_error(&stderr) {
+                return Category::Ok;
+            }
+            return Category::Error;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if Self::fast_forwarded(&stdout).is_some() || Self::pruned(output) > 0 {
+            Category::Updated
+        } else {
+            Category::Clean
+        }
+    }
+}
+
+/// Fetch with pruning, then fast-forward the current branch, each repo within
+/// one worker slot
+pub fn run(ctx: &ExecutionContext, repos: &[Repo], extra_args: &[String]) -> Result<()> {
+    let url_rewrite = ctx.url_rewrite();
+    let settings = ctx.command_settings();
+
+    run_parallel(
+        ctx,
+        repos,
+        |repo| {
+            let mut args = vec!["fetch".to_string(), "--prune".to_string()];
+            args.extend(extra_args.iter().cloned());
+            let path = repo.path().to_path_buf();
+            let settings = settings.clone();
+            let url_rewrite = url_rewrite.clone();
+            let cmd = GitCommand::new(path.clone(), args).with_followup(Box::new(move |fetch| {
+                fast_forward_after_fetch(&path, &settings, fetch, url_rewrite.as_ref())
+            }));
+            // As with pull, one object store (worktrees, alternates) syncs at a time
+            match repo.object_store() {
+                Some(store) => cmd.with_lock_group(store.to_path_buf()),
+                None => cmd,
+            }
+        },
+        &SyncFormatter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn make_output(stdout: &str, stderr: &str, code: i32) -> Output {
+        Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    const PRUNED: &str = "From github.com:org/api\n - [deleted]         (none)     -> origin/old\n - [deleted]         (none)     -> origin/older\n";
+
+    #[test]
+    fn test_fetched_pruned_and_fast_forwarded() {
+        let stdout = format!(
+            "{}4\nUpdating 3b7d4f1..42ef2a4\nFast-forward\n src/lib.rs | 2 +-\n",
+            FAST_FORWARD_MARKER
+        );
+        let output = make_output(&stdout, PRUNED, 0);
+        assert_eq!(
+            SyncFormatter.format(&output),
+            "fetched, 2 stale pruned, fast-forwarded 4"
+        );
+        assert_eq!(SyncFormatter.category(&output), Category::Updated);
+    }
+
+    #[test]
+    fn test_up_to_date() {
+        let output = make_output("Already up to date.\n", "", 0);
+        assert_eq!(SyncFormatter.format(&output), "fetched, up to date");
+        assert_eq!(SyncFormatter.category(&output), Category::Clean);
+    }
+
+    #[test]
+    fn test_blocked_fast_forward_is_skipped() {
+        for stderr in [
+            "error: Your local changes to the following files would be overwritten by merge:\n\tsrc/lib.rs\nPlease commit your changes or stash them before you merge.\nAborting\n",
+            "hint: Diverging branches can't be fast-forwarded, you need to either:\nfatal: Not possible to fast-forward, aborting.\n",
+        ] {
+            let output = make_output("Updating 3b7d4f1..42ef2a4\n", stderr, 128);
+            assert_eq!(
+                SyncFormatter.format(&output),
+                "fetched, local changes, skipped ff"
+            );
+            assert_eq!(SyncFormatter.category(&output), Category::Dirty);
+        }
+    }
+
+    #[test]
+    fn test_fetch_failure_is_error() {
+        let stderr = "fatal: 'origin' does not appear to be a git repository\nfatal: Could not read from remote repository.\n";
+        let output = make_output("", stderr, 128);
+        assert_eq!(
+            SyncFormatter.format(&output),
+            "fatal: 'origin' does not appear to be a git repository"
+        );
+        assert_eq!(SyncFormatter.category(&output), Category::Error);
+    }
+
+    #[test]
+    fn test_fast_forward_range() {
+        let stdout = "Updating 3b7d4f1..42ef2a4\nFast-forward\n";
+        assert_eq!(fast_forward_range(stdout), Some("3b7d4f1..42ef2a4"));
+        assert_eq!(fast_forward_range("Already up to date.\n"), None);
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::Result;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::repo::Repo;
+use crate::runner::{ExecutionContext, GitCommand, collect_parallel, error_line};
+
+/// `git log` format: committer timestamp, short hash, subject, tab-separated
+const LOG_FORMAT: &str = "--format=%ct%x09%h%x09%s";
+
+/// Options for the timeline command
+#[derive(Default, Clone)]
+pub struct TimelineOptions {
+    /// Passed to `git log --since`, e.g. "yesterday" or "2 days ago"
+    pub since: String,
+    /// Passed to `git log --author` when set
+    pub author: Option<String>,
+}
+
+/// One commit in the merged timeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TimelineEntry {
+    timestamp: i64,
+    repo: String,
+    hash: String,
+    subject: String,
+}
+
+fn build_args(opts: &TimelineOptions) -> Vec<String> {
+    let mut args = vec![
+        "log".to_string(),
+        format!("--since={}", opts.since),
+        LOG_FORMAT.to_string(),
+    ];
+    if let Some(ref author) = opts.author {
+        args.push(format!("--author={}", author));
+    }
+    args
+}
+
+/// Parse `LOG_FORMAT` lines; malformed lines are skipped
+fn parse_log(repo: &str, stdout: &str) -> Vec<TimelineEntry> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let timestamp = fields.next()?.parse().ok()?;
+            let hash = fields.next()?.to_string();
+            let subject = fields.next().unwrap_or("").to_string();
+            Some(TimelineEntry {
+                timestamp,
+                repo: repo.to_string(),
+                hash,
+                subject,
+            })
+        })
+        .collect()
+}
+
+/// `git log` fails in a repository with no commits yet; there is simply
+/// nothing to show there
+fn is_unborn_branch(stderr: &str) -> bool {
+    stderr.contains("does not have any commits")
+}
+
+/// Merge per-repo commit lists into one timeline, newest first.
+/// Commits with the same timestamp keep repo order, then log order.
+fn merge_timeline(per_repo: Vec<Vec<TimelineEntry>>) -> Vec<TimelineEntry> {
+    let mut entries: Vec<TimelineEntry> = per_repo.into_iter().flatten().collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+    entries
+}
+
+/// Short age like "45m ago", "3h ago" or "2d ago"
+fn relative_age(seconds: i64) -> String {
+    match seconds.max(0) {
+        s if s < 60 => "just now".to_string(),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86400),
+    }
+}
+
+/// Print commits from every repository as one timeline, newest first.
+pub fn run(ctx: &ExecutionContext, repos: &[Repo], opts: &TimelineOptions) -> Result<()> {
+    let args = build_args(opts);
+    let results = collect_parallel(ctx, repos, |repo| {
+        GitCommand::new(repo.path().to_path_buf(), args.clone())
+    });
+
+    let mut per_repo = Vec::new();
+    for (repo, result) in results {
+        let name = ctx.display_name(&repo);
+        match result {
+            Ok(output) if output.status.success() => {
+                per_repo.push(parse_log(&name, &String::from_utf8_lossy(&output.stdout)));
+            }
+            Ok(output) if is_unborn_branch(&String::from_utf8_lossy(&output.stderr)) => {}
+            Ok(output) => eprintln!("[{}] ERROR: {}", name, error_line(&output)),
+            Err(e) => eprintln!("[{}] ERROR: {}", name, e),
+        }
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    for entry in merge_timeline(per_repo) {
+        println!(
+            "[{}] {} {} ({})",
+            entry.repo,
+            entry.hash,
+            entry.subject,
+            relative_age(now - entry.timestamp)
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: i64, repo: &str, hash: &str) -> TimelineEntry {
+        TimelineEntry {
+            timestamp,
+            repo: repo.to_string(),
+            hash: hash.to_string(),
+            subject: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let stdout =
+            "1700000300\ta1b2c3d\tFix login\tredirect\n1700000000\te4f5a6b\t\nnot a commit\n";
+        assert_eq!(
+            parse_log("api", stdout),
+            vec![
+                TimelineEntry {
+                    timestamp: 1700000300,
+                    repo: "api".to_string(),
+                    hash: "a1b2c3d".to_string(),
+                    subject: "Fix login\tredirect".to_string(),
+                },
+                entry(1700000000, "api", "e4f5a6b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_timeline_sorts_across_repos_by_time() {
+        let api = vec![entry(300, "api", "a3"), entry(100, "api", "a1")];
+        let web = vec![
+            entry(400, "web", "w4"),
+            entry(200, "web", "w2"),
+            entry(100, "web", "w1"),
+        ];
+
+        let hashes: Vec<String> = merge_timeline(vec![api, web])
+            .into_iter()
+            .map(|e| e.hash)
+            .collect();
+        assert_eq!(hashes, vec!["w4", "a3", "w2", "a1", "w1"]);
+    }
+
+    #[test]
+    fn test_unborn_branch_is_not_an_error() {
+        let stderr = "fatal: your current branch 'main' does not have any commits yet\n";
+        assert!(is_unborn_branch(stderr));
+        assert!(!is_unborn_branch("fatal: bad revision 'HEAD'\n"));
+    }
+
+    #[test]
+    fn test_relative_age() {
+        assert_eq!(relative_age(5), "just now");
+        assert_eq!(relative_age(45 * 60), "45m ago");
+        assert_eq!(relative_age(2 * 3600 + 59), "2h ago");
+        assert_eq!(relative_age(3 * 86400), "3d ago");
+        assert_eq!(relative_age(-10), "just now");
+    }
+
+    #[test]
+    fn test_build_args_with_author() {
+        let opts = TimelineOptions {
+            since: "yesterday".to_string(),
+            author: Some("sam".to_string()),
+        };
+        assert_eq!(
+            build_args(&opts),
+            vec!["log", "--since=yesterday", LOG_FORMAT, "--author=sam"]
+        );
+    }
+}
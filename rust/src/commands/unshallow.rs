@@ -0,0 +1,46 @@
+use anyhow::Result;
+use std::process::Output;
+
+use crate::repo::{Repo, is_shallow};
+use crate::runner::{
+    Category, ExecutionContext, GitCommand, OutputFormatter, error_line, run_parallel,
+};
+
+struct UnshallowFormatter;
+
+impl OutputFormatter for UnshallowFormatter {
+    fn format(&self, output: &Output) -> String {
+        if !output.status.success() {
+            return error_line(output);
+        }
+
+        "unshallowed".to_string()
+    }
+
+    fn category(&self, output: &Output) -> Category {
+        if output.status.success() {
+            Category::Updated
+        } else {
+            Category::Error
+        }
+    }
+}
+
+/// Fetch full history for shallow clones; repos that are already complete are skipped.
+pub fn run(ctx: &ExecutionContext, repos: &[Repo], extra_args: &[String]) -> Result<()> {
+    let formatter = UnshallowFormatter;
+
+    run_parallel(
+        ctx,
+        repos,
+        |repo| {
+            if !is_shallow(repo) {
+                return GitCommand::skipped(repo.path().to_path_buf(), "(already full)");
+            }
+            let mut args = vec!["fetch".to_string(), "--unshallow".to_string()];
+            args.extend(extra_args.iter().cloned());
+            GitCommand::new(repo.path().to_path_buf(), args)
+        },
+        &formatter,
+    )
+}
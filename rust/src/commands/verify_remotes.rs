@@ -0,0 +1,91 @@
+use anyhow::Result;
+use std::process::Output;
+
+use crate::repo::Repo;
+use crate::runner::{ExecutionContext, GitCommand, OutputFormatter, run_parallel};
+
+/// Reported for repos without an `origin` remote
+const NO_ORIGIN: &str = "(no origin)";
+
+struct VerifyRemotesFormatter;
+
+impl OutputFormatter for VerifyRemotesFormatter {
+    /// git's reason for an unreachable remote is kept for `--failures-at-end`
+    fn format(&self, output: &Output) -> String {
+        if !output.status.success() {
+            return "UNREACHABLE".to_string();
+        }
+
+        let refs = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .count();
+        format!("ok ({} ref{})", refs, if refs == 1 { "" } else { "s" })
+    }
+}
+
+/// Check that every repo's origin answers `git ls-remote --heads`, reporting
+/// how many branches it advertises. Repos without an origin are skipped.
+pub fn run(ctx: &ExecutionContext, repos: &[Repo], extra_args: &[String]) -> Result<()> {
+    run_parallel(
+        ctx,
+        repos,
+        |repo| {
+            if repo.origin_url().is_none() {
+                return GitCommand::skipped(repo.path().to_path_buf(), NO_ORIGIN);
+            }
+            let mut args = vec!["ls-remote".to_string(), "--heads".to_string()];
+            args.extend(extra_args.iter().cloned());
+            args.push("origin".to_string());
+            GitCommand::new(repo.path().to_path_buf(), args)
+        },
+        &VerifyRemotesFormatter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::Category;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn make_output(stdout: &str, stderr: &str, code: i32) -> Output {
+        Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_counts_advertised_refs() {
+        let stdout = "3b7d4f1\trefs/heads/main\n42ef2a4\trefs/heads/release\n";
+        let output = make_output(stdout, "", 0);
+        assert_eq!(VerifyRemotesFormatter.format(&output), "ok (2 refs)");
+        assert_eq!(VerifyRemotesFormatter.category(&output), Category::Ok);
+
+        let single = make_output("3b7d4f1\trefs/heads/main\n", "", 0);
+        assert_eq!(VerifyRemotesFormatter.format(&single), "ok (1 ref)");
+    }
+
+    #[test]
+    fn test_empty_remote_is_reachable() {
+        assert_eq!(
+            VerifyRemotesFormatter.format(&make_output("", "", 0)),
+            "ok (0 refs)"
+        );
+    }
+
+    #[test]
+    fn test_errors_are_unreachable() {
+        for stderr in [
+            "ssh: Could not resolve hostname gone.example.com: Name or service not known\nfatal: Could not read from remote repository.\n",
+            "remote: Repository not found.\nfatal: repository 'https://github.com/org/gone/' not found\n",
+        ] {
+            let output = make_output("", stderr, 128);
+            assert_eq!(VerifyRemotesFormatter.format(&output), "UNREACHABLE");
+            assert_eq!(VerifyRemotesFormatter.category(&output), Category::Error);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Exit status after Ctrl-C, as shells report a SIGINT death (128 + 2)
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// How often the watcher checks for an interrupt
+const WATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Process ids of running children, terminated on interrupt
+fn children() -> &'static Mutex<HashSet<u32>> {
+    static CHILDREN: OnceLock<Mutex<HashSet<u32>>> = OnceLock::new();
+    CHILDREN.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Whether Ctrl-C has been pressed during this run
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Keeps a child's pid registered while it runs; unregisters on drop
+pub struct ChildGuard(u32);
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        children().lock().unwrap().remove(&self.0);
+    }
+}
+
+/// Register a spawned child so an interrupt can terminate it
+pub fn track(pid: u32) -> ChildGuard {
+    children().lock().unwrap().insert(pid);
+    ChildGuard(pid)
+}
+
+#[cfg(unix)]
+extern "C" fn on_sigint(_: libc::c_int) {
+    // A second Ctrl-C gives up on a clean shutdown
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        // SAFETY: _exit is async-signal-safe
+        unsafe { libc::_exit(INTERRUPTED_EXIT_CODE) };
+    }
+}
+
+/// Handle Ctrl-C by terminating running children instead of dying at once.
+///
+/// The handler only sets a flag (little else is safe in a signal handler); a
+/// watcher thread sends SIGTERM to every tracked child once it is set, so git
+/// can remove its lock files, and no new commands start afterwards. The run
+/// then finishes reporting and exits with `INTERRUPTED_EXIT_CODE`.
+#[cfg(unix)]
+pub fn install() {
+    // SAFETY: on_sigint only touches an atomic and calls _exit
+    unsafe { libc::signal(libc::SIGINT, on_sigint as *const () as libc::sighandler_t) };
+    std::thread::spawn(|| {
+        loop {
+            if interrupted() {
+                for &pid in children().lock().unwrap().iter() {
+                    // SAFETY: kill has no memory-safety preconditions. A pid is
+                    // untracked right after its child is reaped, leaving only a
+                    // tiny window in which it could have been reused
+                    unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+                }
+            }
+            std::thread::sleep(WATCH_INTERVAL);
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn install() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_track_registers_until_dropped() {
+        let guard = track(u32::MAX);
+        assert!(children().lock().unwrap().contains(&u32::MAX));
+        drop(guard);
+        assert!(!children().lock().unwrap().contains(&u32::MAX));
+    }
+}
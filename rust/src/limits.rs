@@ -0,0 +1,160 @@
+use std::path::Path;
+
+/// Where the kernel exposes this process's cgroup (its own cgroup in a container)
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// File descriptors held per running git child: three stdio pipes on our side
+/// while it runs, plus slack for the pipes git opens to its own helpers.
+const FDS_PER_WORKER: u64 = 6;
+
+/// Descriptors kept free for git-all itself (stdio, cache/resume files, repo scans)
+const RESERVED_FDS: u64 = 32;
+
+/// Soft limit on open files for this process, if it can be read
+#[cfg(unix)]
+pub fn open_files_soft_limit() -> Option<u64> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: getrlimit only writes into the struct we pass it
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return None;
+    }
+    if limit.rlim_cur == libc::RLIM_INFINITY {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)] // rlim_t is not u64 on every unix
+    Some(limit.rlim_cur as u64)
+}
+
+#[cfg(not(unix))]
+pub fn open_files_soft_limit() -> Option<u64> {
+    None
+}
+
+/// Most workers that fit under an open-file soft limit (always at least one)
+pub fn worker_cap(soft_limit: u64) -> usize {
+    let available = soft_limit.saturating_sub(RESERVED_FDS) / FDS_PER_WORKER;
+    available.max(1) as usize
+}
+
+/// Worker count to use when `requested` would exceed the cap for `soft_limit`.
+/// Returns None when no clamp is needed; a request of 0 (unlimited) is always clamped.
+pub fn clamp_workers(requested: usize, soft_limit: u64) -> Option<usize> {
+    let cap = worker_cap(soft_limit);
+    if requested == 0 || requested > cap {
+        Some(cap)
+    } else {
+        None
+    }
+}
+
+/// Whole CPUs a CFS quota of `quota` per `period` microseconds amounts to,
+/// rounded up so a 1.5 CPU quota still gets two workers
+fn cpus_from_quota(quota: u64, period: u64) -> Option<usize> {
+    if quota == 0 || period == 0 {
+        return None;
+    }
+    Some(quota.div_ceil(period) as usize)
+}
+
+/// CPUs allowed by a cgroup CPU quota under `root`: cgroup v2's `cpu.max`
+/// ("200000 100000", or "max ..." for no quota), else cgroup v1's
+/// `cpu/cpu.cfs_quota_us` (-1 for no quota) and `cpu/cpu.cfs_period_us`.
+pub fn cgroup_cpu_limit(root: &Path) -> Option<usize> {
+    let read = |file: &str| std::fs::read_to_string(root.join(file)).ok();
+    if let Some(max) = read("cpu.max") {
+        let mut fields = max.split_whitespace();
+        let quota = fields.next()?.parse().ok()?;
+        let period = fields.next()?.parse().ok()?;
+        return cpus_from_quota(quota, period);
+    }
+    let quota = read("cpu/cpu.cfs_quota_us")?.trim().parse().ok()?;
+    let period = read("cpu/cpu.cfs_period_us")?.trim().parse().ok()?;
+    cpus_from_quota(quota, period)
+}
+
+/// CPUs the environment makes available: the `NPROC` hint CI systems set
+/// (passed in as `nproc`), else a cgroup CPU quota under `cgroup_root`, else
+/// what the OS reports
+pub fn cpu_hint(nproc: Option<&str>, cgroup_root: &Path) -> Option<usize> {
+    nproc
+        .and_then(|n| n.trim().parse().ok())
+        .filter(|&n: &usize| n > 0)
+        .or_else(|| cgroup_cpu_limit(cgroup_root))
+        .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worker_cap_leaves_headroom() {
+        assert_eq!(worker_cap(1024), 165);
+        assert_eq!(worker_cap(256), 37);
+    }
+
+    #[test]
+    fn test_worker_cap_never_zero() {
+        assert_eq!(worker_cap(0), 1);
+        assert_eq!(worker_cap(40), 1);
+    }
+
+    #[test]
+    fn test_clamp_workers_when_ulimit_is_low() {
+        assert_eq!(clamp_workers(100, 64), Some(5));
+        assert_eq!(clamp_workers(0, 64), Some(5));
+        assert_eq!(clamp_workers(4, 64), None);
+        assert_eq!(clamp_workers(8, 1024), None);
+    }
+
+    #[test]
+    fn test_cgroup_v2_cpu_max() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("cpu.max"), "150000 100000\n").unwrap();
+        assert_eq!(cgroup_cpu_limit(root.path()), Some(2));
+
+        std::fs::write(root.path().join("cpu.max"), "50000 100000\n").unwrap();
+        assert_eq!(cgroup_cpu_limit(root.path()), Some(1));
+
+        std::fs::write(root.path().join("cpu.max"), "max 100000\n").unwrap();
+        assert_eq!(cgroup_cpu_limit(root.path()), None);
+    }
+
+    #[test]
+    fn test_cgroup_v1_cfs_quota() {
+        let root = tempfile::tempdir().unwrap();
+        let cpu = root.path().join("cpu");
+        std::fs::create_dir(&cpu).unwrap();
+        std::fs::write(cpu.join("cpu.cfs_period_us"), "100000\n").unwrap();
+        std::fs::write(cpu.join("cpu.cfs_quota_us"), "400000\n").unwrap();
+        assert_eq!(cgroup_cpu_limit(root.path()), Some(4));
+
+        std::fs::write(cpu.join("cpu.cfs_quota_us"), "-1\n").unwrap();
+        assert_eq!(cgroup_cpu_limit(root.path()), None);
+    }
+
+    #[test]
+    fn test_cpu_hint_prefers_nproc_then_cgroup() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("cpu.max"), "200000 100000\n").unwrap();
+        assert_eq!(cpu_hint(Some("3"), root.path()), Some(3));
+        assert_eq!(cpu_hint(Some("0"), root.path()), Some(2));
+        assert_eq!(cpu_hint(Some("lots"), root.path()), Some(2));
+        assert_eq!(cpu_hint(None, root.path()), Some(2));
+
+        let unlimited = tempfile::tempdir().unwrap();
+        let os_cpus = std::thread::available_parallelism().ok().map(|n| n.get());
+        assert_eq!(cpu_hint(None, unlimited.path()), os_cpus);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_files_soft_limit_reads_process_limit() {
+        if let Some(limit) = open_files_soft_limit() {
+            assert!(limit > 0);
+        }
+    }
+}
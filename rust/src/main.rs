@@ -1,23 +1,52 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::{Duration, Instant};
 
 #[cfg(unix)]
 use std::os::unix::process::CommandExt;
 
+mod cache;
+mod color;
 mod commands;
+mod interrupt;
+mod limits;
 mod meta;
 mod repo;
+mod resume;
 mod runner;
+mod source;
+mod ssh;
+mod stats;
 
-use commands::{fetch, passthrough, pull, status};
-use repo::{find_git_repos_in, is_inside_git_repo, parse_scan_depth, ScanDepth};
-use runner::{ExecutionContext, UrlScheme};
+use color::{ColorBy, ColorMode};
+use commands::{
+    archive, bisect_run, clone, config, exec, fetch, passthrough, prune_worktrees, pull, push,
+    rebase, status, switch, sync, timeline, unshallow, verify_remotes,
+};
+use repo::{
+    IGNORE_FILE, Repo, ScanDepth, glob_matches, ignore_patterns, is_inside_git_repo,
+    parse_scan_depth, repo_name,
+};
+use runner::{
+    Category, ExecutionContext, Highlight, NameReplace, OutputSort, RepoLabels, SummarySort,
+    UrlScheme,
+};
+use source::{GitConfigSource, RepoSource, ScanSource, StdinSource};
+use stats::RunStats;
 
 #[derive(Parser)]
-#[command(name = "git-all", version, about = "parallel git across many repositories")]
+#[command(
+    name = "git-all",
+    version,
+    about = "parallel git across many repositories"
+)]
 struct Cli {
-    /// Print exact commands without executing
+    /// Print exact commands without executing. Must come before the subcommand;
+    /// after it (e.g. `fetch --dry-run`) the flag is passed to git instead.
     #[arg(long)]
     dry_run: bool,
 
@@ -29,14 +58,210 @@ struct Cli {
     #[arg(long, conflicts_with = "ssh")]
     https: bool,
 
+    /// Host whose URLs --ssh/--https rewrite (default: github.com; repeatable)
+    #[arg(long, value_name = "DOMAIN")]
+    host: Vec<String>,
+
+    /// Hand out worker slots first come, first served so a slow repo early in
+    /// the list can't be overtaken indefinitely; on a terminal, show how many
+    /// finished repos are waiting to be printed behind it
+    #[arg(long)]
+    fair: bool,
+
+    /// Run git without system or user config (GIT_CONFIG_NOSYSTEM=1, HOME set to
+    /// an empty location) so results don't depend on the machine's gitconfig.
+    /// Credential helpers, aliases and url rewrites from those files are lost too.
+    #[arg(long)]
+    isolated: bool,
+
+    /// Share one SSH connection per host across all repos (OpenSSH ControlMaster)
+    #[arg(long)]
+    ssh_multiplex: bool,
+
+    /// Kill any git process still running after SECONDS and report TIMEOUT for
+    /// its repo (per process, so slow repos don't eat into each other's time)
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
     /// Number of parallel workers (default: 8, 0 = unlimited)
     #[arg(short = 'n', long, default_value = "8")]
     workers: usize,
 
+    /// Run across child repositories even from inside a git repository,
+    /// instead of passing the command straight through to git
+    #[arg(long)]
+    no_passthrough: bool,
+
+    /// Lower the worker count if it could exceed the open-file limit (ulimit -n)
+    #[arg(long)]
+    parallelism_cap_from_ulimit: bool,
+
+    /// Lower the default worker count to the CPUs CI allows: NPROC, else a
+    /// cgroup CPU quota, else the CPU count (an explicit -n is kept)
+    #[arg(long)]
+    parallel_limit_env: bool,
+
     /// How deep to scan for repositories (positive integer or "all")
-    #[arg(long, default_value = "1", value_parser = parse_scan_depth, value_name = "DEPTH|all")]
+    #[arg(
+        long,
+        visible_alias = "max-depth",
+        default_value = "1",
+        value_parser = parse_scan_depth,
+        value_name = "DEPTH|all"
+    )]
     scan_depth: ScanDepth,
 
+    /// Cache the discovered repo list, revalidated against directory mtimes
+    #[arg(long)]
+    cache: bool,
+
+    /// Rescan and check the repo cache, refreshing it if stale (implies --cache)
+    #[arg(long)]
+    verify_cache: bool,
+
+    /// Use the repositories listed under git-all.repo in git config (multi-valued,
+    /// e.g. `git config --global --add git-all.repo ~/src/api`) instead of scanning
+    #[arg(long, conflicts_with_all = ["scan_depth", "cache", "verify_cache"])]
+    repos_from_gitconfig: bool,
+
+    /// Read repository paths from stdin, one per line, instead of scanning
+    #[arg(long, conflicts_with_all = ["scan_depth", "cache", "verify_cache", "repos_from_gitconfig"])]
+    from_stdin: bool,
+
+    /// Read NUL-separated repository paths from stdin (e.g. from `find -print0`)
+    #[arg(
+        long,
+        conflicts_with_all = ["scan_depth", "cache", "verify_cache", "repos_from_gitconfig", "from_stdin"]
+    )]
+    from_stdin0: bool,
+
+    /// Also discover bare repositories (e.g. `git clone --mirror` directories)
+    #[arg(long, conflicts_with_all = ["repos_from_gitconfig", "from_stdin", "from_stdin0"])]
+    include_bare: bool,
+
+    /// Only use repos whose directory name matches GLOB (`*` and `?` wildcards;
+    /// repeatable, matching any)
+    #[arg(long, value_name = "GLOB")]
+    filter: Vec<String>,
+
+    /// Skip repos whose directory name matches GLOB (repeatable; applied after --filter)
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Don't skip the repos listed in .git-all-ignore
+    #[arg(long)]
+    no_ignore: bool,
+
+    /// Skip repos already completed by an interrupted run of the same command.
+    /// Commands that change repos record progress on every run; it is cleared
+    /// once a run has no errors.
+    #[arg(long)]
+    resume: bool,
+
+    /// Color results by category: clean green, dirty yellow, errors red
+    /// (auto: only on a terminal, and not when NO_COLOR is set)
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorMode::Auto)]
+    color: ColorMode,
+
+    /// Color repo names by a stable hash of the repo name or its origin host
+    #[arg(long, value_enum, value_name = "repo|host")]
+    color_by: Option<ColorBy>,
+
+    /// With --color-by, explain what the colors mean before the results
+    #[arg(long, requires = "color_by")]
+    legend: bool,
+
+    /// Rewrite displayed repo names, e.g. '^company-service-=' (display only)
+    #[arg(long, value_parser = NameReplace::parse, value_name = "REGEX=REPLACEMENT")]
+    name_replace: Option<NameReplace>,
+
+    /// Show friendly labels from a file of `repo-dir-name=Label` lines (display only)
+    #[arg(long, value_parser = RepoLabels::load, value_name = "FILE")]
+    labels: Option<RepoLabels>,
+
+    /// Buffer output and print repos with identical results together
+    #[arg(long)]
+    group_identical: bool,
+
+    /// Buffer output and print repos under a header for their parent directory
+    #[arg(long, conflicts_with = "group_identical")]
+    group_by_dir: bool,
+
+    /// Print every output line of exec and passthrough commands, each prefixed
+    /// with its repo (like grep -H), instead of one summary line per repo
+    #[arg(long, conflicts_with_all = ["group_identical", "json", "count_by_category"])]
+    prefix_lines: bool,
+
+    /// Order of grouped output: largest groups first, or alphabetical by summary
+    #[arg(long, value_enum, default_value_t = SummarySort::Count, value_name = "count|name")]
+    summary_sort: SummarySort,
+
+    /// Order of per-repo output: by name, by path, or by status (errors and
+    /// dirty repos first, clean last; prints once every repo has finished)
+    #[arg(long, value_enum, default_value_t = OutputSort::Name, value_name = "name|status|path")]
+    sort: OutputSort,
+
+    /// Print only result tallies as key=value pairs (e.g. clean=42 dirty=3 error=0)
+    #[arg(long)]
+    count_by_category: bool,
+
+    /// Finish with a count of results per category ("47 repos: 45 ok, 2 failed")
+    #[arg(long)]
+    summary: bool,
+
+    /// Don't print the per-category count that otherwise ends every run on stderr
+    #[arg(long, conflicts_with = "summary")]
+    no_summary: bool,
+
+    /// After all output, list the failed repositories again with their first error line
+    #[arg(long)]
+    failures_at_end: bool,
+
+    /// Treat stderr of successful commands as output when summarizing
+    /// (for commands like push that report results on stderr)
+    #[arg(long)]
+    stderr_merge: bool,
+
+    /// Emphasize text matching REGEX in summaries (bold/underline on a terminal,
+    /// >>match<< otherwise)
+    #[arg(long, value_parser = Highlight::parse, value_name = "REGEX")]
+    highlight: Option<Highlight>,
+
+    /// Print one JSON object per repository (JSON Lines) instead of text
+    #[arg(long)]
+    json: bool,
+
+    /// Show how long each repository took, plus the total (duration_ms/total_ms with --json)
+    #[arg(long)]
+    timings: bool,
+
+    /// Print discovery and run statistics to stderr when done: dirs scanned,
+    /// repos found and filtered out (by reason), results by category, total time
+    #[arg(long)]
+    stats: bool,
+
+    /// Exit with status 1 if any repository is dirty (decided from every repo,
+    /// including ones hidden by --only-dirty or other display filters)
+    #[arg(long)]
+    dirty_exit_code: bool,
+
+    /// Exit with status 1 if any repository's command failed or couldn't run
+    #[arg(long)]
+    strict: bool,
+
+    /// Run destructive commands (reset, restore, clean --force) without asking
+    #[arg(long, visible_alias = "confirm")]
+    yes: bool,
+
+    /// Print only repositories with local changes (display only)
+    #[arg(long)]
+    only_dirty: bool,
+
+    /// Truncate each printed line to N columns with an ellipsis (default: terminal
+    /// width; 0 = never truncate)
+    #[arg(long, value_name = "N")]
+    max_line_width: Option<usize>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -45,25 +270,248 @@ struct Cli {
 enum Commands {
     /// Pull all repositories
     Pull {
+        /// Prune stale remote-tracking branches while pulling
+        #[arg(long)]
+        prune: bool,
+
+        /// If a branch has no upstream, track origin/<branch> and pull again
+        #[arg(long)]
+        set_upstream_if_missing: bool,
+
+        /// Pull only repos whose upstream has commits HEAD lacks (checked with ls-remote)
+        #[arg(long)]
+        only_behind: bool,
+
+        /// Rebase local commits onto the upstream instead of merging
+        #[arg(long)]
+        rebase: bool,
+
         /// Additional arguments to pass to git pull
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
+    /// Push each repo's current branch to its upstream, skipping repos with nothing to push
+    Push {
+        /// Additional arguments to pass to git push, e.g. --force-with-lease
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Switch every repo that has BRANCH to it; repos with local changes in the way are skipped
+    Switch {
+        /// Create BRANCH in every repo first (git switch -c)
+        #[arg(short = 'c', long)]
+        create: bool,
+
+        /// Branch to switch to
+        branch: String,
+
+        /// Additional arguments to pass to git switch
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Fetch with --prune, then fast-forward the current branch (git pull --ff-only)
+    Sync {
+        /// Additional arguments to pass to git fetch
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
     /// Fetch all repositories
     Fetch {
+        /// Write the commit-graph during fetch (fetch.writeCommitGraph, git 2.24+)
+        #[arg(long)]
+        commit_graph: bool,
+
+        /// Also fetch submodules (MODE: yes, on-demand, no)
+        #[arg(
+            long,
+            value_name = "MODE",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = ""
+        )]
+        recurse_submodules: Option<String>,
+
+        /// Fetch each remote of multi-remote repos as a separate parallel fetch
+        /// (each uses a worker slot)
+        #[arg(long)]
+        parallel_remotes: bool,
+
+        /// Allow updating the ref of the checked-out branch (always on for bare repos)
+        #[arg(long)]
+        update_head_ok: bool,
+
+        /// Skip repos fetched within the last SECONDS (per .git/FETCH_HEAD's mtime)
+        #[arg(long, value_name = "SECONDS")]
+        min_interval: Option<u64>,
+
+        /// Report how many commits arrived on the default branch (origin/HEAD),
+        /// e.g. "main: 12 new commits"
+        #[arg(long)]
+        report_new_commits_count: bool,
+
+        /// Update bare mirrors to match origin (fetch --prune origin '+refs/*:refs/*');
+        /// implies --include-bare, and skips repos with a working tree
+        #[arg(
+            long,
+            conflicts_with_all = ["parallel_remotes", "recurse_submodules", "report_new_commits_count"]
+        )]
+        mirror_update: bool,
+
         /// Additional arguments to pass to git fetch
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
     /// Status of all repositories
     Status {
+        /// Show commits ahead/behind REF (e.g. origin/main) instead of file changes
+        #[arg(long, value_name = "REF")]
+        compare: Option<String>,
+
+        /// Only show repos with a changed path containing TEXT, and the matching lines
+        #[arg(long, value_name = "TEXT", conflicts_with = "compare")]
+        find: Option<String>,
+
+        /// Only show repos with a changed path matching GLOB (e.g. '*.proto'; a
+        /// GLOB without / matches file names in any directory), and the matching lines
+        #[arg(long, value_name = "GLOB", conflicts_with_all = ["compare", "find"])]
+        changed_files_match: Option<String>,
+
+        /// Don't count submodule changes (WHEN: none, untracked, dirty, all; default all)
+        #[arg(
+            long,
+            value_name = "WHEN",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "all",
+            conflicts_with = "compare"
+        )]
+        ignore_submodules: Option<String>,
+
+        /// Only show repos currently on BRANCH (repeatable; detached HEADs never match)
+        #[arg(long, value_name = "BRANCH")]
+        on_branch: Vec<String>,
+
+        /// Only show repos not on BRANCH (repeatable; includes detached HEADs)
+        #[arg(long, value_name = "BRANCH")]
+        not_on_branch: Vec<String>,
+
+        /// Count only staged (index) changes
+        #[arg(long, conflicts_with_all = ["compare", "find", "changed_files_match", "unstaged_only"])]
+        staged_only: bool,
+
+        /// Count only unstaged (worktree) changes, untracked files included
+        #[arg(long, conflicts_with_all = ["compare", "find", "changed_files_match"])]
+        unstaged_only: bool,
+
+        /// With --json, list each changed path with its index and worktree status
+        #[arg(long, conflicts_with_all = ["compare", "find", "changed_files_match"])]
+        files: bool,
+
+        /// Show warnings git prints on a successful status (e.g. about
+        /// .gitattributes) after the summary; they never change the counts
+        #[arg(long, conflicts_with_all = ["compare", "find", "changed_files_match"])]
+        verbose: bool,
+
+        /// Report files marked assume-unchanged or skip-worktree that were
+        /// modified anyway, which plain status never shows
+        #[arg(
+            long,
+            conflicts_with_all = ["compare", "find", "changed_files_match", "files", "verbose"]
+        )]
+        hidden_changes: bool,
+
         /// Additional arguments to pass to git status
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
-    /// git-all internal commands (help, version info)
+    /// Recent commits from all repositories as one timeline, newest first
+    Timeline {
+        /// Show commits more recent than this (any date git log --since accepts)
+        #[arg(long, default_value = "1 day ago", value_name = "DATE")]
+        since: String,
+
+        /// Only show commits by a matching author
+        #[arg(long, value_name = "PATTERN")]
+        author: Option<String>,
+    },
+    /// Write a .tar.gz of each repository's HEAD
+    Archive {
+        /// Directory to write archives to (created if missing)
+        #[arg(long, default_value = ".")]
+        output_dir: PathBuf,
+
+        /// Additional arguments to pass to git archive
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Rebase all repositories (e.g. --onto X Y), flagging repos stopped on conflicts
+    Rebase {
+        /// Additional arguments to pass to git rebase
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Read a config value in every repository, or set it when VALUE is given
+    Config {
+        /// Config key, e.g. pull.rebase
+        key: String,
+
+        /// Value to set; omit to print the current value
+        value: Option<String>,
+    },
+    /// Fetch full history for shallow clones (git fetch --unshallow)
+    Unshallow {
+        /// Additional arguments to pass to git fetch
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Prune administrative files of deleted worktrees (git worktree prune)
+    PruneWorktrees {
+        /// Additional arguments to pass to git worktree prune
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Check that every origin remote is reachable (git ls-remote --heads origin)
+    /// and report how many branches it has
+    VerifyRemotes {
+        /// Additional arguments to pass to git ls-remote
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Run a test command in every repository's checkout and report pass/fail
+    /// (exits non-zero if any repository fails)
+    BisectRun {
+        /// Command and arguments to run in each repository, e.g. -- ./test.sh
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Run any command in every repository; {repo} and {name} in its arguments
+    /// are replaced with each repository's path and name
+    Exec {
+        /// Run the command with `sh -c`, so pipes, globs and && work. The command
+        /// is shell code run in every repo: never build it from untrusted input.
+        /// {repo} and {name} are substituted shell-quoted.
+        #[arg(long)]
+        shell: bool,
+
+        /// Command and arguments, e.g. -- cp template.conf {repo}/config.conf
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Clone repositories into the current directory in parallel, each into a
+    /// directory named after its URL; existing directories are skipped
+    Clone {
+        /// File of repository URLs to clone, one per line (# comments allowed)
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+
+        /// Repository URLs, e.g. git@github.com:org/api.git, or manifest files
+        /// listing them, e.g. `git-all clone repos.txt`
+        #[arg(value_name = "URL|MANIFEST")]
+        urls: Vec<String>,
+    },
+    /// git-all internal commands (help, version info, discovery diagnostics)
     Meta {
-        /// Subcommand (help is the only option)
+        /// Subcommand: help, or discover [--explain]
         #[arg(trailing_var_arg = true)]
         args: Vec<String>,
     },
@@ -72,6 +520,87 @@ enum Commands {
     External(Vec<String>),
 }
 
+/// Exit status for `--dirty-exit-code` when any repository is dirty
+const DIRTY_EXIT_CODE: i32 = 1;
+
+/// Exit status for `--strict` when any repository's command failed
+const FAILURE_EXIT_CODE: i32 = 1;
+
+impl Commands {
+    /// Whether this command can throw away work, so it needs `--yes` or an
+    /// answer at the terminal before running
+    fn is_destructive(&self) -> bool {
+        match self {
+            Commands::External(args) => passthrough::is_destructive(args),
+            Commands::Switch { args, .. } => args
+                .iter()
+                .any(|arg| matches!(arg.as_str(), "--discard-changes" | "-f" | "--force")),
+            Commands::Pull { .. }
+            | Commands::Push { .. }
+            | Commands::Sync { .. }
+            | Commands::Fetch { .. }
+            | Commands::Status { .. }
+            | Commands::Timeline { .. }
+            | Commands::Archive { .. }
+            | Commands::Rebase { .. }
+            | Commands::Config { .. }
+            | Commands::Unshallow { .. }
+            | Commands::PruneWorktrees { .. }
+            | Commands::VerifyRemotes { .. }
+            | Commands::BisectRun { .. }
+            | Commands::Exec { .. }
+            | Commands::Clone { .. }
+            | Commands::Meta { .. } => false,
+        }
+    }
+
+    /// Whether this command changes repos, so every run of it records progress
+    /// that a later `--resume` can pick up from
+    fn records_progress(&self) -> bool {
+        match self {
+            Commands::Config { value, .. } => value.is_some(),
+            Commands::Status { .. }
+            | Commands::Timeline { .. }
+            | Commands::VerifyRemotes { .. }
+            | Commands::BisectRun { .. }
+            | Commands::Meta { .. } => false,
+            Commands::Pull { .. }
+            | Commands::Push { .. }
+            | Commands::Switch { .. }
+            | Commands::Sync { .. }
+            | Commands::Fetch { .. }
+            | Commands::Archive { .. }
+            | Commands::Rebase { .. }
+            | Commands::Unshallow { .. }
+            | Commands::PruneWorktrees { .. }
+            | Commands::Exec { .. }
+            | Commands::Clone { .. }
+            | Commands::External(_) => true,
+        }
+    }
+}
+
+/// Ask at the terminal whether to run a destructive command in `repo_count`
+/// repos. Without a terminal to ask, refuses: scripts must pass `--yes`.
+fn confirm_destructive(description: &str, repo_count: usize) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "refusing to run destructive '{}' without confirmation; pass --yes to proceed",
+            description
+        );
+    }
+    eprint!(
+        "git-all: '{}' can discard uncommitted work. Run it in {} repositories? [y/N] ",
+        description, repo_count
+    );
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(()),
+        _ => anyhow::bail!("aborted"),
+    }
+}
+
 /// Exec git with all original args, replacing the git-all process.
 /// This is used when git-all is invoked from inside a git repository.
 #[cfg(unix)]
@@ -93,28 +622,149 @@ fn passthrough_to_git() -> ! {
     std::process::exit(status.code().unwrap_or(1));
 }
 
+/// Whether `args` invoke one of git-all's own subcommands (status, fetch, pull,
+/// meta, ...). Those always run across repositories, even from inside a git
+/// repository; anything else, including args clap rejects, goes to git there.
+fn is_git_all_subcommand(args: &[String]) -> bool {
+    let argv = std::iter::once("git-all").chain(args.iter().map(String::as_str));
+    match Cli::try_parse_from(argv) {
+        Ok(cli) => !matches!(cli.command, None | Some(Commands::External(_))),
+        Err(_) => false,
+    }
+}
+
 fn main() -> Result<()> {
     let args: Vec<String> = std::env::args().skip(1).collect();
-    let is_meta = args.first().map(|s| s == "meta").unwrap_or(false);
+    // Checked before clap parses anything, since passthrough never returns
+    let no_passthrough = args.iter().any(|a| a == "--no-passthrough");
 
-    if !is_meta && is_inside_git_repo() {
+    if !no_passthrough && !is_git_all_subcommand(&args) && is_inside_git_repo() {
         passthrough_to_git();
     }
 
-    let cli = Cli::parse();
+    let mut command = Cli::command();
+    // version_info runs `git --version`; only pay for that when it's printed
+    if args.iter().any(|a| a == "-V" || a == "--version") {
+        command = command.version(meta::version_info());
+    }
+    let matches = command.get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     if let Some(Commands::Meta { args }) = &cli.command {
-        meta::run(args);
-        return Ok(());
+        return meta::run(args, cli.scan_depth);
+    }
+
+    if cli.prefix_lines
+        && !matches!(
+            cli.command,
+            Some(Commands::Exec { .. } | Commands::External(_))
+        )
+    {
+        anyhow::bail!("--prefix-lines applies only to exec and passthrough commands");
     }
 
+    let mut stats = cli.stats.then(|| RunStats::new(Instant::now()));
     let cwd = std::env::current_dir()?;
-    let repos = find_git_repos_in(&cwd, cli.scan_depth)?;
-    if repos.is_empty() {
+    let use_cache = cli.cache || cli.verify_cache;
+    let mirror_update = matches!(
+        cli.command,
+        Some(Commands::Fetch {
+            mirror_update: true,
+            ..
+        })
+    );
+    let source: Box<dyn RepoSource> = if cli.from_stdin || cli.from_stdin0 {
+        Box::new(StdinSource {
+            base: cwd.clone(),
+            null_delimited: cli.from_stdin0,
+        })
+    } else if cli.repos_from_gitconfig {
+        Box::new(GitConfigSource {
+            base: cwd.clone(),
+            config_file: None,
+        })
+    } else {
+        Box::new(ScanSource {
+            root: cwd.clone(),
+            scan_depth: cli.scan_depth,
+            include_bare: cli.include_bare || mirror_update,
+            cache: cache::default_cache_dir()
+                .filter(|_| use_cache)
+                .map(|dir| (dir, cli.verify_cache)),
+            discovery: Default::default(),
+        })
+    };
+    // clone creates its repositories rather than discovering them
+    let is_clone = matches!(cli.command, Some(Commands::Clone { .. }));
+    let repos = if is_clone {
+        Vec::new()
+    } else {
+        source.repos()?
+    };
+    let print_stats = |stats: &Option<RunStats>, categories: &[Category]| {
+        if let Some(stats) = stats {
+            stats.print(categories);
+        }
+    };
+    if let Some(stats) = &mut stats {
+        stats.discovered(source.discovery(), repos.len());
+    }
+    if repos.is_empty() && !is_clone {
         println!("No git repositories found in current directory");
+        print_stats(&stats, &[]);
+        return Ok(());
+    }
+    let matches_any = |globs: &[String], repo: &Repo| {
+        let name = repo_name(repo);
+        globs.iter().any(|glob| glob_matches(glob, &name))
+    };
+    let (repos, unmatched): (Vec<Repo>, Vec<Repo>) = repos
+        .into_iter()
+        .partition(|repo| cli.filter.is_empty() || matches_any(&cli.filter, repo));
+    if let Some(stats) = &mut stats {
+        stats.filter_out("filter", unmatched.len());
+    }
+    if repos.is_empty() && !is_clone {
+        println!("No repositories match --filter {}", cli.filter.join(", "));
+        print_stats(&stats, &[]);
+        return Ok(());
+    }
+    let ignored = if cli.no_ignore {
+        Vec::new()
+    } else {
+        ignore_patterns(&cwd)?
+    };
+    let (excluded, repos): (Vec<Repo>, Vec<Repo>) = repos
+        .into_iter()
+        .partition(|repo| matches_any(&cli.exclude, repo));
+    let (ignored_repos, repos): (Vec<Repo>, Vec<Repo>) = repos
+        .into_iter()
+        .partition(|repo| matches_any(&ignored, repo));
+    if let Some(stats) = &mut stats {
+        stats.filter_out("exclude", excluded.len());
+        stats.filter_out("ignore", ignored_repos.len());
+    }
+    if repos.is_empty() && !is_clone {
+        let mut sources = Vec::new();
+        if !cli.exclude.is_empty() {
+            sources.push(format!("--exclude {}", cli.exclude.join(", ")));
+        }
+        if !ignored.is_empty() {
+            sources.push(IGNORE_FILE.to_string());
+        }
+        println!("All repositories excluded by {}", sources.join(" and "));
+        print_stats(&stats, &[]);
         return Ok(());
     }
 
+    if let Some(command) = &cli.command
+        && command.is_destructive()
+        && !cli.yes
+        && !cli.dry_run
+    {
+        confirm_destructive(matches.subcommand_name().unwrap_or("command"), repos.len())?;
+    }
+
     let url_scheme = if cli.ssh {
         Some(UrlScheme::Ssh)
     } else if cli.https {
@@ -123,7 +773,106 @@ fn main() -> Result<()> {
         None
     };
 
-    let ctx = ExecutionContext::new(cli.dry_run, url_scheme, cli.workers, cwd);
+    let no_color = std::env::var("NO_COLOR").ok();
+    let color = cli
+        .color
+        .enabled(std::io::stdout().is_terminal(), no_color.as_deref());
+    let color_by = cli.color_by.filter(|_| color);
+
+    // A dry run changes nothing, so it leaves any recorded progress alone
+    let records_progress = cli.resume
+        || (!cli.dry_run && cli.command.as_ref().is_some_and(Commands::records_progress));
+    let run_state = match cache::default_cache_dir().filter(|_| records_progress) {
+        Some(state_dir) => {
+            let command_line: Vec<String> =
+                args.iter().filter(|a| *a != "--resume").cloned().collect();
+            Some(if cli.resume {
+                resume::RunState::for_run(&state_dir, &cwd, &command_line)
+            } else {
+                resume::RunState::start(&state_dir, &cwd, &command_line)
+            })
+        }
+        None => {
+            if cli.resume {
+                eprintln!(
+                    "git-all: --resume needs a cache directory (set XDG_CACHE_HOME or HOME); \
+                     running every repo"
+                );
+            }
+            None
+        }
+    };
+
+    let mut workers = cli.workers;
+    if cli.parallel_limit_env
+        && matches.value_source("workers") == Some(ValueSource::DefaultValue)
+        && let Some(cpus) = limits::cpu_hint(
+            std::env::var("NPROC").ok().as_deref(),
+            Path::new(limits::CGROUP_ROOT),
+        )
+        && cpus < workers
+    {
+        eprintln!(
+            "git-all: {} CPUs available; using {} workers instead of {}",
+            cpus, cpus, workers
+        );
+        workers = cpus;
+    }
+    if cli.parallelism_cap_from_ulimit
+        && let Some(soft_limit) = limits::open_files_soft_limit()
+        && let Some(capped) = limits::clamp_workers(workers, soft_limit)
+    {
+        eprintln!(
+            "git-all: open file limit is {}; using {} workers instead of {}",
+            soft_limit,
+            capped,
+            if workers == 0 {
+                "unlimited".to_string()
+            } else {
+                workers.to_string()
+            }
+        );
+        workers = capped;
+    }
+
+    // Held until the end of main; dropping it closes the shared connections
+    let ssh_multiplex = if cli.ssh_multiplex {
+        Some(ssh::SshMultiplex::new()?)
+    } else {
+        None
+    };
+    let ssh_command = ssh_multiplex
+        .as_ref()
+        .map(|m| m.ssh_command(std::env::var("GIT_SSH_COMMAND").ok().as_deref()));
+
+    let ctx = ExecutionContext::new(cli.dry_run, url_scheme, workers, cwd.clone())
+        .with_color_by(color_by)
+        .with_color_status(color)
+        .with_legend(cli.legend)
+        .with_grouping(cli.group_identical, cli.summary_sort)
+        .with_sort(cli.sort)
+        .with_group_by_dir(cli.group_by_dir)
+        .with_prefix_lines(cli.prefix_lines)
+        .with_count_by_category(cli.count_by_category)
+        .with_name_replace(cli.name_replace)
+        .with_labels(cli.labels)
+        .with_hosts(cli.host.clone())
+        .with_ssh_command(ssh_command)
+        .with_isolated(cli.isolated)
+        .with_timeout(cli.timeout.map(Duration::from_secs))
+        .with_run_state(run_state)
+        .with_stderr_merge(cli.stderr_merge)
+        .with_highlight(cli.highlight.map(|h| h.with_color(color)))
+        .with_json(cli.json)
+        .with_timings(cli.timings)
+        .with_summary(cli.summary)
+        .with_stderr_summary(!cli.no_summary)
+        .with_fair(cli.fair)
+        .with_failures_at_end(cli.failures_at_end)
+        .with_only_dirty(cli.only_dirty)
+        .with_max_line_width(cli.max_line_width);
+
+    interrupt::install();
 
     if cli.dry_run {
         println!(
@@ -132,10 +881,113 @@ fn main() -> Result<()> {
         );
     }
 
-    match cli.command {
-        Some(Commands::Pull { args }) => pull::run(&ctx, &repos, &args),
-        Some(Commands::Fetch { args }) => fetch::run(&ctx, &repos, &args),
-        Some(Commands::Status { args }) => status::run(&ctx, &repos, &args),
+    let result = match cli.command {
+        Some(Commands::Pull {
+            prune,
+            set_upstream_if_missing,
+            only_behind,
+            rebase,
+            args,
+        }) => {
+            let opts = pull::PullOptions {
+                prune,
+                set_upstream_if_missing,
+                only_behind,
+                rebase,
+            };
+            pull::run(&ctx, &repos, &opts, &args)
+        }
+        Some(Commands::Push { args }) => push::run(&ctx, &repos, &args),
+        Some(Commands::Sync { args }) => sync::run(&ctx, &repos, &args),
+        Some(Commands::Switch {
+            create,
+            branch,
+            args,
+        }) => switch::run(
+            &ctx,
+            &repos,
+            &switch::SwitchOptions { create },
+            &branch,
+            &args,
+        ),
+        Some(Commands::Fetch {
+            commit_graph,
+            recurse_submodules,
+            parallel_remotes,
+            update_head_ok,
+            min_interval,
+            report_new_commits_count,
+            mirror_update,
+            args,
+        }) => {
+            let opts = fetch::FetchOptions {
+                commit_graph,
+                recurse_submodules,
+                parallel_remotes,
+                update_head_ok,
+                min_interval: min_interval.map(Duration::from_secs),
+                report_new_commits: report_new_commits_count,
+                mirror_update,
+            };
+            fetch::run(&ctx, &repos, &opts, &args)
+        }
+        Some(Commands::Status {
+            compare,
+            find,
+            changed_files_match,
+            ignore_submodules,
+            on_branch,
+            not_on_branch,
+            staged_only,
+            unstaged_only,
+            files,
+            verbose,
+            hidden_changes,
+            args,
+        }) => {
+            let scope = if staged_only {
+                status::ChangeScope::Staged
+            } else if unstaged_only {
+                status::ChangeScope::Unstaged
+            } else {
+                status::ChangeScope::All
+            };
+            let find = find
+                .map(status::PathMatch::Contains)
+                .or(changed_files_match.map(status::PathMatch::Glob));
+            let opts = status::StatusOptions {
+                compare,
+                find,
+                ignore_submodules,
+                on_branch,
+                not_on_branch,
+                scope,
+                files,
+                verbose,
+                hidden_changes,
+            };
+            status::run(&ctx, &repos, &opts, &args)
+        }
+        Some(Commands::Timeline { since, author }) => {
+            let opts = timeline::TimelineOptions { since, author };
+            timeline::run(&ctx, &repos, &opts)
+        }
+        Some(Commands::Archive { output_dir, args }) => {
+            archive::run(&ctx, &repos, &output_dir, &args)
+        }
+        Some(Commands::Config { key, value }) => config::run(&ctx, &repos, &key, value.as_deref()),
+        Some(Commands::Rebase { args }) => rebase::run(&ctx, &repos, &args),
+        Some(Commands::Unshallow { args }) => unshallow::run(&ctx, &repos, &args),
+        Some(Commands::PruneWorktrees { args }) => prune_worktrees::run(&ctx, &repos, &args),
+        Some(Commands::VerifyRemotes { args }) => verify_remotes::run(&ctx, &repos, &args),
+        Some(Commands::BisectRun { command }) => bisect_run::run(&ctx, &repos, &command),
+        Some(Commands::Exec { shell, command }) => {
+            let opts = exec::ExecOptions { shell };
+            exec::run(&ctx, &repos, &opts, &command)
+        }
+        Some(Commands::Clone { manifest, urls }) => {
+            clone::run(&ctx, &cwd, &urls, manifest.as_deref())
+        }
         Some(Commands::External(args)) => passthrough::run(&ctx, &repos, &args),
         Some(Commands::Meta { .. }) => unreachable!(), // handled above
         None => {
@@ -143,5 +995,23 @@ fn main() -> Result<()> {
             println!("No command specified. Use --help for usage information.");
             Ok(())
         }
+    };
+    print_stats(&stats, &ctx.categories());
+    if interrupt::interrupted() {
+        drop(ssh_multiplex);
+        std::process::exit(interrupt::INTERRUPTED_EXIT_CODE);
+    }
+    result?;
+
+    // Exit codes come from every repo's category, not from what was printed,
+    // so display filters like --only-dirty never change them
+    if cli.strict && ctx.failure_count() > 0 {
+        drop(ssh_multiplex);
+        std::process::exit(FAILURE_EXIT_CODE);
+    }
+    if cli.dirty_exit_code && ctx.categories().contains(&Category::Dirty) {
+        drop(ssh_multiplex);
+        std::process::exit(DIRTY_EXIT_CODE);
     }
+    Ok(())
 }
@@ -1,17 +1,50 @@
+use anyhow::Result;
 use clap::CommandFactory;
+use std::path::Path;
 use std::process::Command;
 
 use crate::Cli;
+use crate::repo::{ScanDepth, find_git_repos_with, repo_display_name};
 
-pub fn run(args: &[String]) {
+pub fn run(args: &[String], scan_depth: ScanDepth) -> Result<()> {
     match args.first().map(|s| s.as_str()) {
         None | Some("help") => print_help(),
+        Some("discover") => {
+            let explain = args[1..].iter().any(|a| a == "--explain");
+            discover(&std::env::current_dir()?, scan_depth, explain)?;
+        }
         Some(unknown) => {
             eprintln!("Unknown meta subcommand: {}", unknown);
-            eprintln!("Available: help");
+            eprintln!("Available: help, discover [--explain]");
             std::process::exit(1);
         }
     }
+    Ok(())
+}
+
+/// List discovered repositories. With `explain`, print every directory the
+/// discovery walk visited and what it decided, to debug `--scan-depth`.
+fn discover(root: &Path, scan_depth: ScanDepth, explain: bool) -> Result<()> {
+    let repos = find_git_repos_with(root, scan_depth, false, &mut |path, depth, event| {
+        if explain {
+            println!(
+                "depth {:<3} {:<12} {}",
+                depth,
+                event.label(),
+                repo_display_name(path, root)
+            );
+        }
+    })?;
+
+    if explain {
+        println!();
+        println!("{} repositories found", repos.len());
+    } else {
+        for repo in &repos {
+            println!("{}", repo_display_name(repo, root));
+        }
+    }
+    Ok(())
 }
 
 fn print_help() {
@@ -28,6 +61,31 @@ fn print_help() {
     println!();
 }
 
+/// Version text for `--version`: the crate version, then the git in use and
+/// the platform, so a pasted `--version` is enough for a bug report.
+pub fn version_info() -> String {
+    format!(
+        "{}\ngit {}, {}-{}",
+        env!("CARGO_PKG_VERSION"),
+        get_git_version(),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
+/// Parse "2.43.0" (or "2.39.3 (Apple Git-146)") into (major, minor).
+pub fn parse_git_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split_whitespace().next()?.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Whether the installed git is at least the given major.minor version.
+pub fn git_supports(min_version: (u32, u32)) -> bool {
+    parse_git_version(&get_git_version()).is_some_and(|v| v >= min_version)
+}
+
 fn get_git_version() -> String {
     Command::new("git")
         .arg("--version")
@@ -37,3 +95,15 @@ fn get_git_version() -> String {
         .map(|s| s.trim().replace("git version ", ""))
         .unwrap_or_else(|| "unknown".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_version() {
+        assert_eq!(parse_git_version("2.43.0"), Some((2, 43)));
+        assert_eq!(parse_git_version("2.39.3 (Apple Git-146)"), Some((2, 39)));
+        assert_eq!(parse_git_version("unknown"), None);
+    }
+}
@@ -1,7 +1,80 @@
 use anyhow::Result;
 use std::fs;
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// A discovered repository: its path plus metadata looked up on first use and
+/// cached, so features that need e.g. the origin host don't each spawn git.
+/// Derefs to its path, so it can be passed wherever a `&Path` is expected.
+#[derive(Debug, Clone)]
+pub struct Repo {
+    path: PathBuf,
+    origin_url: OnceLock<Option<String>>,
+    current_branch: OnceLock<Option<String>>,
+    object_store: OnceLock<Option<PathBuf>>,
+}
+
+impl Repo {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            origin_url: OnceLock::new(),
+            current_branch: OnceLock::new(),
+            object_store: OnceLock::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The `origin` remote URL, see `origin_url`
+    pub fn origin_url(&self) -> Option<&str> {
+        self.origin_url
+            .get_or_init(|| origin_url(&self.path))
+            .as_deref()
+    }
+
+    /// Host of the `origin` remote, see `remote_host`
+    pub fn remote_host(&self) -> Option<String> {
+        self.origin_url().and_then(remote_host)
+    }
+
+    /// Checked-out branch, None when detached; see `current_branch`
+    pub fn current_branch(&self) -> Option<&str> {
+        self.current_branch
+            .get_or_init(|| current_branch(&self.path))
+            .as_deref()
+    }
+
+    /// Whether this is a bare repository (no working tree), see `is_bare_repo`
+    pub fn is_bare(&self) -> bool {
+        !self.path.join(".git").exists() && is_bare_repo(&self.path)
+    }
+
+    /// Object directory this repo stores objects in, see `object_store`
+    pub fn object_store(&self) -> Option<&Path> {
+        self.object_store
+            .get_or_init(|| object_store(&self.path))
+            .as_deref()
+    }
+}
+
+impl Deref for Repo {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for Repo {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScanDepth {
@@ -24,11 +97,9 @@ pub fn parse_scan_depth(value: &str) -> Result<ScanDepth, String> {
         return Ok(ScanDepth::All);
     }
 
-    let depth: usize = normalized.parse().map_err(|_| {
-        format!(
-            "invalid scan depth: {value}. Use a positive integer or \"all\"."
-        )
-    })?;
+    let depth: usize = normalized
+        .parse()
+        .map_err(|_| format!("invalid scan depth: {value}. Use a positive integer or \"all\"."))?;
 
     if depth == 0 {
         return Err("scan depth must be a positive integer or \"all\"".to_string());
@@ -51,10 +122,62 @@ pub fn is_inside_git_repo() -> bool {
         .unwrap_or(false)
 }
 
+/// Whether `path` is itself a git directory, as a bare repository is: it has
+/// `HEAD`, `objects/` and `refs/` at the top level rather than under `.git`.
+pub fn is_bare_repo(path: &Path) -> bool {
+    path.join("HEAD").is_file() && path.join("objects").is_dir() && path.join("refs").is_dir()
+}
+
+/// What discovery decided for a directory it visited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanEvent {
+    /// Directory contains `.git` (or is a bare repo, when included) and was
+    /// collected as a repository
+    Repo,
+    /// Directory is not a repository and was scanned for children
+    Descended,
+    /// Directory is not a repository but sits at the scan depth limit
+    DepthLimit,
+}
+
+impl ScanEvent {
+    pub fn label(self) -> &'static str {
+        match self {
+            ScanEvent::Repo => "repo",
+            ScanEvent::Descended => "descended",
+            ScanEvent::DepthLimit => "depth limit",
+        }
+    }
+}
+
 /// Find all git repositories under the given root, honoring scan depth.
-pub fn find_git_repos_in(root: &Path, scan_depth: ScanDepth) -> Result<Vec<PathBuf>> {
+/// Bare repositories are collected too when `include_bare` is set.
+#[cfg(test)]
+pub fn find_git_repos_in(
+    root: &Path,
+    scan_depth: ScanDepth,
+    include_bare: bool,
+) -> Result<Vec<PathBuf>> {
+    find_git_repos_with(root, scan_depth, include_bare, &mut |_, _, _| {})
+}
+
+/// Find repositories like `find_git_repos_in`, reporting every directory visited
+/// (with its depth, starting at 1 for direct children) to `on_visit`.
+pub fn find_git_repos_with(
+    root: &Path,
+    scan_depth: ScanDepth,
+    include_bare: bool,
+    on_visit: &mut dyn FnMut(&Path, usize, ScanEvent),
+) -> Result<Vec<PathBuf>> {
     let mut repos = Vec::new();
-    scan_dir(root, 0, scan_depth.max_depth(), &mut repos)?;
+    scan_dir(
+        root,
+        0,
+        scan_depth.max_depth(),
+        include_bare,
+        &mut repos,
+        on_visit,
+    )?;
     repos.sort();
     Ok(repos)
 }
@@ -63,23 +186,31 @@ fn scan_dir(
     dir: &Path,
     depth: usize,
     max_depth: Option<usize>,
+    include_bare: bool,
     repos: &mut Vec<PathBuf>,
+    on_visit: &mut dyn FnMut(&Path, usize, ScanEvent),
 ) -> Result<()> {
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
 
+    for path in entries {
         if path.is_dir() {
             let git_dir = path.join(".git");
-            if git_dir.exists() {
+            if git_dir.exists() || (include_bare && is_bare_repo(&path)) {
+                on_visit(&path, depth + 1, ScanEvent::Repo);
                 repos.push(path);
                 continue;
             }
 
             let next_depth = depth + 1;
-            let should_descend = max_depth.map_or(true, |max| next_depth < max);
+            let should_descend = max_depth.is_none_or(|max| next_depth < max);
             if should_descend {
-                scan_dir(&path, next_depth, max_depth, repos)?;
+                on_visit(&path, next_depth, ScanEvent::Descended);
+                scan_dir(&path, next_depth, max_depth, include_bare, repos, on_visit)?;
+            } else {
+                on_visit(&path, next_depth, ScanEvent::DepthLimit);
             }
         }
     }
@@ -95,21 +226,357 @@ pub fn repo_name(path: &Path) -> String {
         .to_string()
 }
 
+/// Whether `name` matches the shell-style `pattern`, where `*` matches any
+/// run of characters and `?` any single character
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // After a `*`: the pattern position past it, and where its match ends so far
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                // Let the last `*` absorb one more character and retry
+                Some((after, matched)) => {
+                    star = Some((after, matched + 1));
+                    p = after;
+                    n = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// File in the working directory listing repo name globs to always skip
+pub const IGNORE_FILE: &str = ".git-all-ignore";
+
+/// Repo name globs from `dir`'s ignore file, one per line with blank lines
+/// and `#` comments skipped. Empty when there is no ignore file.
+pub fn ignore_patterns(dir: &Path) -> Result<Vec<String>> {
+    let contents = match fs::read_to_string(dir.join(IGNORE_FILE)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
 /// Display a repository path relative to the given root when possible.
 pub fn repo_display_name(path: &Path, root: &Path) -> String {
     match path.strip_prefix(root) {
-        Ok(relative) if !relative.as_os_str().is_empty() => {
-            relative.to_string_lossy().to_string()
-        }
+        Ok(relative) if !relative.as_os_str().is_empty() => relative.to_string_lossy().to_string(),
         _ => repo_name(path),
     }
 }
 
+/// A shallow clone records its cut-off commits in `shallow` in its common git
+/// dir, which a bare repo or linked worktree keeps somewhere other than `.git`.
+pub fn is_shallow(path: &Path) -> bool {
+    common_dir(path).is_some_and(|dir| dir.join("shallow").exists())
+}
+
+/// A repository's git directory: `.git`, the linked worktree's git dir a `.git`
+/// file points to, or the repo itself when bare. Read from disk, without git.
+pub fn git_dir(path: &Path) -> Option<PathBuf> {
+    let dot_git = path.join(".git");
+    if dot_git.is_file() {
+        let contents = fs::read_to_string(&dot_git).ok()?;
+        let target = contents.trim().strip_prefix("gitdir:")?.trim();
+        return Some(path.join(target));
+    }
+    if dot_git.exists() || !is_bare_repo(path) {
+        Some(dot_git)
+    } else {
+        Some(path.to_path_buf())
+    }
+}
+
+/// The git dir holding state shared by all of a repository's worktrees
+/// (objects, `shallow`): `git_dir`, or the main repository's when `commondir`
+/// points there from a linked worktree
+fn common_dir(path: &Path) -> Option<PathBuf> {
+    let git_dir = git_dir(path)?;
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common) => Some(git_dir.join(common.trim())),
+        Err(_) => Some(git_dir),
+    }
+}
+
+/// The object directory a repository actually stores objects in, canonicalized,
+/// so repos sharing one get the same path. Follows a `.git` file to a linked
+/// worktree's git dir, `commondir` to the main repository, and the first entry
+/// of `objects/info/alternates` (as in `git clone --shared`). Read from disk
+/// rather than via `git rev-parse --git-path objects` to avoid a git process
+/// per repository. None if the layout can't be resolved.
+pub fn object_store(path: &Path) -> Option<PathBuf> {
+    let objects = common_dir(path)?.join("objects");
+    let alternate = fs::read_to_string(objects.join("info").join("alternates"))
+        .ok()
+        .and_then(|alternates| {
+            alternates
+                .lines()
+                .map(str::trim)
+                .find(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(|l| objects.join(l))
+        });
+
+    alternate.unwrap_or(objects).canonicalize().ok()
+}
+
+/// Name of the checked-out branch, or None for a detached HEAD.
+pub fn current_branch(path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["symbolic-ref", "--short", "--quiet", "HEAD"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if branch.is_empty() {
+        None
+    } else {
+        Some(branch)
+    }
+}
+
+/// Like `current_branch`, but read from the git dir's `HEAD` file rather than
+/// by running git, for filtering many repos before any work is scheduled.
+pub fn head_branch(path: &Path) -> Option<String> {
+    let head = fs::read_to_string(git_dir(path)?.join("HEAD")).ok()?;
+    let branch = head.trim().strip_prefix("ref: refs/heads/")?;
+    if branch.is_empty() {
+        None
+    } else {
+        Some(branch.to_string())
+    }
+}
+
+/// Read the `origin` remote URL for a repository, if one is configured.
+pub fn origin_url(path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["config", "--get", "remote.origin.url"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if url.is_empty() { None } else { Some(url) }
+}
+
+/// Names of the repository's configured remotes, in `git remote` order.
+pub fn remotes(path: &Path) -> Vec<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .arg("remote")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Extract the host from a git remote URL.
+/// Handles `scheme://[user@]host[:port]/path` and scp-like `[user@]host:path`.
+pub fn remote_host(url: &str) -> Option<String> {
+    let authority = match url.split_once("://") {
+        Some((_, rest)) => {
+            let authority = rest.split('/').next().unwrap_or("");
+            let authority = authority.rsplit('@').next().unwrap_or("");
+            authority.split(':').next().unwrap_or("")
+        }
+        None => {
+            let (before_colon, _) = url.split_once(':')?;
+            before_colon.rsplit('@').next().unwrap_or("")
+        }
+    };
+
+    if authority.is_empty() {
+        None
+    } else {
+        Some(authority.to_string())
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use std::fs;
 
+    /// Run git in `dir` with a test identity, asserting it succeeds
+    pub(crate) fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("service-*", "service-auth"));
+        assert!(glob_matches("service-*", "service-"));
+        assert!(!glob_matches("service-*", "web-service"));
+        assert!(glob_matches("*-api", "billing-api"));
+        assert!(glob_matches("*a*b*", "xaxxbx"));
+        assert!(!glob_matches("*a*b", "xaxxbx"));
+        assert!(glob_matches("web-?", "web-2"));
+        assert!(!glob_matches("web-?", "web-12"));
+        assert!(glob_matches("café-*", "café-über"));
+        assert!(glob_matches("api", "api"));
+        assert!(!glob_matches("api", "api2"));
+        assert!(glob_matches("*", ""));
+    }
+
+    #[test]
+    fn test_ignore_patterns() {
+        let temp = tempfile::tempdir().unwrap();
+        assert!(ignore_patterns(temp.path()).unwrap().is_empty());
+
+        fs::write(
+            temp.path().join(IGNORE_FILE),
+            "# old stuff\narchived-*\n\n   \n  scratch  \n\t# indented comment\n",
+        )
+        .unwrap();
+        assert_eq!(
+            ignore_patterns(temp.path()).unwrap(),
+            ["archived-*", "scratch"]
+        );
+    }
+
+    #[test]
+    fn test_repo_accessors_cache_metadata() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("api");
+        fs::create_dir(&path).unwrap();
+        git(&path, &["init", "-q", "-b", "main"]);
+        git(
+            &path,
+            &["remote", "add", "origin", "git@github.com:acme/api.git"],
+        );
+
+        let repo = Repo::new(path.clone());
+        assert_eq!(repo.path(), path);
+        assert!(repo.join(".git").exists());
+        assert_eq!(repo.origin_url(), Some("git@github.com:acme/api.git"));
+        assert_eq!(repo.remote_host().as_deref(), Some("github.com"));
+        assert_eq!(repo.current_branch(), Some("main"));
+
+        // Looked up once: later changes are not seen by the same Repo
+        git(
+            &path,
+            &[
+                "remote",
+                "set-url",
+                "origin",
+                "https://gitlab.com/acme/api.git",
+            ],
+        );
+        git(&path, &["checkout", "-q", "-b", "feature"]);
+        assert_eq!(repo.remote_host().as_deref(), Some("github.com"));
+        assert_eq!(repo.current_branch(), Some("main"));
+
+        let fresh = Repo::new(path);
+        assert_eq!(fresh.remote_host().as_deref(), Some("gitlab.com"));
+        assert_eq!(fresh.current_branch(), Some("feature"));
+    }
+
+    #[test]
+    fn test_object_store_groups_worktrees_and_shared_clones() {
+        let temp = tempfile::tempdir().unwrap();
+        let main = temp.path().join("main");
+        fs::create_dir(&main).unwrap();
+        git(&main, &["init", "-q"]);
+        git(&main, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(&main, &["worktree", "add", "-q", "../linked"]);
+        git(temp.path(), &["clone", "-q", "--shared", "main", "shared"]);
+        git(temp.path(), &["clone", "-q", "main", "independent"]);
+
+        let main_store = object_store(&main).unwrap();
+        assert_eq!(
+            main_store,
+            main.join(".git/objects").canonicalize().unwrap()
+        );
+        assert_eq!(
+            object_store(&temp.path().join("linked")),
+            Some(main_store.clone())
+        );
+        assert_eq!(
+            object_store(&temp.path().join("shared")),
+            Some(main_store.clone())
+        );
+        assert_ne!(
+            object_store(&temp.path().join("independent")),
+            Some(main_store)
+        );
+        assert_eq!(object_store(&temp.path().join("missing")), None);
+    }
+
+    #[test]
+    fn test_head_branch_reads_head_from_disk() {
+        let temp = tempfile::tempdir().unwrap();
+        let main = temp.path().join("main");
+        fs::create_dir(&main).unwrap();
+        git(&main, &["init", "-q", "-b", "main"]);
+        git(&main, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        git(
+            &main,
+            &["worktree", "add", "-q", "-b", "feature", "../linked"],
+        );
+        assert_eq!(head_branch(&main).as_deref(), Some("main"));
+        assert_eq!(
+            head_branch(&temp.path().join("linked")).as_deref(),
+            Some("feature")
+        );
+
+        git(&main, &["checkout", "-q", "--detach"]);
+        assert_eq!(head_branch(&main), None);
+        assert_eq!(head_branch(&temp.path().join("missing")), None);
+    }
+
     #[test]
     fn test_repo_name() {
         let path = PathBuf::from("/home/user/src/my-repo");
@@ -127,10 +594,7 @@ mod tests {
         let root = PathBuf::from("/tmp/workspace");
         let repo = root.join("nested").join("repo");
         let expected = PathBuf::from("nested").join("repo");
-        assert_eq!(
-            repo_display_name(&repo, &root),
-            expected.to_string_lossy()
-        );
+        assert_eq!(repo_display_name(&repo, &root), expected.to_string_lossy());
     }
 
     #[test]
@@ -161,7 +625,7 @@ mod tests {
         create_repo(root.join("boundary"), true);
         create_repo(root.join("boundary/child"), true);
 
-        let mut depth1 = find_git_repos_in(root, ScanDepth::Depth(1)).unwrap();
+        let mut depth1 = find_git_repos_in(root, ScanDepth::Depth(1), false).unwrap();
         let mut expected_depth1 = vec![
             root.join("boundary"),
             root.join("repo1"),
@@ -171,7 +635,7 @@ mod tests {
         expected_depth1.sort();
         assert_eq!(depth1, expected_depth1);
 
-        let mut depth2 = find_git_repos_in(root, ScanDepth::Depth(2)).unwrap();
+        let mut depth2 = find_git_repos_in(root, ScanDepth::Depth(2), false).unwrap();
         let mut expected_depth2 = vec![
             root.join("boundary"),
             root.join("repo1"),
@@ -182,7 +646,7 @@ mod tests {
         expected_depth2.sort();
         assert_eq!(depth2, expected_depth2);
 
-        let mut depth_all = find_git_repos_in(root, ScanDepth::All).unwrap();
+        let mut depth_all = find_git_repos_in(root, ScanDepth::All, false).unwrap();
         let mut expected_depth_all = vec![
             root.join("boundary"),
             root.join("repo1"),
@@ -195,6 +659,159 @@ mod tests {
         assert_eq!(depth_all, expected_depth_all);
     }
 
+    #[test]
+    fn test_find_git_repos_with_reports_visits() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let root = temp.path();
+
+        create_repo(root.join("repo1"), true);
+        create_repo(root.join("nested/repo2"), true);
+        create_repo(root.join("nested/deeper/repo3"), true);
+
+        let mut events = Vec::new();
+        find_git_repos_with(
+            root,
+            ScanDepth::Depth(2),
+            false,
+            &mut |path, depth, event| {
+                let rel = path.strip_prefix(root).unwrap().to_path_buf();
+                events.push((rel, depth, event));
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                (PathBuf::from("nested"), 1, ScanEvent::Descended),
+                (PathBuf::from("nested/deeper"), 2, ScanEvent::DepthLimit),
+                (PathBuf::from("nested/repo2"), 2, ScanEvent::Repo),
+                (PathBuf::from("repo1"), 1, ScanEvent::Repo),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_git_repos_accepts_git_file() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let root = temp.path();
+
+        // Linked worktrees and submodules have a `.git` file pointing at their git dir
+        create_repo(root.join("main"), true);
+        fs::create_dir_all(root.join("main/.git/worktrees/feature"))
+            .expect("create worktree git dir");
+        fs::create_dir_all(root.join("feature")).expect("create worktree");
+        fs::write(
+            root.join("feature/.git"),
+            "gitdir: ../main/.git/worktrees/feature\n",
+        )
+        .expect("write .git file");
+
+        assert_eq!(
+            find_git_repos_in(root, ScanDepth::Depth(1), false).unwrap(),
+            vec![root.join("feature"), root.join("main")]
+        );
+    }
+
+    #[test]
+    fn test_find_git_repos_include_bare() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let root = temp.path();
+
+        create_repo(root.join("work"), true);
+        let mirror = root.join("mirror.git");
+        fs::create_dir_all(mirror.join("objects")).expect("create objects");
+        fs::create_dir_all(mirror.join("refs")).expect("create refs");
+        fs::write(mirror.join("HEAD"), "ref: refs/heads/main\n").expect("write HEAD");
+
+        assert!(is_bare_repo(&mirror));
+        assert!(!is_bare_repo(&root.join("work")));
+        assert_eq!(
+            find_git_repos_in(root, ScanDepth::Depth(1), false).unwrap(),
+            vec![root.join("work")]
+        );
+        assert_eq!(
+            find_git_repos_in(root, ScanDepth::Depth(1), true).unwrap(),
+            vec![mirror.clone(), root.join("work")]
+        );
+        assert!(Repo::new(mirror).is_bare());
+    }
+
+    #[test]
+    fn test_is_shallow() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let full = temp.path().join("full");
+        let shallow = temp.path().join("shallow");
+        create_repo(full.clone(), true);
+        create_repo(shallow.clone(), true);
+        fs::write(shallow.join(".git/shallow"), "abc123\n").expect("write shallow");
+
+        assert!(!is_shallow(&full));
+        assert!(is_shallow(&shallow));
+    }
+
+    #[test]
+    fn test_is_shallow_for_bare_repos_and_worktrees() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let origin = temp.path().join("origin");
+        fs::create_dir(&origin).unwrap();
+        git(&origin, &["init", "-q", "-b", "main"]);
+        git(&origin, &["commit", "-q", "--allow-empty", "-m", "one"]);
+        git(&origin, &["commit", "-q", "--allow-empty", "-m", "two"]);
+        let url = format!("file://{}", origin.display());
+        git(
+            temp.path(),
+            &["clone", "-q", "--bare", "--depth", "1", &url, "bare.git"],
+        );
+        git(temp.path(), &["clone", "-q", "--depth", "1", &url, "work"]);
+        git(
+            &temp.path().join("work"),
+            &["worktree", "add", "-q", "-b", "side", "../linked"],
+        );
+
+        assert!(is_shallow(&temp.path().join("bare.git")));
+        assert!(is_shallow(&temp.path().join("linked")));
+        assert!(!is_shallow(&origin));
+        assert!(!is_shallow(&temp.path().join("missing")));
+    }
+
+    #[test]
+    fn test_remotes_lists_configured_remotes() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(temp.path())
+                .args(args)
+                .status()
+                .expect("git");
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        assert!(remotes(temp.path()).is_empty());
+
+        git(&["remote", "add", "origin", "https://example.com/a.git"]);
+        git(&["remote", "add", "upstream", "https://example.com/b.git"]);
+        assert_eq!(remotes(temp.path()), vec!["origin", "upstream"]);
+    }
+
+    #[test]
+    fn test_remote_host_formats() {
+        assert_eq!(
+            remote_host("git@github.com:user/repo.git").as_deref(),
+            Some("github.com")
+        );
+        assert_eq!(
+            remote_host("https://gitlab.example.com/group/repo").as_deref(),
+            Some("gitlab.example.com")
+        );
+        assert_eq!(
+            remote_host("ssh://git@host.example:2222/repo.git").as_deref(),
+            Some("host.example")
+        );
+        assert_eq!(remote_host("/local/path/repo"), None);
+    }
+
     fn create_repo(path: PathBuf, git_dir: bool) {
         fs::create_dir_all(&path).expect("create repo dir");
         let git_path = path.join(".git");
@@ -0,0 +1,161 @@
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::color::stable_hash;
+
+/// Progress of a run, persisted so an interrupted run can be resumed.
+///
+/// Each repository that finishes without error is appended to the state file
+/// as soon as its result is printed. A later run of the same command with
+/// `--resume` skips those repositories; one without starts the record over.
+/// The file is removed once a run completes with no errors.
+pub struct RunState {
+    path: PathBuf,
+    completed: HashSet<PathBuf>,
+    file: Mutex<Option<File>>,
+}
+
+impl RunState {
+    /// Load what an earlier run of this command in `root` recorded, to resume it
+    pub fn for_run(state_dir: &Path, root: &Path, command_line: &[String]) -> Self {
+        let path = state_path(state_dir, root, command_line);
+        let completed = fs::read_to_string(&path)
+            .map(|contents| contents.lines().map(PathBuf::from).collect())
+            .unwrap_or_default();
+        Self::new(path, completed)
+    }
+
+    /// Start recording a new run, forgetting what an earlier run recorded
+    pub fn start(state_dir: &Path, root: &Path, command_line: &[String]) -> Self {
+        let state = Self::new(state_path(state_dir, root, command_line), HashSet::new());
+        if let Err(e) = state.clear() {
+            eprintln!("git-all: failed to reset progress: {}", e);
+        }
+        state
+    }
+
+    fn new(path: PathBuf, completed: HashSet<PathBuf>) -> Self {
+        Self {
+            path,
+            completed,
+            file: Mutex::new(None),
+        }
+    }
+
+    pub fn is_completed(&self, repo: &Path) -> bool {
+        self.completed.contains(repo)
+    }
+
+    /// Mark a repository as done. Written through immediately so the record
+    /// survives an interrupt.
+    pub fn record(&self, repo: &Path) -> Result<()> {
+        if self.is_completed(repo) {
+            return Ok(());
+        }
+
+        let mut file = self.file.lock().unwrap();
+        if file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            *file = Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?,
+            );
+        }
+        if let Some(file) = file.as_mut() {
+            writeln!(file, "{}", repo.display())?;
+        }
+        Ok(())
+    }
+
+    /// Forget all progress, after a run where every repository succeeded
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// State file for a run, identified by its working directory and command line
+fn state_path(state_dir: &Path, root: &Path, command_line: &[String]) -> PathBuf {
+    let key = format!("{}\0{}", root.display(), command_line.join("\0"));
+    state_dir.join(format!("run-{:016x}", stable_hash(&key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_recorded_repos_are_skipped_on_resume() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let root = PathBuf::from("/work");
+
+        let first = RunState::for_run(temp.path(), &root, &command(&["pull"]));
+        assert!(!first.is_completed(&root.join("api")));
+        first.record(&root.join("api")).unwrap();
+        first.record(&root.join("web")).unwrap();
+
+        let resumed = RunState::for_run(temp.path(), &root, &command(&["pull"]));
+        assert!(resumed.is_completed(&root.join("api")));
+        assert!(resumed.is_completed(&root.join("web")));
+        assert!(!resumed.is_completed(&root.join("worker")));
+    }
+
+    #[test]
+    fn test_state_is_per_command() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let root = PathBuf::from("/work");
+
+        RunState::for_run(temp.path(), &root, &command(&["pull"]))
+            .record(&root.join("api"))
+            .unwrap();
+
+        let fetch = RunState::for_run(temp.path(), &root, &command(&["fetch"]));
+        assert!(!fetch.is_completed(&root.join("api")));
+    }
+
+    #[test]
+    fn test_clear_forgets_progress() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let root = PathBuf::from("/work");
+
+        let state = RunState::for_run(temp.path(), &root, &command(&["pull"]));
+        state.record(&root.join("api")).unwrap();
+        state.clear().unwrap();
+
+        let again = RunState::for_run(temp.path(), &root, &command(&["pull"]));
+        assert!(!again.is_completed(&root.join("api")));
+        // Clearing with no state file is fine
+        again.clear().unwrap();
+    }
+
+    #[test]
+    fn test_start_begins_a_fresh_record() {
+        let temp = tempfile::tempdir().expect("temp dir");
+        let root = PathBuf::from("/work");
+
+        let interrupted = RunState::for_run(temp.path(), &root, &command(&["pull"]));
+        interrupted.record(&root.join("api")).unwrap();
+
+        let rerun = RunState::start(temp.path(), &root, &command(&["pull"]));
+        assert!(!rerun.is_completed(&root.join("api")));
+        rerun.record(&root.join("web")).unwrap();
+
+        let resumed = RunState::for_run(temp.path(), &root, &command(&["pull"]));
+        assert!(!resumed.is_completed(&root.join("api")));
+        assert!(resumed.is_completed(&root.join("web")));
+    }
+}
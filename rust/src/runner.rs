@@ -1,46 +1,249 @@
 use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{IsTerminal, Read};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Output, Stdio};
 use std::sync::mpsc;
 use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
 
-use crate::repo::repo_display_name;
+use crate::color::{ColorBy, paint_by_key, paint_category};
+use crate::interrupt;
+use crate::repo::{Repo, repo_display_name, repo_name};
+use crate::resume::RunState;
+use regex::Regex;
 
 /// Simple counting semaphore using stdlib primitives.
 /// Allows limiting concurrent operations to N at a time.
 struct Semaphore {
-    count: Mutex<usize>,
+    state: Mutex<SemaphoreState>,
     cond: Condvar,
+    /// Grant permits in the order `acquire` was called (`--fair`)
+    fifo: bool,
+}
+
+struct SemaphoreState {
+    permits: usize,
+    /// Next ticket handed out, and the ticket allowed to take the next permit
+    next_ticket: u64,
+    serving: u64,
 }
 
 impl Semaphore {
     fn new(permits: usize) -> Self {
         Semaphore {
-            count: Mutex::new(permits),
+            state: Mutex::new(SemaphoreState {
+                permits,
+                next_ticket: 0,
+                serving: 0,
+            }),
             cond: Condvar::new(),
+            fifo: false,
+        }
+    }
+
+    /// A semaphore that grants permits first come, first served, so a waiting
+    /// repo can't be overtaken by ones that asked later
+    fn fifo(permits: usize) -> Self {
+        Semaphore {
+            fifo: true,
+            ..Semaphore::new(permits)
         }
     }
 
     /// Acquire a permit, blocking if none available.
     fn acquire(&self) {
-        let mut count = self.count.lock().unwrap();
-        while *count == 0 {
-            count = self.cond.wait(count).unwrap();
+        let mut state = self.state.lock().unwrap();
+        if !self.fifo {
+            while state.permits == 0 {
+                state = self.cond.wait(state).unwrap();
+            }
+            state.permits -= 1;
+            return;
+        }
+
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        while state.serving != ticket || state.permits == 0 {
+            state = self.cond.wait(state).unwrap();
         }
-        *count -= 1;
+        state.serving += 1;
+        state.permits -= 1;
+        // The next ticket holder may be able to go too
+        self.cond.notify_all();
+    }
+
+    /// Acquire a permit that is released when the guard is dropped
+    fn permit(&self) -> Permit<'_> {
+        self.acquire();
+        Permit(self)
     }
 
-    /// Release a permit, waking one waiting thread.
+    /// Release a permit, waking a waiting thread.
     fn release(&self) {
-        let mut count = self.count.lock().unwrap();
-        *count += 1;
-        self.cond.notify_one();
+        let mut state = self.state.lock().unwrap();
+        state.permits += 1;
+        if self.fifo {
+            // Only the holder of the next ticket may proceed, so wake everyone
+            self.cond.notify_all();
+        } else {
+            self.cond.notify_one();
+        }
+    }
+}
+
+/// A worker slot held from `Semaphore::permit` until dropped
+struct Permit<'a>(&'a Semaphore);
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.0.release();
     }
 }
 
 const MIN_REPO_NAME_WIDTH: usize = 4;
 const MAX_REPO_NAME_WIDTH_CAP: usize = 48;
 
+/// Coarse classification of a repository's result, used for tallies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// Nothing to report: no local changes, nothing new upstream
+    Clean,
+    /// Local changes present
+    Dirty,
+    /// The command changed something (new commits, refs, history)
+    Updated,
+    /// Succeeded with no more specific classification
+    Ok,
+    /// Not run for this repo
+    Skipped,
+    /// The command failed or could not be started
+    Error,
+}
+
+impl Category {
+    pub const ALL: [Category; 6] = [
+        Category::Clean,
+        Category::Dirty,
+        Category::Updated,
+        Category::Ok,
+        Category::Skipped,
+        Category::Error,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Category::Clean => "clean",
+            Category::Dirty => "dirty",
+            Category::Updated => "updated",
+            Category::Ok => "ok",
+            Category::Skipped => "skipped",
+            Category::Error => "error",
+        }
+    }
+}
+
+/// Machine-readable tally of categories: `clean=2 dirty=1 ... error=0`
+fn format_tally(categories: &[Category]) -> String {
+    Category::ALL
+        .iter()
+        .map(|cat| {
+            let count = categories.iter().filter(|c| *c == cat).count();
+            format!("{}={}", cat.key(), count)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Human-readable footer for `--summary`: "47 repos: 45 ok, 2 failed".
+/// Only categories that occurred are listed, in `Category::ALL` order.
+fn format_summary_footer(categories: &[Category]) -> String {
+    let counts: Vec<String> = Category::ALL
+        .iter()
+        .filter_map(|cat| {
+            let count = categories.iter().filter(|c| *c == cat).count();
+            let label = match cat {
+                Category::Error => "failed",
+                _ => cat.key(),
+            };
+            (count > 0).then(|| format!("{} {}", count, label))
+        })
+        .collect();
+    let noun = if categories.len() == 1 {
+        "repo"
+    } else {
+        "repos"
+    };
+    format!("{} {}: {}", categories.len(), noun, counts.join(", "))
+}
+
+/// Ordering of grouped output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum SummarySort {
+    /// Largest groups first (ties broken alphabetically)
+    #[default]
+    Count,
+    /// Alphabetical by summary text
+    Name,
+}
+
+/// Order of per-repo output lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputSort {
+    /// By repo name on disk, before `--labels` or `--name-replace` rename it
+    #[default]
+    Name,
+    /// Problems first: errors, dirty, updated, ok, skipped, then clean.
+    /// Needs every result, so nothing prints until all repos finish.
+    Status,
+    /// By repository path on disk
+    Path,
+}
+
+/// Where a category sorts under `--sort=status`
+fn status_rank(category: Category) -> usize {
+    match category {
+        Category::Error => 0,
+        Category::Dirty => 1,
+        Category::Updated => 2,
+        Category::Ok => 3,
+        Category::Skipped => 4,
+        Category::Clean => 5,
+    }
+}
+
+/// Indexes into `repos` in the order their results print. Name and path
+/// orders are known up front, so results can still stream in that order.
+fn print_order(repos: &[Repo], ctx: &ExecutionContext) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..repos.len()).collect();
+    match ctx.sort() {
+        OutputSort::Name => {
+            order.sort_by_cached_key(|&i| repo_display_name(repos[i].path(), ctx.display_root()))
+        }
+        OutputSort::Path => order.sort_by(|&a, &b| repos[a].path().cmp(repos[b].path())),
+        OutputSort::Status => {}
+    }
+    order
+}
+
+/// Visible results in `--sort=status` order: by category, then by name on
+/// disk (as in name order), so display renames don't reorder ties
+fn status_order<'a>(
+    results: &'a [RepoResult],
+    ctx: &ExecutionContext,
+    formatter: &dyn OutputFormatter,
+) -> Vec<&'a RepoResult> {
+    let mut visible: Vec<&RepoResult> =
+        results.iter().filter(|r| ctx.shows(r, formatter)).collect();
+    visible.sort_by_cached_key(|r| {
+        (
+            status_rank(r.category(formatter)),
+            repo_display_name(&r.repo, ctx.display_root()),
+        )
+    });
+    visible
+}
+
 /// URL scheme to force for git operations
 #[derive(Clone, Copy)]
 pub enum UrlScheme {
@@ -50,25 +253,177 @@ pub enum UrlScheme {
     Https,
 }
 
+/// Display-only rename of repos, parsed from `REGEX=REPLACEMENT`
+#[derive(Debug, Clone)]
+pub struct NameReplace {
+    regex: Regex,
+    replacement: String,
+}
+
+impl NameReplace {
+    /// Parse `REGEX=REPLACEMENT`, splitting at the first `=`.
+    /// The replacement may use `$1`/`${name}` capture references.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (pattern, replacement) = value
+            .split_once('=')
+            .ok_or_else(|| format!("expected REGEX=REPLACEMENT, got: {value}"))?;
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid regex {pattern:?}: {e}"))?;
+        Ok(Self {
+            regex,
+            replacement: replacement.to_string(),
+        })
+    }
+
+    pub fn apply(&self, name: &str) -> String {
+        self.regex
+            .replace_all(name, self.replacement.as_str())
+            .to_string()
+    }
+}
+
+/// Display-only labels for repos, from a `--labels` file of `repo-dir-name=Friendly Name`
+/// lines. Blank lines and `#` comments are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct RepoLabels {
+    labels: HashMap<String, String>,
+}
+
+impl RepoLabels {
+    /// Read and parse a labels file; usable as a clap value parser
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+        Self::parse(&contents).map_err(|e| format!("{path}: {e}"))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut labels = HashMap::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once('=') {
+                Some((key, label)) if !key.trim().is_empty() => {
+                    labels.insert(key.trim().to_string(), label.trim().to_string());
+                }
+                _ => {
+                    return Err(format!(
+                        "line {}: expected NAME=LABEL, got: {line}",
+                        number + 1
+                    ));
+                }
+            }
+        }
+        Ok(Self { labels })
+    }
+
+    /// Label for a repo, looked up by its displayed path (for nested repos),
+    /// then by its directory name
+    fn label(&self, display_name: &str, repo: &Path) -> Option<&str> {
+        self.labels
+            .get(display_name)
+            .or_else(|| self.labels.get(&repo_name(repo)))
+            .map(String::as_str)
+    }
+}
+
+/// Emphasis for summary text matching a pattern, from `--highlight REGEX`
+#[derive(Debug, Clone)]
+pub struct Highlight {
+    regex: Regex,
+    color: bool,
+}
+
+impl Highlight {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid regex {pattern:?}: {e}"))?;
+        Ok(Self { regex, color: true })
+    }
+
+    /// Use bold/underline when `color` is set, otherwise `>>match<<` markers
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        self.regex
+            .replace_all(text, |caps: &regex::Captures| {
+                let matched = &caps[0];
+                if matched.is_empty() {
+                    String::new()
+                } else if self.color {
+                    // Turn off only bold/underline so any surrounding color survives
+                    format!("\x1b[1;4m{}\x1b[22;24m", matched)
+                } else {
+                    format!(">>{}<<", matched)
+                }
+            })
+            .to_string()
+    }
+}
+
+/// Host whose remote URLs `--ssh`/`--https` rewrite when no `--host` is given
+const DEFAULT_URL_HOST: &str = "github.com";
+
+impl UrlScheme {
+    /// The `-c` config value that rewrites remote URLs on `host` to this scheme
+    fn insteadof_config(self, host: &str) -> String {
+        match self {
+            UrlScheme::Ssh => format!("url.git@{host}:.insteadOf=https://{host}/"),
+            UrlScheme::Https => format!("url.https://{host}/.insteadOf=git@{host}:"),
+        }
+    }
+}
+
+/// The remote URL rewriting `--ssh`/`--https` asks for: a scheme, and the
+/// hosts (from `--host`) whose URLs are rewritten to it
+#[derive(Clone)]
+pub struct UrlRewrite {
+    scheme: UrlScheme,
+    hosts: Vec<String>,
+}
+
+impl UrlRewrite {
+    /// Rewrite URLs on `hosts`, or on github.com when there are none
+    pub fn new(scheme: UrlScheme, hosts: &[String]) -> Self {
+        let hosts = match hosts {
+            [] => vec![DEFAULT_URL_HOST.to_string()],
+            hosts => hosts.to_vec(),
+        };
+        Self { scheme, hosts }
+    }
+
+    /// `-c` pairs rewriting every host, in `--host` order
+    fn config_args(&self) -> Vec<String> {
+        self.hosts
+            .iter()
+            .flat_map(|host| ["-c".to_string(), self.scheme.insteadof_config(host)])
+            .collect()
+    }
+}
+
 /// Format repo name with fixed width: truncate long names, pad short ones
-fn compute_name_width(repos: &[PathBuf], display_root: &Path) -> usize {
+fn compute_name_width(repos: &[Repo], ctx: &ExecutionContext) -> usize {
     let mut max_len = 0usize;
     for repo in repos {
-        let name = repo_display_name(repo, display_root);
-        max_len = max_len.max(name.len());
+        let name = ctx.display_name(repo);
+        max_len = max_len.max(name.chars().count());
     }
 
     let capped = max_len.min(MAX_REPO_NAME_WIDTH_CAP);
     capped.max(MIN_REPO_NAME_WIDTH)
 }
 
-/// Format repo name with fixed width: truncate long names, pad short ones
+/// Format repo name with fixed width: truncate long names, pad short ones.
+/// Widths count chars, so multibyte names are never cut mid-character.
 fn format_repo_name(name: &str, width: usize) -> String {
-    let display_name = if name.len() > width {
+    let display_name = if name.chars().count() > width {
         if width <= 4 {
             name.chars().take(width).collect()
         } else {
-            format!("{}-...", &name[..width - 4])
+            format!("{}-...", name.chars().take(width - 4).collect::<String>())
         }
     } else {
         name.to_string()
@@ -80,8 +435,38 @@ fn format_repo_name(name: &str, width: usize) -> String {
 pub struct ExecutionContext {
     dry_run: bool,
     url_scheme: Option<UrlScheme>,
+    /// Hosts `url_scheme` applies to (empty: github.com)
+    url_hosts: Vec<String>,
     max_connections: usize,
     display_root: PathBuf,
+    color_by: Option<ColorBy>,
+    /// Color each result line by its category (clean, dirty, error)
+    color_status: bool,
+    legend: bool,
+    group_identical: bool,
+    group_by_dir: bool,
+    prefix_lines: bool,
+    summary_sort: SummarySort,
+    sort: OutputSort,
+    count_by_category: bool,
+    name_replace: Option<NameReplace>,
+    labels: Option<RepoLabels>,
+    ssh_command: Option<String>,
+    /// Run git without system or user config (`--isolated`)
+    isolated: bool,
+    timeout: Option<Duration>,
+    run_state: Option<RunState>,
+    stderr_merge: bool,
+    highlight: Option<Highlight>,
+    json: bool,
+    timings: bool,
+    summary: bool,
+    stderr_summary: bool,
+    fair: bool,
+    failures_at_end: bool,
+    only_dirty: bool,
+    categories: Mutex<Vec<(PathBuf, Category)>>,
+    max_line_width: Option<usize>,
 }
 
 impl ExecutionContext {
@@ -94,17 +479,200 @@ impl ExecutionContext {
         Self {
             dry_run,
             url_scheme,
+            url_hosts: Vec::new(),
             max_connections,
             display_root,
+            color_by: None,
+            color_status: false,
+            legend: false,
+            group_identical: false,
+            group_by_dir: false,
+            prefix_lines: false,
+            summary_sort: SummarySort::default(),
+            sort: OutputSort::default(),
+            count_by_category: false,
+            name_replace: None,
+            labels: None,
+            ssh_command: None,
+            isolated: false,
+            timeout: None,
+            run_state: None,
+            stderr_merge: false,
+            highlight: None,
+            json: false,
+            timings: false,
+            summary: false,
+            stderr_summary: false,
+            fair: false,
+            failures_at_end: false,
+            only_dirty: false,
+            categories: Mutex::new(Vec::new()),
+            max_line_width: None,
         }
     }
 
+    /// Truncate printed lines to this many columns (0 = never truncate).
+    /// None uses the terminal width.
+    pub fn with_max_line_width(mut self, max_line_width: Option<usize>) -> Self {
+        self.max_line_width = max_line_width;
+        self
+    }
+
+    /// Explain what repo name colors mean before the results (when coloring)
+    pub fn with_legend(mut self, legend: bool) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    /// Print only repos whose result is `Category::Dirty`. A display filter:
+    /// every repo's category is still recorded in `categories()`.
+    pub fn with_only_dirty(mut self, only_dirty: bool) -> Self {
+        self.only_dirty = only_dirty;
+        self
+    }
+
+    /// Print one JSON object per repository instead of text lines
+    pub fn with_json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// Report how long each repository's command took, and the whole run
+    pub fn with_timings(mut self, timings: bool) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Finish with a count of results per category, for any command
+    pub fn with_summary(mut self, summary: bool) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    /// Print the per-category count to stderr when `--summary` isn't putting
+    /// it on stdout, so it never mixes into output scripts parse
+    pub fn with_stderr_summary(mut self, stderr_summary: bool) -> Self {
+        self.stderr_summary = stderr_summary;
+        self
+    }
+
+    /// Grant worker slots first come, first served, and on a terminal show how
+    /// many finished repos are waiting behind the one being printed next
+    pub fn with_fair(mut self, fair: bool) -> Self {
+        self.fair = fair;
+        self
+    }
+
+    /// After all other output, list the failed repositories again with their errors
+    pub fn with_failures_at_end(mut self, failures_at_end: bool) -> Self {
+        self.failures_at_end = failures_at_end;
+        self
+    }
+
+    /// Emphasize matches of a pattern in printed summaries
+    pub fn with_highlight(mut self, highlight: Option<Highlight>) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
+    /// Fold stderr of successful commands into stdout before formatting
+    pub fn with_stderr_merge(mut self, stderr_merge: bool) -> Self {
+        self.stderr_merge = stderr_merge;
+        self
+    }
+
+    /// Record progress to `run_state` and skip repos it lists as completed
+    pub fn with_run_state(mut self, run_state: Option<RunState>) -> Self {
+        self.run_state = run_state;
+        self
+    }
+
+    /// Hosts whose remote URLs the URL scheme rewrites (default: github.com)
+    pub fn with_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.url_hosts = hosts;
+        self
+    }
+
+    /// Run every git command without system or user git config
+    pub fn with_isolated(mut self, isolated: bool) -> Self {
+        self.isolated = isolated;
+        self
+    }
+
+    /// Run every command with `GIT_SSH_COMMAND` set to this
+    pub fn with_ssh_command(mut self, ssh_command: Option<String>) -> Self {
+        self.ssh_command = ssh_command;
+        self
+    }
+
+    /// Kill any single process still running after this long, reporting TIMEOUT
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_labels(mut self, labels: Option<RepoLabels>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Rewrite displayed repo names (display only; never affects which repo runs)
+    pub fn with_name_replace(mut self, name_replace: Option<NameReplace>) -> Self {
+        self.name_replace = name_replace;
+        self
+    }
+
+    /// Print only a `key=value` tally of result categories instead of per-repo lines
+    pub fn with_count_by_category(mut self, count_by_category: bool) -> Self {
+        self.count_by_category = count_by_category;
+        self
+    }
+
+    /// Buffer results and print repos with identical summaries together
+    pub fn with_grouping(mut self, group_identical: bool, summary_sort: SummarySort) -> Self {
+        self.group_identical = group_identical;
+        self.summary_sort = summary_sort;
+        self
+    }
+
+    /// Order per-repo output by name, status, or path
+    pub fn with_sort(mut self, sort: OutputSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Buffer results and print them under a header per parent directory
+    pub fn with_group_by_dir(mut self, group_by_dir: bool) -> Self {
+        self.group_by_dir = group_by_dir;
+        self
+    }
+
+    /// Print each repo's full output, one prefixed line at a time, instead of a summary
+    pub fn with_prefix_lines(mut self, prefix_lines: bool) -> Self {
+        self.prefix_lines = prefix_lines;
+        self
+    }
+
+    /// Color repo names by the given dimension (None disables coloring)
+    pub fn with_color_by(mut self, color_by: Option<ColorBy>) -> Self {
+        self.color_by = color_by;
+        self
+    }
+
+    /// Color result summaries by category: clean green, dirty yellow, errors red
+    pub fn with_color_status(mut self, color_status: bool) -> Self {
+        self.color_status = color_status;
+        self
+    }
+
     pub fn is_dry_run(&self) -> bool {
         self.dry_run
     }
 
-    pub fn url_scheme(&self) -> Option<UrlScheme> {
+    /// The URL rewriting to apply to commands that talk to a remote, if any
+    pub fn url_rewrite(&self) -> Option<UrlRewrite> {
         self.url_scheme
+            .map(|scheme| UrlRewrite::new(scheme, &self.url_hosts))
     }
 
     pub fn max_connections(&self) -> usize {
@@ -114,192 +682,1359 @@ impl ExecutionContext {
     pub fn display_root(&self) -> &std::path::Path {
         &self.display_root
     }
-}
 
-/// A git command ready to be executed against a repository
-pub struct GitCommand {
-    pub repo_path: PathBuf,
-    pub args: Vec<String>,
-}
-
-impl GitCommand {
-    pub fn new(repo_path: PathBuf, args: Vec<String>) -> Self {
-        Self { repo_path, args }
+    pub fn run_state(&self) -> Option<&RunState> {
+        self.run_state.as_ref()
     }
 
-    /// Spawn the git command without waiting for completion.
-    /// Returns immediately with a Child process handle.
-    pub fn spawn(&self, url_scheme: Option<UrlScheme>) -> std::io::Result<std::process::Child> {
-        let mut cmd = Command::new("git");
-
-        // Inject URL scheme override if specified (must come before other args)
-        if let Some(scheme) = url_scheme {
-            match scheme {
-                UrlScheme::Ssh => {
-                    cmd.arg("-c")
-                        .arg("url.git@github.com:.insteadOf=https://github.com/");
-                }
-                UrlScheme::Https => {
-                    cmd.arg("-c")
-                        .arg("url.https://github.com/.insteadOf=git@github.com:");
-                }
-            }
-        }
+    pub fn stderr_merge(&self) -> bool {
+        self.stderr_merge
+    }
 
-        cmd.arg("-C")
-            .arg(&self.repo_path)
-            .args(&self.args)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .env("GIT_TERMINAL_PROMPT", "0")
-            .spawn()
+    pub fn json(&self) -> bool {
+        self.json
     }
 
-    /// Build the full command string for display (used in dry-run)
-    pub fn command_string_with_scheme(&self, url_scheme: Option<UrlScheme>) -> String {
-        let scheme_args = match url_scheme {
-            Some(UrlScheme::Ssh) => "-c \"url.git@github.com:.insteadOf=https://github.com/\" ",
-            Some(UrlScheme::Https) => "-c \"url.https://github.com/.insteadOf=git@github.com:\" ",
-            None => "",
-        };
-        format!(
-            "git {}-C {} {}",
-            scheme_args,
-            self.repo_path.display(),
-            self.args.join(" ")
-        )
+    pub fn timings(&self) -> bool {
+        self.timings
     }
-}
 
-/// Trait for formatting command output into one line
-pub trait OutputFormatter: Sync {
-    fn format(&self, output: &Output) -> String;
-}
+    pub fn summary(&self) -> bool {
+        self.summary
+    }
 
-/// Run commands in parallel across all repos with streaming output.
-///
-/// Results are printed in alphabetical order (repos are pre-sorted) as soon as
-/// contiguous results are available. Uses head-of-line blocking: if repo "aaa"
-/// is slow, "bbb" and "ccc" won't print until "aaa" completes.
-///
-/// Uses thread-per-process pattern with `wait_with_output()` which is deadlock-safe
-/// (stdlib internally spawns threads to drain stdout/stderr concurrently).
-pub fn run_parallel<F>(
-    ctx: &ExecutionContext,
-    repos: &[PathBuf],
-    build_command: F,
-    formatter: &dyn OutputFormatter,
-) -> Result<()>
-where
-    F: Fn(&PathBuf) -> GitCommand + Sync,
-{
-    let url_scheme = ctx.url_scheme();
+    pub fn stderr_summary(&self) -> bool {
+        self.stderr_summary && !self.summary
+    }
 
-    if ctx.is_dry_run() {
-        for repo in repos {
-            let cmd = build_command(repo);
-            println!("{}", cmd.command_string_with_scheme(url_scheme));
-        }
-        return Ok(());
+    pub fn fair(&self) -> bool {
+        self.fair
     }
 
-    let name_width = compute_name_width(repos, ctx.display_root());
+    pub fn failures_at_end(&self) -> bool {
+        self.failures_at_end
+    }
 
-    let max_workers = ctx.max_connections();
+    pub fn max_line_width(&self) -> Option<usize> {
+        self.max_line_width
+    }
 
-    let semaphore = if max_workers > 0 && max_workers < repos.len() {
-        Some(Arc::new(Semaphore::new(max_workers)))
-    } else {
-        None
-    };
+    /// Categories of every repo run so far, whether or not it was printed.
+    /// Exit codes are computed from this, never from what was displayed.
+    pub fn categories(&self) -> Vec<Category> {
+        self.categories
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, c)| *c)
+            .collect()
+    }
 
-    let mut results: Vec<Option<(PathBuf, Result<Output, std::io::Error>)>> =
-        (0..repos.len()).map(|_| None).collect();
-    let mut next_to_print: usize = 0;
+    /// How many repos failed: a non-zero git exit or a command that couldn't run
+    pub fn failure_count(&self) -> usize {
+        self.categories()
+            .iter()
+            .filter(|c| **c == Category::Error)
+            .count()
+    }
 
-    let (tx, rx) = mpsc::channel();
+    /// Repos run so far whose result fell in `category`, in repo order
+    pub fn repos_in(&self, category: Category) -> Vec<PathBuf> {
+        let mut repos: Vec<PathBuf> = self
+            .categories
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, c)| *c == category)
+            .map(|(repo, _)| repo.clone())
+            .collect();
+        repos.sort();
+        repos
+    }
 
-    std::thread::scope(|s| {
-        for (idx, repo) in repos.iter().enumerate() {
-            let tx = tx.clone();
-            let cmd = build_command(repo);
-            let repo = repo.clone();
-            let sem = semaphore.clone();
+    fn record_category(&self, repo: &Path, category: Category) {
+        self.categories
+            .lock()
+            .unwrap()
+            .push((repo.to_path_buf(), category));
+    }
 
-            s.spawn(move || {
-                if let Some(ref sem) = sem {
-                    sem.acquire();
-                }
+    /// Whether a result should be printed, after display filters
+    fn shows(&self, repo_result: &RepoResult, formatter: &dyn OutputFormatter) -> bool {
+        repo_result.is_visible(formatter)
+            && (!self.only_dirty || repo_result.category(formatter) == Category::Dirty)
+    }
 
-                let result = cmd.spawn(url_scheme).and_then(|c| c.wait_with_output());
+    /// Summary text as printed, with any `--highlight` matches emphasized
+    fn highlighted(&self, summary: String) -> String {
+        match self.highlight {
+            Some(ref highlight) => highlight.apply(&summary),
+            None => summary,
+        }
+    }
 
-                if let Some(ref sem) = sem {
-                    sem.release();
-                }
+    /// Apply context-wide settings that every command inherits
+    fn prepare(&self, cmd: GitCommand) -> GitCommand {
+        self.command_settings().prepare(cmd)
+    }
 
-                let _ = tx.send((idx, repo, result));
-            });
+    /// Settings for commands a followup runs, so they get the same treatment
+    /// as the command that ran first
+    pub fn command_settings(&self) -> CommandSettings {
+        CommandSettings {
+            timeout: self.timeout,
+            ssh_command: self.ssh_command.clone(),
+            isolated: self.isolated,
         }
-        drop(tx);
+    }
 
-        for (idx, repo, result) in rx {
-            results[idx] = Some((repo, result));
-
-            while next_to_print < results.len() {
-                if let Some((ref repo_path, ref res)) = results[next_to_print] {
-                    print_result(
-                        repo_path,
-                        res,
-                        formatter,
-                        ctx.display_root(),
-                        name_width,
-                    );
-                    next_to_print += 1;
-                } else {
-                    break;
-                }
-            }
+    /// Name shown for a repo: its `--labels` label if it has one, otherwise its
+    /// path relative to the display root after any `--name-replace` transform
+    pub fn display_name(&self, repo: &Path) -> String {
+        let name = repo_display_name(repo, &self.display_root);
+        if let Some(label) = self.labels.as_ref().and_then(|l| l.label(&name, repo)) {
+            return label.to_string();
         }
-    });
-
-    Ok(())
-}
-
-/// Print result for a single repository
-fn print_result(
-    repo_path: &std::path::Path,
-    result: &Result<Output, std::io::Error>,
-    formatter: &dyn OutputFormatter,
-    display_root: &std::path::Path,
-    name_width: usize,
-) {
-    let name = repo_display_name(repo_path, display_root);
-    let output_line = match result {
-        Ok(output) => {
-            let formatted = formatter.format(output);
-            format!("{} {}", format_repo_name(&name, name_width), formatted)
+        match self.name_replace {
+            Some(ref replace) => replace.apply(&name),
+            None => name,
         }
-        Err(e) => format!("{} ERROR: {}", format_repo_name(&name, name_width), e),
-    };
-    println!("{}", output_line);
-}
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    pub fn color_by(&self) -> Option<ColorBy> {
+        self.color_by
+    }
 
-    #[test]
-    fn test_format_repo_name_short() {
-        let result = format_repo_name("my-repo", 24);
-        assert_eq!(result, "[my-repo                 ]");
-        assert_eq!(result.len(), 26); // [ + 24 + ]
+    pub fn legend(&self) -> bool {
+        self.legend
     }
 
-    #[test]
-    fn test_format_repo_name_exact_length() {
-        let result = format_repo_name("exactly-twenty-four-chr", 24);
-        assert_eq!(result.len(), 26);
+    pub fn group_identical(&self) -> bool {
+        self.group_identical
+    }
+
+    pub fn group_by_dir(&self) -> bool {
+        self.group_by_dir
+    }
+
+    pub fn prefix_lines(&self) -> bool {
+        self.prefix_lines
+    }
+
+    pub fn summary_sort(&self) -> SummarySort {
+        self.summary_sort
+    }
+
+    pub fn sort(&self) -> OutputSort {
+        self.sort
+    }
+
+    pub fn count_by_category(&self) -> bool {
+        self.count_by_category
+    }
+}
+
+/// Environment for isolated git children. HOME (and XDG_CONFIG_HOME) name a
+/// directory that doesn't exist, so git finds no ~/.gitconfig or
+/// ~/.config/git/config, and GIT_CONFIG_GLOBAL would otherwise name a user
+/// config file despite HOME; repository config still applies.
+const ISOLATION_ENV: [(&str, &str); 4] = [
+    ("GIT_CONFIG_NOSYSTEM", "1"),
+    ("HOME", "/nonexistent/git-all-isolated"),
+    ("XDG_CONFIG_HOME", "/nonexistent/git-all-isolated"),
+    ("GIT_CONFIG_GLOBAL", "/dev/null"),
+];
+
+/// The context-wide settings every git command runs with (see
+/// `ExecutionContext::command_settings`). Followups build their commands with
+/// it, since they run outside `run_parallel`'s reach.
+#[derive(Clone, Default)]
+pub struct CommandSettings {
+    timeout: Option<Duration>,
+    ssh_command: Option<String>,
+    isolated: bool,
+}
+
+impl CommandSettings {
+    /// A git command in `repo` with these settings applied
+    pub fn command(&self, repo: &Path, args: Vec<String>) -> GitCommand {
+        self.prepare(GitCommand::new(repo.to_path_buf(), args))
+    }
+
+    fn prepare(&self, mut cmd: GitCommand) -> GitCommand {
+        cmd.timeout = self.timeout;
+        // Programs other than git (exec, bisect-run) keep the user's HOME
+        if self.isolated && cmd.program.is_none() {
+            for (key, value) in ISOLATION_ENV {
+                cmd = cmd.with_env(key, value);
+            }
+        }
+        match self.ssh_command {
+            Some(ref ssh_command) => cmd.with_env("GIT_SSH_COMMAND", ssh_command),
+            None => cmd,
+        }
+    }
+}
+
+/// Extra work run in the same worker slot after a command finishes (a fanned-out
+/// command takes a slot again for it). It receives the command's output and
+/// returns the output to report (which may come from further git commands it ran).
+pub type Followup = Box<dyn FnOnce(Output) -> std::io::Result<Output> + Send>;
+
+/// A git command ready to be executed against a repository
+pub struct GitCommand {
+    pub repo_path: PathBuf,
+    pub args: Vec<String>,
+    /// When set, the repo is not touched and this message is reported instead
+    pub skip_reason: Option<String>,
+    followup: Option<Followup>,
+    /// Further argument lists run alongside `args`, each in its own worker slot
+    fan_out: Vec<Vec<String>>,
+    /// A program to run in the repo directory instead of git
+    program: Option<String>,
+    /// Commands sharing a lock group never run at the same time
+    lock_group: Option<PathBuf>,
+    /// Extra environment variables for the child process
+    env: Vec<(String, String)>,
+    /// Kill the process if it runs longer than this (each fan-out invocation
+    /// gets its own deadline)
+    timeout: Option<Duration>,
+    /// Arguments a dry run shows in place of `args`: the command a gated
+    /// followup would run
+    planned: Option<Vec<String>>,
+}
+
+impl GitCommand {
+    pub fn new(repo_path: PathBuf, args: Vec<String>) -> Self {
+        Self {
+            repo_path,
+            args,
+            skip_reason: None,
+            followup: None,
+            fan_out: Vec::new(),
+            program: None,
+            lock_group: None,
+            env: Vec::new(),
+            timeout: None,
+            planned: None,
+        }
+    }
+
+    /// A placeholder for a repo that should not be run, reported with `reason`
+    pub fn skipped(repo_path: PathBuf, reason: impl Into<String>) -> Self {
+        Self {
+            repo_path,
+            args: Vec::new(),
+            skip_reason: Some(reason.into()),
+            followup: None,
+            fan_out: Vec::new(),
+            program: None,
+            lock_group: None,
+            env: Vec::new(),
+            timeout: None,
+            planned: None,
+        }
+    }
+
+    /// Run an arbitrary program (not git) with the repo as its working directory.
+    /// No git config (such as the URL scheme) is injected.
+    pub fn program(repo_path: PathBuf, program: String, args: Vec<String>) -> Self {
+        Self {
+            program: Some(program),
+            ..Self::new(repo_path, args)
+        }
+    }
+
+    /// Run `followup` with this command's output before it is reported.
+    /// Not shown in dry-run output, since what it does depends on the result.
+    pub fn with_followup(mut self, followup: Followup) -> Self {
+        self.followup = Some(followup);
+        self
+    }
+
+    /// Also run each of `args_lists` in this repo, in parallel with `args`.
+    /// Every invocation takes its own worker slot; their outputs are combined
+    /// into one result (see `combine_outputs`) before formatting.
+    pub fn with_fan_out(mut self, args_lists: Vec<Vec<String>>) -> Self {
+        self.fan_out = args_lists;
+        self
+    }
+
+    /// Run this repo's command (fan-out and followup included) only while no
+    /// other command in the same `group` is running, whatever the worker count.
+    /// The group is any key naming a shared resource, e.g. an object store.
+    pub fn with_lock_group(mut self, group: PathBuf) -> Self {
+        self.lock_group = Some(group);
+        self
+    }
+
+    /// Show `args` in dry-run output instead of this command's own. For a
+    /// command that only checks whether its followup should run `args`, so a
+    /// dry run lists the real work rather than the check.
+    pub fn with_planned(mut self, args: Vec<String>) -> Self {
+        self.planned = Some(args);
+        self
+    }
+
+    /// Set an environment variable for the command (shown in dry-run output)
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// What this runs, for display: the program, or the git subcommand after
+    /// any leading `-c key=value` pairs
+    pub fn name(&self) -> &str {
+        if let Some(ref program) = self.program {
+            return program;
+        }
+        let mut args = self.planned.as_ref().unwrap_or(&self.args).iter();
+        while let Some(arg) = args.next() {
+            if arg != "-c" {
+                return arg;
+            }
+            args.next();
+        }
+        "git"
+    }
+
+    /// This command plus one per fan-out argument list
+    fn invocations(&self) -> Vec<GitCommand> {
+        std::iter::once(self.args.clone())
+            .chain(self.fan_out.iter().cloned())
+            .map(|args| GitCommand {
+                program: self.program.clone(),
+                env: self.env.clone(),
+                timeout: self.timeout,
+                ..GitCommand::new(self.repo_path.clone(), args)
+            })
+            .collect()
+    }
+
+    /// What a dry run prints for this command: its invocations, or the planned
+    /// command when one is set
+    fn planned_invocations(&self) -> Vec<GitCommand> {
+        match self.planned {
+            Some(ref args) => vec![GitCommand {
+                program: self.program.clone(),
+                env: self.env.clone(),
+                timeout: self.timeout,
+                ..GitCommand::new(self.repo_path.clone(), args.clone())
+            }],
+            None => self.invocations(),
+        }
+    }
+
+    /// Run the command to completion, capturing its output. With a timeout, a
+    /// process that overruns is killed and a `TimedOut` error returned.
+    /// Nothing is started once the run has been interrupted.
+    pub fn run(&self, url_rewrite: Option<&UrlRewrite>) -> std::io::Result<Output> {
+        if interrupt::interrupted() {
+            return Err(interrupted_error());
+        }
+        // Discovery may have run before the volume holding the repo went away
+        if let Some(e) = unavailable_error(&self.repo_path) {
+            return Err(e);
+        }
+        let child = self.spawn(url_rewrite)?;
+        let _tracked = interrupt::track(child.id());
+        match self.timeout {
+            Some(timeout) => wait_with_timeout(child, timeout),
+            None => child.wait_with_output(),
+        }
+    }
+
+    /// Spawn the git command without waiting for completion.
+    /// Returns immediately with a Child process handle.
+    pub fn spawn(&self, url_rewrite: Option<&UrlRewrite>) -> std::io::Result<std::process::Child> {
+        if let Some(ref program) = self.program {
+            return Command::new(program)
+                .args(&self.args)
+                .envs(self.env.iter().map(|(k, v)| (k, v)))
+                .current_dir(&self.repo_path)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn();
+        }
+
+        self.git_command(url_rewrite).spawn()
+    }
+
+    fn git_command(&self, url_rewrite: Option<&UrlRewrite>) -> Command {
+        let mut cmd = Command::new("git");
+
+        // Inject URL scheme override if specified (must come before other args)
+        if let Some(rewrite) = url_rewrite {
+            cmd.args(rewrite.config_args());
+        }
+
+        cmd.arg("-C")
+            .arg(&self.repo_path)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .env("GIT_TERMINAL_PROMPT", "0");
+        cmd.envs(self.env.iter().map(|(k, v)| (k, v)));
+        cmd
+    }
+
+    /// Build the full command string for display (used in dry-run).
+    /// Every word is shell-quoted so the line can be copied and run as-is.
+    pub fn command_string_with_scheme(&self, url_rewrite: Option<&UrlRewrite>) -> String {
+        let env: Vec<String> = self
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, shell_quote(value)))
+            .collect();
+
+        if let Some(ref program) = self.program {
+            let words: Vec<String> = std::iter::once(program)
+                .chain(&self.args)
+                .map(|w| shell_quote(w))
+                .collect();
+            return format!(
+                "(cd {} && {})",
+                shell_quote(&self.repo_path.to_string_lossy()),
+                env.into_iter().chain(words).collect::<Vec<_>>().join(" ")
+            );
+        }
+
+        let mut words = vec!["git".to_string()];
+        if let Some(rewrite) = url_rewrite {
+            words.extend(rewrite.config_args());
+        }
+        words.push("-C".to_string());
+        words.push(self.repo_path.to_string_lossy().to_string());
+        words.extend(self.args.iter().cloned());
+
+        env.into_iter()
+            .chain(words.iter().map(|w| shell_quote(w)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Merge outputs of fanned-out invocations into one, in invocation order.
+/// The result fails with the first failing status; failed invocations' stderr
+/// comes first so the reported error line is a real one.
+pub fn combine_outputs(results: Vec<std::io::Result<Output>>) -> std::io::Result<Output> {
+    let mut outputs = results
+        .into_iter()
+        .collect::<std::io::Result<Vec<Output>>>()?;
+    outputs.sort_by_key(|o| o.status.success());
+
+    let mut combined = outputs.remove(0);
+    for output in outputs {
+        combined.stdout.extend(output.stdout);
+        combined.stderr.extend(output.stderr);
+    }
+    Ok(combined)
+}
+
+/// Reported for repos cut short (or never started) because of Ctrl-C
+fn interrupted_error() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Interrupted, "INTERRUPTED")
+}
+
+fn is_interrupted_error(e: &std::io::Error) -> bool {
+    e.kind() == std::io::ErrorKind::Interrupted
+}
+
+/// Prefix of the error for a repo directory that is gone or unreadable
+const UNAVAILABLE: &str = "UNAVAILABLE";
+
+/// Reported instead of a confusing spawn or git error when `dir` is missing
+/// (e.g. an unmounted volume), isn't a directory, or can't be read
+fn unavailable_error(dir: &Path) -> Option<std::io::Error> {
+    let reason = match std::fs::metadata(dir) {
+        Ok(metadata) if metadata.is_dir() => return None,
+        Ok(_) => "not a directory".to_string(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => "path missing".to_string(),
+        Err(e) => e.to_string(),
+    };
+    Some(std::io::Error::other(format!(
+        "{} ({})",
+        UNAVAILABLE, reason
+    )))
+}
+
+fn is_unavailable_error(e: &std::io::Error) -> bool {
+    e.to_string().starts_with(UNAVAILABLE)
+}
+
+/// How often `wait_with_timeout` checks whether the child has exited
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Like `Child::wait_with_output`, but kill the child once `timeout` has passed.
+/// stdout and stderr are drained on their own threads so a chatty child never
+/// blocks on a full pipe while we poll. A killed child is still waited on, so
+/// no zombie is left behind; its reader threads are not joined, since a
+/// grandchild (e.g. ssh) may hold the pipes open a while longer.
+fn wait_with_timeout(mut child: std::process::Child, timeout: Duration) -> std::io::Result<Output> {
+    fn drain(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            child.wait()?;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("TIMEOUT after {}s", timeout.as_secs_f64()),
+            ));
+        }
+        std::thread::sleep(TIMEOUT_POLL_INTERVAL);
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+/// Quote a word for POSIX sh so dry-run output can be pasted back into a shell.
+/// Words made only of safe characters are left as-is.
+pub fn shell_quote(word: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c);
+    if !word.is_empty() && word.chars().all(is_safe) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+/// Numeric exit code of a finished command. A unix process killed by a signal
+/// has no exit code; it is reported as 128 + signal number, as shells do.
+pub fn exit_code(output: &Output) -> Option<i32> {
+    if let Some(code) = output.status.code() {
+        return Some(code);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        output.status.signal().map(|signal| 128 + signal)
+    }
+
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+/// One-line description of a failed command: its first non-empty stderr line,
+/// or its exit code when git printed nothing.
+pub fn error_line(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if let Some(line) = stderr.lines().map(str::trim).find(|l| !l.is_empty()) {
+        return line.to_string();
+    }
+    match exit_code(output) {
+        Some(code) => format!("exited with code {}", code),
+        None => "unknown error".to_string(),
+    }
+}
+
+/// Append a successful command's stderr to its stdout, so formatters that
+/// summarize from stdout also see results git reports on stderr (push, checkout).
+/// Failed output is left alone; its stderr is the error.
+pub fn merge_stderr(mut output: Output) -> Output {
+    if !output.status.success() || output.stderr.is_empty() {
+        return output;
+    }
+    if !output.stdout.is_empty() && !output.stdout.ends_with(b"\n") {
+        output.stdout.push(b'\n');
+    }
+    output.stdout.append(&mut output.stderr);
+    output
+}
+
+/// First non-empty line of stdout, falling back to stderr
+pub fn first_output_line(output: &Output) -> Option<String> {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(str::to_string)
+}
+
+/// Trait for formatting command output into one line
+pub trait OutputFormatter: Sync {
+    fn format(&self, output: &Output) -> String;
+
+    /// Format to fit in `width` columns after the repo name, when the terminal
+    /// width is known. Formatters that can elide detail override this.
+    fn format_for_width(&self, output: &Output, _width: Option<usize>) -> String {
+        self.format(output)
+    }
+
+    /// Format with access to the repository path, for formatters that need to
+    /// inspect files the command produced.
+    fn format_for_repo(&self, _repo: &Path, output: &Output, width: Option<usize>) -> String {
+        self.format_for_width(output, width)
+    }
+
+    /// Classify the result. Formatters with richer outcomes (clean vs dirty,
+    /// updated vs up to date) override this.
+    fn category(&self, output: &Output) -> Category {
+        if output.status.success() {
+            Category::Ok
+        } else {
+            Category::Error
+        }
+    }
+
+    /// Whether to print this result at all. Formatters that filter repos
+    /// (e.g. `status --find`) hide the ones that don't match.
+    fn is_visible(&self, _output: &Output) -> bool {
+        true
+    }
+
+    /// Structured detail added to the repo's `--json` record, e.g. status
+    /// counts. Only asked for when the command succeeded.
+    fn json_fields(&self, _output: &Output) -> serde_json::Map<String, serde_json::Value> {
+        serde_json::Map::new()
+    }
+}
+
+/// Maximum printed line width: `--max-line-width` when given (0 disables),
+/// otherwise the terminal width, or None when stdout is not a terminal.
+fn line_width(ctx: &ExecutionContext) -> Option<usize> {
+    match ctx.max_line_width() {
+        Some(0) => None,
+        Some(width) => Some(width),
+        None => {
+            let (terminal_size::Width(cols), _) = terminal_size::terminal_size()?;
+            Some(cols as usize)
+        }
+    }
+}
+
+/// Columns left for a formatter's summary after the `[repo] ` prefix
+fn summary_width(name_width: usize, line_width: Option<usize>) -> Option<usize> {
+    line_width.map(|cols| cols.saturating_sub(name_width + 3))
+}
+
+/// Cut `text` to at most `width` characters, ending in an ellipsis when cut.
+/// Counts chars, so multi-byte text is never split mid-character.
+fn truncate_to_width(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut truncated: String = text.chars().take(width - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Print how many repos a dry run would touch, before its planned commands,
+/// e.g. "[git-all] would run 'pull' on 47 repositories". Skipped repos are not
+/// counted. Under `--json` this is a `{"dry_run":{...}}` object instead.
+fn print_dry_run_scope(ctx: &ExecutionContext, commands: &[GitCommand]) {
+    let runnable: Vec<&GitCommand> = commands
+        .iter()
+        .filter(|c| c.skip_reason.is_none())
+        .collect();
+    let name = runnable.first().map_or("git", |cmd| cmd.name());
+    if ctx.json() {
+        println!(
+            "{}",
+            serde_json::json!({ "dry_run": { "command": name, "repos": runnable.len() } })
+        );
+    } else {
+        println!(
+            "[git-all] would run '{}' on {} {}",
+            name,
+            runnable.len(),
+            if runnable.len() == 1 {
+                "repository"
+            } else {
+                "repositories"
+            }
+        );
+    }
+}
+
+/// Run commands in parallel across all repos with streaming output.
+///
+/// Results are printed in alphabetical order (repos are pre-sorted) as soon as
+/// contiguous results are available. Uses head-of-line blocking: if repo "aaa"
+/// is slow, "bbb" and "ccc" won't print until "aaa" completes.
+///
+/// With `--group-identical`, output is buffered and printed grouped by summary
+/// once every repo has finished. `--sort=status` buffers the same way.
+///
+/// Uses thread-per-process pattern with `wait_with_output()` which is deadlock-safe
+/// (stdlib internally spawns threads to drain stdout/stderr concurrently).
+pub fn run_parallel<F>(
+    ctx: &ExecutionContext,
+    repos: &[Repo],
+    build_command: F,
+    formatter: &dyn OutputFormatter,
+) -> Result<()>
+where
+    F: Fn(&Repo) -> GitCommand + Sync,
+{
+    let url_rewrite = ctx.url_rewrite();
+    let url_rewrite = url_rewrite.as_ref();
+    let run_state = ctx.run_state();
+
+    let build_command = |repo: &Repo| match run_state {
+        Some(state) if state.is_completed(repo) => GitCommand::skipped(
+            repo.path().to_path_buf(),
+            "completed in previous run, skipped",
+        ),
+        _ => ctx.prepare(build_command(repo)),
+    };
+
+    if ctx.is_dry_run() {
+        let commands: Vec<GitCommand> = repos.iter().map(build_command).collect();
+        print_dry_run_scope(ctx, &commands);
+        for cmd in commands {
+            match cmd.skip_reason {
+                Some(ref reason) => println!("# skip {}: {}", cmd.repo_path.display(), reason),
+                None => {
+                    for invocation in cmd.planned_invocations() {
+                        println!("{}", invocation.command_string_with_scheme(url_rewrite));
+                    }
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    if ctx.legend()
+        && !ctx.json()
+        && let Some(color_by) = ctx.color_by()
+    {
+        println!("{}", color_legend(repos, color_by, ctx.display_root()));
+    }
+
+    let name_width = compute_name_width(repos, ctx);
+    let width = summary_width(name_width, line_width(ctx));
+
+    let max_workers = ctx.max_connections();
+
+    // Fanned-out commands can run more git processes than there are repos,
+    // so any worker limit needs the semaphore
+    let semaphore = if max_workers > 0 {
+        Some(Arc::new(if ctx.fair() {
+            Semaphore::fifo(max_workers)
+        } else {
+            Semaphore::new(max_workers)
+        }))
+    } else {
+        None
+    };
+
+    let color_by = ctx.color_by();
+    let stderr_merge = ctx.stderr_merge();
+    let display_root = ctx.display_root();
+
+    // JSON lines stream in repo order; grouping only applies to text output.
+    // Sorting by status has to wait for every result.
+    let streaming = !ctx.count_by_category()
+        && ctx.sort() != OutputSort::Status
+        && (ctx.json() || !(ctx.group_identical() || ctx.group_by_dir()));
+    let order = print_order(repos, ctx);
+    let started = Instant::now();
+
+    let mut results: Vec<Option<RepoResult>> = (0..repos.len()).map(|_| None).collect();
+    let mut next_to_print: usize = 0;
+    // Transient stderr line for results held back behind a slower repo
+    let show_blocked = ctx.fair() && streaming && !ctx.json() && std::io::stderr().is_terminal();
+    let mut blocked_status_shown = false;
+
+    let commands: Vec<GitCommand> = repos.iter().map(&build_command).collect();
+    let group_locks: HashMap<PathBuf, Mutex<()>> = commands
+        .iter()
+        .filter_map(|cmd| cmd.lock_group.clone())
+        .map(|group| (group, Mutex::new(())))
+        .collect();
+    let group_locks = &group_locks;
+
+    let (tx, rx) = mpsc::channel();
+
+    std::thread::scope(|s| {
+        for ((idx, repo), cmd) in repos.iter().enumerate().zip(commands) {
+            let tx = tx.clone();
+            let repo = repo.clone();
+            let sem = semaphore.clone();
+
+            s.spawn(move || {
+                let mut cmd = cmd;
+                let followup = cmd.followup.take();
+                let mut duration = None;
+                let outcome = match cmd.skip_reason {
+                    Some(reason) => Outcome::Skipped(reason),
+                    None => {
+                        // Taken before any worker permit, so waiting on the group
+                        // doesn't hold a slot another repo could use
+                        let _group_guard = cmd
+                            .lock_group
+                            .as_ref()
+                            .map(|group| group_locks[group].lock().unwrap());
+
+                        let permit = || sem.as_deref().map(Semaphore::permit);
+                        // Each invocation is timed after acquiring its permit,
+                        // so queueing isn't counted
+                        let run_one = |cmd: &GitCommand| {
+                            let started = Instant::now();
+                            (cmd.run(url_rewrite), started.elapsed())
+                        };
+                        let run_followup = |result: std::io::Result<Output>| {
+                            let started = Instant::now();
+                            let result = result.and_then(|output| match followup {
+                                Some(followup) => followup(output),
+                                None => Ok(output),
+                            });
+                            (result, started.elapsed())
+                        };
+
+                        let (result, elapsed) = if cmd.fan_out.is_empty() {
+                            // One slot covers the command and its followup
+                            let _permit = permit();
+                            let (result, elapsed) = run_one(&cmd);
+                            let (result, followup_elapsed) = run_followup(result);
+                            (result, elapsed + followup_elapsed)
+                        } else {
+                            let (result, elapsed) = std::thread::scope(|fan| {
+                                let handles: Vec<_> = cmd
+                                    .invocations()
+                                    .into_iter()
+                                    .map(|invocation| {
+                                        fan.spawn(move || {
+                                            let _permit = permit();
+                                            run_one(&invocation)
+                                        })
+                                    })
+                                    .collect();
+                                let (results, times): (Vec<_>, Vec<_>) = handles
+                                    .into_iter()
+                                    .map(|h| h.join().expect("git worker panicked"))
+                                    .unzip();
+                                (
+                                    combine_outputs(results),
+                                    times.into_iter().max().unwrap_or_default(),
+                                )
+                            });
+                            // The fanned-out slots are free again; the followup needs its own
+                            let _permit = permit();
+                            let (result, followup_elapsed) = run_followup(result);
+                            (result, elapsed + followup_elapsed)
+                        };
+                        duration = Some(elapsed);
+
+                        match result {
+                            // Killed, or failing because a helper was, after Ctrl-C
+                            Ok(output) if !output.status.success() && interrupt::interrupted() => {
+                                Outcome::SpawnError(interrupted_error())
+                            }
+                            Ok(output) if stderr_merge => Outcome::Output(merge_stderr(output)),
+                            Ok(output) => Outcome::Output(output),
+                            Err(e) => Outcome::SpawnError(e),
+                        }
+                    }
+                };
+
+                let color_key = color_by.map(|dim| color_key(&repo, dim, display_root));
+                let _ = tx.send((
+                    idx,
+                    RepoResult {
+                        repo: repo.path().to_path_buf(),
+                        outcome,
+                        color_key,
+                        duration,
+                    },
+                ));
+            });
+        }
+        drop(tx);
+
+        for (idx, repo_result) in rx {
+            ctx.record_category(&repo_result.repo, repo_result.category(formatter));
+            if let Some(state) = run_state
+                && repo_result.category(formatter) != Category::Error
+                && let Err(e) = state.record(&repo_result.repo)
+            {
+                eprintln!("git-all: failed to record progress: {}", e);
+            }
+            results[idx] = Some(repo_result);
+
+            while streaming && next_to_print < order.len() {
+                if let Some(ref repo_result) = results[order[next_to_print]] {
+                    if blocked_status_shown {
+                        eprint!("{}", CLEAR_LINE);
+                        blocked_status_shown = false;
+                    }
+                    if ctx.shows(repo_result, formatter) {
+                        if ctx.json() {
+                            println!("{}", json_record(repo_result, formatter, ctx));
+                        } else {
+                            print_result(repo_result, formatter, ctx, name_width, width);
+                        }
+                    }
+                    next_to_print += 1;
+                } else {
+                    break;
+                }
+            }
+
+            if show_blocked && next_to_print < order.len() {
+                let blocked = order[next_to_print..]
+                    .iter()
+                    .filter(|&&i| results[i].is_some())
+                    .count();
+                if blocked > 0 {
+                    let waiting_on = &repos[order[next_to_print]];
+                    let status = blocked_status(&ctx.display_name(waiting_on), blocked);
+                    eprint!("{}{}", CLEAR_LINE, status);
+                    blocked_status_shown = true;
+                }
+            }
+        }
+    });
+
+    let results: Vec<RepoResult> = results.into_iter().flatten().collect();
+
+    if let Some(state) = run_state
+        && results
+            .iter()
+            .all(|r| r.category(formatter) != Category::Error)
+    {
+        state.clear()?;
+    }
+
+    if ctx.count_by_category() {
+        let categories: Vec<Category> = results.iter().map(|r| r.category(formatter)).collect();
+        println!("{}", format_tally(&categories));
+    } else if ctx.group_identical() && !ctx.json() {
+        let entries = results
+            .iter()
+            .filter(|r| ctx.shows(r, formatter))
+            .map(|r| (r, summary_text(r, formatter, width)))
+            .collect();
+        for (summary, members) in group_by_summary(entries, ctx.summary_sort()) {
+            println!("{} ({})", ctx.highlighted(summary), members.len());
+            for repo_result in members {
+                println!("  {}", display_label(repo_result, ctx, None));
+            }
+        }
+    } else if ctx.group_by_dir() && !ctx.json() {
+        let visible = results.iter().filter(|r| ctx.shows(r, formatter)).collect();
+        let indented_width = width.map(|w| w.saturating_sub(2));
+        for (dir, members) in group_by_parent_dir(visible, display_root) {
+            println!("{}", dir);
+            for repo_result in members {
+                print!("  ");
+                print_result(repo_result, formatter, ctx, name_width, indented_width);
+            }
+        }
+    } else if ctx.sort() == OutputSort::Status {
+        for repo_result in status_order(&results, ctx, formatter) {
+            if ctx.json() {
+                println!("{}", json_record(repo_result, formatter, ctx));
+            } else {
+                print_result(repo_result, formatter, ctx, name_width, width);
+            }
+        }
+    }
+
+    // Failures are listed even if a display filter hid them above
+    let failed: Vec<&RepoResult> = results
+        .iter()
+        .filter(|r| r.category(formatter) == Category::Error)
+        .collect();
+    if ctx.failures_at_end() && !ctx.json() && !ctx.count_by_category() && !failed.is_empty() {
+        println!();
+        println!("Failed repositories:");
+        for repo_result in failed {
+            println!(
+                "  {} {}",
+                display_label(repo_result, ctx, Some(name_width)),
+                ctx.highlighted(failure_line(repo_result, formatter))
+            );
+        }
+    }
+
+    // Counts cover every repo, including those hidden by display filters
+    if ctx.summary() && !ctx.count_by_category() {
+        let categories: Vec<Category> = results.iter().map(|r| r.category(formatter)).collect();
+        if ctx.json() {
+            let counts: serde_json::Map<String, serde_json::Value> = Category::ALL
+                .iter()
+                .map(|cat| {
+                    let count = categories.iter().filter(|c| *c == cat).count();
+                    (cat.key().to_string(), count.into())
+                })
+                .collect();
+            println!("{}", serde_json::json!({ "summary": counts }));
+        } else {
+            println!("{}", format_summary_footer(&categories));
+        }
+    }
+
+    if ctx.timings() && !ctx.count_by_category() {
+        let total_ms = started.elapsed().as_millis() as u64;
+        if ctx.json() {
+            println!("{}", serde_json::json!({ "total_ms": total_ms }));
+        } else {
+            println!("total {}ms", total_ms);
+        }
+    }
+
+    if ctx.stderr_summary() && !ctx.count_by_category() {
+        let categories: Vec<Category> = results.iter().map(|r| r.category(formatter)).collect();
+        eprintln!("{}", format_summary_footer(&categories));
+    }
+
+    if interrupt::interrupted() {
+        let cut_short = results
+            .iter()
+            .filter(|r| matches!(r.outcome, Outcome::SpawnError(ref e) if is_interrupted_error(e)))
+            .count();
+        eprintln!(
+            "git-all: interrupted: {} completed, {} interrupted",
+            results.len() - cut_short,
+            cut_short
+        );
+    }
+
+    Ok(())
+}
+
+/// Run commands in parallel and return each repo's output, in repo order,
+/// without printing anything. For commands that merge results across repos
+/// before display. In dry-run mode the planned commands are printed and
+/// nothing is returned.
+pub fn collect_parallel<F>(
+    ctx: &ExecutionContext,
+    repos: &[Repo],
+    build_command: F,
+) -> Vec<(PathBuf, std::io::Result<Output>)>
+where
+    F: Fn(&Repo) -> GitCommand + Sync,
+{
+    let url_rewrite = ctx.url_rewrite();
+    let url_rewrite = url_rewrite.as_ref();
+    let build_command = |repo: &Repo| ctx.prepare(build_command(repo));
+
+    if ctx.is_dry_run() {
+        let commands: Vec<GitCommand> = repos.iter().map(build_command).collect();
+        print_dry_run_scope(ctx, &commands);
+        for cmd in commands {
+            println!("{}", cmd.command_string_with_scheme(url_rewrite));
+        }
+        return Vec::new();
+    }
+
+    let max_workers = ctx.max_connections();
+    let semaphore = if max_workers > 0 && max_workers < repos.len() {
+        Some(if ctx.fair() {
+            Semaphore::fifo(max_workers)
+        } else {
+            Semaphore::new(max_workers)
+        })
+    } else {
+        None
+    };
+
+    std::thread::scope(|s| {
+        let handles: Vec<_> = repos
+            .iter()
+            .map(|repo| {
+                let cmd = build_command(repo);
+                let sem = semaphore.as_ref();
+                s.spawn(move || {
+                    let _permit = sem.map(Semaphore::permit);
+                    cmd.run(url_rewrite)
+                })
+            })
+            .collect();
+
+        repos
+            .iter()
+            .map(|repo| repo.path().to_path_buf())
+            .zip(handles)
+            .map(|(repo, handle)| (repo, handle.join().expect("git worker panicked")))
+            .collect()
+    })
+}
+
+/// Group results by their repo's parent directory relative to `root`, with
+/// groups in alphabetical order ("./" for repos directly under the root).
+/// Results within a group keep their original (alphabetical) order.
+fn group_by_parent_dir<'a>(
+    results: Vec<&'a RepoResult>,
+    root: &Path,
+) -> Vec<(String, Vec<&'a RepoResult>)> {
+    let mut groups: BTreeMap<String, Vec<&RepoResult>> = BTreeMap::new();
+    for repo_result in results {
+        let parent = repo_result.repo.parent().unwrap_or(root);
+        let dir = match parent.strip_prefix(root) {
+            Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+            Ok(rel) => rel.display().to_string(),
+            Err(_) => parent.display().to_string(),
+        };
+        groups
+            .entry(format!("{}/", dir))
+            .or_default()
+            .push(repo_result);
+    }
+    groups.into_iter().collect()
+}
+
+/// Group items by identical summary text, ordering groups per `sort`.
+/// Items within a group keep their original (alphabetical) order.
+fn group_by_summary<T>(entries: Vec<(T, String)>, sort: SummarySort) -> Vec<(String, Vec<T>)> {
+    let mut groups: Vec<(String, Vec<T>)> = Vec::new();
+    for (item, summary) in entries {
+        match groups.iter_mut().find(|(s, _)| *s == summary) {
+            Some((_, members)) => members.push(item),
+            None => groups.push((summary, vec![item])),
+        }
+    }
+
+    match sort {
+        SummarySort::Count => groups.sort_by(|(a_summary, a), (b_summary, b)| {
+            b.len().cmp(&a.len()).then_with(|| a_summary.cmp(b_summary))
+        }),
+        SummarySort::Name => groups.sort_by(|(a, _), (b, _)| a.cmp(b)),
+    }
+    groups
+}
+
+/// What happened when a repository's command was run (or not)
+enum Outcome {
+    Output(Output),
+    SpawnError(std::io::Error),
+    Skipped(String),
+}
+
+/// Outcome of running a command in one repository, as collected by the printer
+struct RepoResult {
+    repo: PathBuf,
+    outcome: Outcome,
+    color_key: Option<String>,
+    /// Time spent running the command; None when it was skipped
+    duration: Option<Duration>,
+}
+
+impl RepoResult {
+    fn category(&self, formatter: &dyn OutputFormatter) -> Category {
+        match &self.outcome {
+            Outcome::Output(output) => formatter.category(output),
+            Outcome::SpawnError(_) => Category::Error,
+            Outcome::Skipped(_) => Category::Skipped,
+        }
+    }
+
+    fn is_visible(&self, formatter: &dyn OutputFormatter) -> bool {
+        match &self.outcome {
+            Outcome::Output(output) => formatter.is_visible(output),
+            Outcome::SpawnError(_) | Outcome::Skipped(_) => true,
+        }
+    }
+}
+
+/// Key used to pick a repo's display color for the given dimension.
+/// Repos without an origin host share the "local" color.
+fn color_key(repo: &Repo, color_by: ColorBy, display_root: &Path) -> String {
+    match color_by {
+        ColorBy::Repo => repo_display_name(repo, display_root),
+        ColorBy::Host => repo.remote_host().unwrap_or_else(|| "local".to_string()),
+    }
+}
+
+/// One line explaining `--color-by` colors: each origin host painted in its
+/// color, or a note that every repo name gets its own
+fn color_legend(repos: &[Repo], color_by: ColorBy, display_root: &Path) -> String {
+    match color_by {
+        ColorBy::Repo => {
+            "Legend: each repo name has its own color, the same on every run".to_string()
+        }
+        ColorBy::Host => {
+            let hosts: BTreeSet<String> = repos
+                .iter()
+                .map(|repo| color_key(repo, color_by, display_root))
+                .collect();
+            let painted: Vec<String> = hosts.iter().map(|host| paint_by_key(host, host)).collect();
+            format!(
+                "Legend: repo names are colored by origin host: {}",
+                painted.join("  ")
+            )
+        }
+    }
+}
+
+/// Repo name for display, padded to `name_width` in brackets when given
+fn display_label(
+    repo_result: &RepoResult,
+    ctx: &ExecutionContext,
+    name_width: Option<usize>,
+) -> String {
+    let name = ctx.display_name(&repo_result.repo);
+    let label = match name_width {
+        Some(name_width) => format_repo_name(&name, name_width),
+        None => name,
+    };
+    match repo_result.color_key {
+        Some(ref key) => paint_by_key(&label, key),
+        None => label,
+    }
+}
+
+/// The one-line summary for a repository's outcome (without the repo name)
+fn summary_text(
+    repo_result: &RepoResult,
+    formatter: &dyn OutputFormatter,
+    width: Option<usize>,
+) -> String {
+    match &repo_result.outcome {
+        Outcome::Output(output) => formatter.format_for_repo(&repo_result.repo, output, width),
+        Outcome::SpawnError(e)
+            if e.kind() == std::io::ErrorKind::TimedOut
+                || is_interrupted_error(e)
+                || is_unavailable_error(e) =>
+        {
+            e.to_string()
+        }
+        Outcome::SpawnError(e) => format!("ERROR: {}", e),
+        Outcome::Skipped(reason) => reason.clone(),
+    }
+}
+
+/// Return to the start of the line and erase it
+const CLEAR_LINE: &str = "\r\x1b[K";
+
+/// Transient progress shown while finished results wait on `waiting_on`
+fn blocked_status(waiting_on: &str, blocked: usize) -> String {
+    format!("waiting on {}: {} finished", waiting_on, blocked)
+}
+
+/// Why a repository failed, for `--failures-at-end`: git's first error line,
+/// or the formatter's summary when the formatter flagged a successful exit
+fn failure_line(repo_result: &RepoResult, formatter: &dyn OutputFormatter) -> String {
+    match &repo_result.outcome {
+        Outcome::Output(output) if !output.status.success() => error_line(output),
+        _ => summary_text(repo_result, formatter, None),
+    }
+}
+
+/// One repository's result as a JSON object (a line of `--json` output)
+fn json_record(
+    repo_result: &RepoResult,
+    formatter: &dyn OutputFormatter,
+    ctx: &ExecutionContext,
+) -> serde_json::Value {
+    let mut record = serde_json::json!({
+        "repo": ctx.display_name(&repo_result.repo),
+        "path": repo_result.repo.to_string_lossy(),
+        "category": repo_result.category(formatter).key(),
+        "summary": summary_text(repo_result, formatter, None),
+    });
+    if let Outcome::Output(ref output) = repo_result.outcome
+        && output.status.success()
+        && let Some(fields) = record.as_object_mut()
+    {
+        fields.extend(formatter.json_fields(output));
+    }
+    if ctx.timings()
+        && let Some(duration) = repo_result.duration
+    {
+        record["duration_ms"] = (duration.as_millis() as u64).into();
+    }
+    record
+}
+
+/// Every line a command wrote, stdout before stderr, for `--prefix-lines`
+fn output_lines(output: &Output) -> Vec<String> {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Print result for a single repository
+fn print_result(
+    repo_result: &RepoResult,
+    formatter: &dyn OutputFormatter,
+    ctx: &ExecutionContext,
+    name_width: usize,
+    width: Option<usize>,
+) {
+    let timing = match repo_result.duration {
+        Some(duration) if ctx.timings() => format!(" ({}ms)", duration.as_millis()),
+        _ => String::new(),
+    };
+    if ctx.prefix_lines()
+        && let Outcome::Output(ref output) = repo_result.outcome
+    {
+        let lines = output_lines(output);
+        if !lines.is_empty() {
+            let label = display_label(repo_result, ctx, Some(name_width));
+            for line in lines {
+                println!("{} {}", label, ctx.highlighted(line));
+            }
+            return;
+        }
+    }
+    let mut summary = summary_text(repo_result, formatter, width);
+    // Formatters adapt to the width where they can; this guarantees the line fits
+    if let Some(width) = width {
+        summary = truncate_to_width(&summary, width.saturating_sub(timing.len()));
+    }
+    let mut summary = ctx.highlighted(summary);
+    if ctx.color_status {
+        summary = paint_category(&summary, repo_result.category(formatter));
+    }
+    println!(
+        "{} {}{}",
+        display_label(repo_result, ctx, Some(name_width)),
+        summary,
+        timing
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_repo_name_short() {
+        let result = format_repo_name("my-repo", 24);
+        assert_eq!(result, "[my-repo                 ]");
+        assert_eq!(result.len(), 26); // [ + 24 + ]
+    }
+
+    #[test]
+    fn test_format_repo_name_exact_length() {
+        let result = format_repo_name("exactly-twenty-four-chr", 24);
+        assert_eq!(result.len(), 26);
     }
 
     #[test]
@@ -309,22 +2044,829 @@ mod tests {
         assert_eq!(result.len(), 26);
     }
 
+    #[test]
+    fn test_format_repo_name_truncates_multibyte_on_char_boundaries() {
+        // Byte 20 falls inside "ï"; slicing by bytes would panic here
+        let result = format_repo_name("🚀-café-über-naïve-project-здесь-long", 24);
+        assert_eq!(result, "[🚀-café-über-naïve-pr-...]");
+        assert_eq!(result.chars().count(), 26);
+
+        assert_eq!(format_repo_name("naïve", 24).chars().count(), 26);
+    }
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("3 files changed", 20), "3 files changed");
+        assert_eq!(truncate_to_width("3 files changed", 15), "3 files changed");
+        assert_eq!(truncate_to_width("3 files changed", 8), "3 files…");
+        assert_eq!(truncate_to_width("abc", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_to_width_respects_char_boundaries() {
+        // Multi-byte characters count as one column each and are never split
+        assert_eq!(truncate_to_width("héllo wörld", 6), "héllo…");
+        assert_eq!(truncate_to_width("日本語のコミット", 4), "日本語…");
+    }
+
+    #[test]
+    fn test_max_line_width_leaves_room_for_repo_prefix() {
+        let ctx = ExecutionContext::new(false, None, 8, PathBuf::from("/workspace"))
+            .with_max_line_width(Some(40));
+        // "[name      ] " takes name_width + 3 columns
+        assert_eq!(summary_width(10, line_width(&ctx)), Some(27));
+
+        let unlimited = ExecutionContext::new(false, None, 8, PathBuf::from("/workspace"))
+            .with_max_line_width(Some(0));
+        assert_eq!(summary_width(10, line_width(&unlimited)), None);
+    }
+
     #[test]
     fn test_compute_name_width_caps_and_min() {
         let root = PathBuf::from("/workspace");
         let repos = vec![
-            root.join("a"),
-            root.join("short"),
-            root.join("this-is-a-very-long-repository-name-that-exceeds-cap"),
+            Repo::new(root.join("a")),
+            Repo::new(root.join("short")),
+            Repo::new(root.join("this-is-a-very-long-repository-name-that-exceeds-cap")),
         ];
-        let width = compute_name_width(&repos, &root);
+        let ctx = ExecutionContext::new(false, None, 8, root.clone());
+        let width = compute_name_width(&repos, &ctx);
         assert_eq!(width, MAX_REPO_NAME_WIDTH_CAP);
 
-        let tiny = vec![root.join("a")];
-        let tiny_width = compute_name_width(&tiny, &root);
+        let tiny = vec![Repo::new(root.join("a"))];
+        let tiny_width = compute_name_width(&tiny, &ctx);
         assert_eq!(tiny_width, MIN_REPO_NAME_WIDTH);
     }
 
+    #[cfg(unix)]
+    fn output_with_raw_status(raw: i32, stderr: &str) -> Output {
+        use std::os::unix::process::ExitStatusExt;
+        Output {
+            status: std::process::ExitStatus::from_raw(raw),
+            stdout: Vec::new(),
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exit_code_unix() {
+        // Raw wait statuses: exit code in the high byte, signal in the low bits
+        assert_eq!(exit_code(&output_with_raw_status(0, "")), Some(0));
+        assert_eq!(exit_code(&output_with_raw_status(256, "")), Some(1));
+        assert_eq!(exit_code(&output_with_raw_status(128 << 8, "")), Some(128));
+        assert_eq!(exit_code(&output_with_raw_status(9, "")), Some(137));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_error_line() {
+        let with_stderr = output_with_raw_status(128 << 8, "\nfatal: not a git repository\n");
+        assert_eq!(error_line(&with_stderr), "fatal: not a git repository");
+
+        let silent = output_with_raw_status(256, "");
+        assert_eq!(error_line(&silent), "exited with code 1");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_merge_stderr_folds_success_messages() {
+        let mut output = output_with_raw_status(
+            0,
+            "To github.com:user/repo.git\n   abc..def  main -> main\n",
+        );
+        output.stdout = b"Everything up-to-date".to_vec();
+        let merged = merge_stderr(output);
+        assert_eq!(
+            String::from_utf8_lossy(&merged.stdout),
+            "Everything up-to-date\nTo github.com:user/repo.git\n   abc..def  main -> main\n"
+        );
+        assert!(merged.stderr.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_merge_stderr_leaves_failures_alone() {
+        let merged = merge_stderr(output_with_raw_status(256, "error: failed to push\n"));
+        assert!(merged.stdout.is_empty());
+        assert_eq!(error_line(&merged), "error: failed to push");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_first_output_line_falls_back_to_stderr() {
+        let output = output_with_raw_status(0, "\nSwitched to branch 'main'\n");
+        assert_eq!(
+            first_output_line(&output).as_deref(),
+            Some("Switched to branch 'main'")
+        );
+        assert_eq!(first_output_line(&output_with_raw_status(0, "")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_combine_outputs_concatenates_in_order() {
+        let mut first = output_with_raw_status(0, "from origin\n");
+        first.stdout = b"a\n".to_vec();
+        let mut second = output_with_raw_status(0, "from upstream\n");
+        second.stdout = b"b\n".to_vec();
+
+        let combined = combine_outputs(vec![Ok(first), Ok(second)]).unwrap();
+        assert!(combined.status.success());
+        assert_eq!(combined.stdout, b"a\nb\n");
+        assert_eq!(combined.stderr, b"from origin\nfrom upstream\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_combine_outputs_fails_with_first_failure() {
+        let ok = output_with_raw_status(0, "From origin\n");
+        let failed = output_with_raw_status(128 << 8, "fatal: unreachable\n");
+
+        let combined = combine_outputs(vec![Ok(ok), Ok(failed)]).unwrap();
+        assert_eq!(exit_code(&combined), Some(128));
+        assert_eq!(error_line(&combined), "fatal: unreachable");
+    }
+
+    #[test]
+    fn test_exit_code_from_real_process() {
+        let output = Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet", "no-such-ref-anywhere"])
+            .output()
+            .expect("run git");
+        assert_eq!(exit_code(&output), Some(1));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("--oneline"), "--oneline");
+        assert_eq!(shell_quote("two words"), "'two words'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn test_command_name_skips_config_pairs() {
+        let args = ["-c", "fetch.writeCommitGraph=true", "fetch", "--prune"];
+        let cmd = GitCommand::new(PathBuf::from("/repos/app"), args.map(String::from).to_vec());
+        assert_eq!(cmd.name(), "fetch");
+
+        let program =
+            GitCommand::program(PathBuf::from("/repos/app"), "make".to_string(), Vec::new());
+        assert_eq!(program.name(), "make");
+    }
+
+    #[test]
+    fn test_planned_command_replaces_the_check_in_dry_runs() {
+        let check = ["rev-list", "--count", "@{upstream}..HEAD"]
+            .map(String::from)
+            .to_vec();
+        let cmd = GitCommand::new(PathBuf::from("/repos/app"), check.clone())
+            .with_planned(vec!["push".to_string()]);
+        assert_eq!(cmd.name(), "push");
+        let planned = cmd.planned_invocations();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].args, vec!["push"]);
+        assert_eq!(cmd.invocations()[0].args, check);
+    }
+
+    #[test]
+    fn test_command_string_quotes_args() {
+        let cmd = GitCommand::new(
+            PathBuf::from("/repos/app"),
+            vec![
+                "commit".to_string(),
+                "-m".to_string(),
+                "fix \"quoted\" bug".to_string(),
+            ],
+        );
+        assert_eq!(
+            cmd.command_string_with_scheme(None),
+            "git -C /repos/app commit -m 'fix \"quoted\" bug'"
+        );
+    }
+
+    #[test]
+    fn test_isolated_git_children_skip_user_and_system_config() {
+        let ctx = |isolated| {
+            ExecutionContext::new(false, None, 1, PathBuf::from("/repos"))
+                .with_isolated(isolated)
+                .with_ssh_command(Some("ssh -v".to_string()))
+        };
+        let env = |ctx: ExecutionContext| -> Vec<(String, Option<String>)> {
+            ctx.command_settings()
+                .command(Path::new("/repos/app"), vec!["status".to_string()])
+                .git_command(None)
+                .get_envs()
+                .map(|(k, v)| {
+                    (
+                        k.to_string_lossy().into_owned(),
+                        v.map(|v| v.to_string_lossy().into_owned()),
+                    )
+                })
+                .collect()
+        };
+        let isolated = env(ctx(true));
+        for (key, value) in ISOLATION_ENV {
+            assert!(
+                isolated.contains(&(key.to_string(), Some(value.to_string()))),
+                "{key}"
+            );
+        }
+        assert!(isolated.contains(&("GIT_SSH_COMMAND".to_string(), Some("ssh -v".to_string()))));
+
+        let inherited = env(ctx(false));
+        assert!(
+            !inherited
+                .iter()
+                .any(|(key, _)| key == "HOME" || key == "GIT_CONFIG_NOSYSTEM")
+        );
+
+        // exec and bisect-run commands keep the user's environment
+        let program = GitCommand::program(PathBuf::from("/repos/app"), "make".to_string(), vec![]);
+        assert!(
+            ctx(true)
+                .prepare(program)
+                .env
+                .iter()
+                .all(|(key, _)| key == "GIT_SSH_COMMAND")
+        );
+    }
+
+    #[test]
+    fn test_program_command_string() {
+        let cmd = GitCommand::program(
+            PathBuf::from("/repos/my app"),
+            "./test.sh".to_string(),
+            vec!["--fast".to_string()],
+        );
+        assert_eq!(
+            cmd.command_string_with_scheme(Some(&UrlRewrite::new(UrlScheme::Ssh, &[]))),
+            "(cd '/repos/my app' && ./test.sh --fast)"
+        );
+    }
+
+    #[test]
+    fn test_shell_quote_special_characters() {
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+        assert_eq!(shell_quote("*.rs"), "'*.rs'");
+        assert_eq!(shell_quote("a;b"), "'a;b'");
+        assert_eq!(shell_quote("`cmd`"), "'`cmd`'");
+        assert_eq!(shell_quote("line\nbreak"), "'line\nbreak'");
+    }
+
+    #[test]
+    fn test_insteadof_config_for_other_hosts() {
+        assert_eq!(
+            UrlScheme::Ssh.insteadof_config("gitlab.example.com"),
+            "url.git@gitlab.example.com:.insteadOf=https://gitlab.example.com/"
+        );
+        assert_eq!(
+            UrlScheme::Https.insteadof_config("gitlab.example.com"),
+            "url.https://gitlab.example.com/.insteadOf=git@gitlab.example.com:"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_name_order_ignores_display_renames() {
+        let root = PathBuf::from("/workspace");
+        let repos: Vec<Repo> = ["api", "web", "docs"]
+            .iter()
+            .map(|n| Repo::new(root.join(n)))
+            .collect();
+        let labels = RepoLabels::parse("api=Zeta API\nweb=Alpha Web\n").unwrap();
+        let ctx = ExecutionContext::new(false, None, 1, root.clone())
+            .with_labels(Some(labels))
+            .with_name_replace(Some(NameReplace::parse("^docs$=zz-docs").unwrap()));
+        assert_eq!(print_order(&repos, &ctx), vec![0, 2, 1]);
+
+        let ctx = ctx.with_sort(OutputSort::Path);
+        assert_eq!(print_order(&repos, &ctx), vec![0, 2, 1]);
+
+        struct Done;
+        impl OutputFormatter for Done {
+            fn format(&self, _output: &Output) -> String {
+                "done".to_string()
+            }
+        }
+        let results: Vec<RepoResult> = ["api", "web", "docs"]
+            .iter()
+            .map(|n| finished_result(n, 0))
+            .collect();
+        let ctx = ctx.with_sort(OutputSort::Status);
+        let order: Vec<&Path> = status_order(&results, &ctx, &Done)
+            .iter()
+            .map(|r| r.repo.as_path())
+            .collect();
+        assert_eq!(
+            order,
+            vec![root.join("api"), root.join("docs"), root.join("web")]
+        );
+    }
+
+    #[test]
+    fn test_url_rewrite_covers_each_host() {
+        let cmd = GitCommand::new(PathBuf::from("/repos/app"), vec!["fetch".to_string()]);
+        let ctx = ExecutionContext::new(false, Some(UrlScheme::Https), 1, PathBuf::from("/repos"))
+            .with_hosts(vec![
+                "gitlab.example.com".to_string(),
+                "github.com".to_string(),
+            ]);
+        assert_eq!(
+            cmd.command_string_with_scheme(ctx.url_rewrite().as_ref()),
+            "git -c url.https://gitlab.example.com/.insteadOf=git@gitlab.example.com: \
+             -c url.https://github.com/.insteadOf=git@github.com: -C /repos/app fetch"
+        );
+
+        let plain = ExecutionContext::new(false, None, 1, PathBuf::from("/repos"));
+        assert!(
+            plain
+                .with_hosts(vec!["github.com".to_string()])
+                .url_rewrite()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_command_string_quotes_repo_path_and_scheme() {
+        let cmd = GitCommand::new(
+            PathBuf::from("/my repos/it's here"),
+            vec!["fetch".to_string()],
+        );
+        assert_eq!(
+            cmd.command_string_with_scheme(Some(&UrlRewrite::new(UrlScheme::Ssh, &[]))),
+            "git -c url.git@github.com:.insteadOf=https://github.com/ -C '/my repos/it'\\''s here' fetch"
+        );
+    }
+
+    #[test]
+    fn test_name_replace_strips_prefix() {
+        let replace = NameReplace::parse("^company-service-=").unwrap();
+        assert_eq!(replace.apply("company-service-foo"), "foo");
+    }
+
+    #[test]
+    fn test_name_replace_capture_groups() {
+        let replace = NameReplace::parse("^(\\w+)-api$=$1 (api)").unwrap();
+        assert_eq!(replace.apply("billing-api"), "billing (api)");
+    }
+
+    #[test]
+    fn test_name_replace_no_match_passthrough() {
+        let replace = NameReplace::parse("^company-=").unwrap();
+        assert_eq!(replace.apply("other-repo"), "other-repo");
+    }
+
+    #[test]
+    fn test_name_replace_rejects_bad_input() {
+        assert!(NameReplace::parse("no-equals-sign").is_err());
+        assert!(NameReplace::parse("(unclosed=x").is_err());
+    }
+
+    #[test]
+    fn test_color_legend() {
+        let root = PathBuf::from("/nonexistent/src");
+        // Neither has an origin, so both share the "local" color and it is listed once
+        let repos = vec![Repo::new(root.join("api")), Repo::new(root.join("web"))];
+        assert_eq!(
+            color_legend(&repos, ColorBy::Host, &root),
+            format!(
+                "Legend: repo names are colored by origin host: {}",
+                paint_by_key("local", "local")
+            )
+        );
+        assert_eq!(
+            color_legend(&repos, ColorBy::Repo, &root),
+            "Legend: each repo name has its own color, the same on every run"
+        );
+    }
+
+    #[test]
+    fn test_highlight_wraps_matches_in_color() {
+        let highlight = Highlight::parse("CONFLICT").unwrap();
+        assert_eq!(
+            highlight.apply("CONFLICT (stopped) - resolve"),
+            "\x1b[1;4mCONFLICT\x1b[22;24m (stopped) - resolve"
+        );
+    }
+
+    #[test]
+    fn test_highlight_markers_without_color() {
+        let highlight = Highlight::parse("main|dev").unwrap().with_color(false);
+        assert_eq!(highlight.apply("main -> dev"), ">>main<< -> >>dev<<");
+        assert_eq!(highlight.apply("up to date"), "up to date");
+    }
+
+    #[test]
+    fn test_highlight_ignores_empty_matches() {
+        let highlight = Highlight::parse("x*").unwrap().with_color(false);
+        assert_eq!(highlight.apply("abc"), "abc");
+        assert_eq!(highlight.apply("axxb"), "a>>xx<<b");
+    }
+
+    #[test]
+    fn test_highlight_rejects_bad_regex() {
+        assert!(Highlight::parse("(unclosed").is_err());
+    }
+
+    #[cfg(unix)]
+    fn finished_result(repo: &str, duration_ms: u64) -> RepoResult {
+        RepoResult {
+            repo: PathBuf::from("/workspace").join(repo),
+            outcome: Outcome::Output(output_with_raw_status(0, "")),
+            color_key: None,
+            duration: Some(Duration::from_millis(duration_ms)),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_json_record_fields() {
+        struct Summary;
+        impl OutputFormatter for Summary {
+            fn format(&self, _output: &Output) -> String {
+                "up to date".to_string()
+            }
+        }
+
+        let ctx =
+            ExecutionContext::new(false, None, 8, PathBuf::from("/workspace")).with_json(true);
+        let record = json_record(&finished_result("api", 42), &Summary, &ctx);
+        assert_eq!(
+            record.to_string(),
+            r#"{"repo":"api","path":"/workspace/api","category":"ok","summary":"up to date"}"#
+        );
+
+        let ctx = ctx.with_timings(true);
+        let record = json_record(&finished_result("api", 42), &Summary, &ctx);
+        assert_eq!(record["duration_ms"], 42);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lock_group_serializes_commands() {
+        struct Exit;
+        impl OutputFormatter for Exit {
+            fn format(&self, output: &Output) -> String {
+                error_line(output)
+            }
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let repos: Vec<Repo> = ["a", "b", "c"]
+            .iter()
+            .map(|n| Repo::new(temp.path().join(n)))
+            .collect();
+        for repo in &repos {
+            std::fs::create_dir(repo.path()).unwrap();
+        }
+        // mkdir fails if another command in the group is inside its critical section
+        let critical = format!(
+            "mkdir {0} && sleep 0.1 && rmdir {0}",
+            temp.path().join("busy").display()
+        );
+
+        let ctx = ExecutionContext::new(false, None, 8, temp.path().to_path_buf());
+        run_parallel(
+            &ctx,
+            &repos,
+            |repo| {
+                GitCommand::program(
+                    repo.path().to_path_buf(),
+                    "sh".to_string(),
+                    vec!["-c".to_string(), critical.clone()],
+                )
+                .with_lock_group(temp.path().to_path_buf())
+            },
+            &Exit,
+        )
+        .unwrap();
+        assert_eq!(ctx.repos_in(Category::Ok).len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_followup_counts_against_worker_limit() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        struct Exit;
+        impl OutputFormatter for Exit {
+            fn format(&self, output: &Output) -> String {
+                error_line(output)
+            }
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let repos: Vec<Repo> = ["a", "b", "c"]
+            .iter()
+            .map(|n| Repo::new(temp.path().join(n)))
+            .collect();
+        for repo in &repos {
+            std::fs::create_dir(repo.path()).unwrap();
+        }
+        let running = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+
+        let ctx = ExecutionContext::new(false, None, 1, temp.path().to_path_buf());
+        run_parallel(
+            &ctx,
+            &repos,
+            |repo| {
+                let (running, most) = (running.clone(), most.clone());
+                GitCommand::program(repo.path().to_path_buf(), "true".to_string(), Vec::new())
+                    .with_followup(Box::new(move |output| {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        most.fetch_max(now, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(50));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        Ok(output)
+                    }))
+            },
+            &Exit,
+        )
+        .unwrap();
+        assert_eq!(ctx.repos_in(Category::Ok).len(), 3);
+        assert_eq!(most.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_applies_to_followup_commands() {
+        struct Exit;
+        impl OutputFormatter for Exit {
+            fn format(&self, output: &Output) -> String {
+                error_line(output)
+            }
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let repo = Repo::new(temp.path().to_path_buf());
+        let ctx = ExecutionContext::new(false, None, 1, temp.path().to_path_buf())
+            .with_timeout(Some(Duration::from_millis(200)));
+        let settings = ctx.command_settings();
+        let started = Instant::now();
+        run_parallel(
+            &ctx,
+            std::slice::from_ref(&repo),
+            |repo| {
+                let (path, settings) = (repo.path().to_path_buf(), settings.clone());
+                GitCommand::program(path.clone(), "true".to_string(), Vec::new()).with_followup(
+                    Box::new(move |_| {
+                        let hang = ["-c", "alias.hang=!sleep 10", "hang"];
+                        settings
+                            .command(&path, hang.map(str::to_string).to_vec())
+                            .run(None)
+                    }),
+                )
+            },
+            &Exit,
+        )
+        .unwrap();
+        assert_eq!(
+            ctx.repos_in(Category::Error),
+            vec![repo.path().to_path_buf()]
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_followup_commands_get_ssh_command() {
+        let ctx = ExecutionContext::new(false, None, 1, PathBuf::from("/repos"))
+            .with_ssh_command(Some("ssh -o ControlMaster=auto".to_string()));
+        let cmd = ctx
+            .command_settings()
+            .command(Path::new("/repos/app"), vec!["pull".to_string()]);
+        let ssh = (
+            "GIT_SSH_COMMAND".to_string(),
+            "ssh -o ControlMaster=auto".to_string(),
+        );
+        assert_eq!(cmd.env, vec![ssh]);
+
+        let plain = ExecutionContext::new(false, None, 1, PathBuf::from("/repos"));
+        let cmd = plain
+            .command_settings()
+            .command(Path::new("/repos/app"), Vec::new());
+        assert!(cmd.env.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_repo_removed_after_discovery_is_unavailable() {
+        struct Exit;
+        impl OutputFormatter for Exit {
+            fn format(&self, output: &Output) -> String {
+                error_line(output)
+            }
+        }
+
+        let temp = tempfile::tempdir().unwrap();
+        let repos: Vec<Repo> = ["a", "b"]
+            .iter()
+            .map(|n| Repo::new(temp.path().join(n)))
+            .collect();
+        for repo in &repos {
+            std::fs::create_dir(repo.path()).unwrap();
+        }
+        let gone = temp.path().join("b");
+
+        let ctx = ExecutionContext::new(false, None, 8, temp.path().to_path_buf());
+        run_parallel(
+            &ctx,
+            &repos,
+            |repo| {
+                // As if b's volume were unmounted once the run had started
+                if repo.path() == gone {
+                    std::fs::remove_dir(&gone).unwrap();
+                }
+                GitCommand::program(repo.path().to_path_buf(), "true".to_string(), Vec::new())
+            },
+            &Exit,
+        )
+        .unwrap();
+        assert_eq!(ctx.repos_in(Category::Ok), vec![temp.path().join("a")]);
+        assert_eq!(ctx.repos_in(Category::Error), vec![gone.clone()]);
+
+        let err = GitCommand::new(gone.clone(), vec!["status".to_string()])
+            .run(None)
+            .unwrap_err();
+        let result = RepoResult {
+            repo: gone,
+            outcome: Outcome::SpawnError(err),
+            color_key: None,
+            duration: None,
+        };
+        assert_eq!(
+            summary_text(&result, &Exit, None),
+            "UNAVAILABLE (path missing)"
+        );
+
+        std::fs::write(temp.path().join("c"), "").unwrap();
+        let err = unavailable_error(&temp.path().join("c")).unwrap();
+        assert_eq!(err.to_string(), "UNAVAILABLE (not a directory)");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_kills_overrunning_process() {
+        let sh = |script: &str| {
+            GitCommand::program(
+                PathBuf::from("."),
+                "sh".to_string(),
+                vec!["-c".to_string(), script.to_string()],
+            )
+        };
+
+        let mut quick = sh("echo out; echo err >&2");
+        quick.timeout = Some(Duration::from_secs(5));
+        let output = quick.run(None).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+
+        let mut hung = sh("sleep 10");
+        hung.timeout = Some(Duration::from_millis(200));
+        let started = Instant::now();
+        let err = hung.run(None).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        assert_eq!(err.to_string(), "TIMEOUT after 0.2s");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_repo_labels_parse() {
+        let labels =
+            RepoLabels::parse("# dashboards\n\napi-svc-7 = Payments API\nweb=Storefront\n")
+                .unwrap();
+        assert_eq!(
+            labels.label("api-svc-7", Path::new("/w/api-svc-7")),
+            Some("Payments API")
+        );
+        assert_eq!(labels.label("web", Path::new("/w/web")), Some("Storefront"));
+        assert!(
+            RepoLabels::parse("no separator")
+                .unwrap_err()
+                .starts_with("line 1:")
+        );
+        assert!(RepoLabels::parse("=Nameless").is_err());
+    }
+
+    #[test]
+    fn test_display_name_uses_labels_with_fallback() {
+        let root = PathBuf::from("/workspace");
+        let labels = RepoLabels::parse("api-svc-7=Payments API\nteam/web=Storefront\n").unwrap();
+        let ctx = ExecutionContext::new(false, None, 8, root.clone())
+            .with_labels(Some(labels))
+            .with_name_replace(Some(NameReplace::parse("^legacy-=").unwrap()));
+        assert_eq!(ctx.display_name(&root.join("api-svc-7")), "Payments API");
+        assert_eq!(ctx.display_name(&root.join("team/web")), "Storefront");
+        assert_eq!(ctx.display_name(&root.join("legacy-billing")), "billing");
+    }
+
+    #[test]
+    fn test_display_name_uses_name_replace() {
+        let root = PathBuf::from("/workspace");
+        let ctx = ExecutionContext::new(false, None, 8, root.clone())
+            .with_name_replace(Some(NameReplace::parse("^company-service-=").unwrap()));
+        assert_eq!(ctx.display_name(&root.join("company-service-foo")), "foo");
+    }
+
+    fn grouped_names(sort: SummarySort) -> Vec<(String, Vec<&'static str>)> {
+        let entries = vec![
+            ("api", "clean".to_string()),
+            ("db", "2 modified".to_string()),
+            ("infra", "clean".to_string()),
+            ("web", "1 untracked".to_string()),
+            ("worker", "clean".to_string()),
+            ("zeta", "2 modified".to_string()),
+        ];
+        group_by_summary(entries, sort)
+    }
+
+    #[test]
+    fn test_group_by_summary_count_order() {
+        assert_eq!(
+            grouped_names(SummarySort::Count),
+            vec![
+                ("clean".to_string(), vec!["api", "infra", "worker"]),
+                ("2 modified".to_string(), vec!["db", "zeta"]),
+                ("1 untracked".to_string(), vec!["web"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_summary_name_order() {
+        let summaries: Vec<String> = grouped_names(SummarySort::Name)
+            .into_iter()
+            .map(|(summary, _)| summary)
+            .collect();
+        assert_eq!(summaries, vec!["1 untracked", "2 modified", "clean"]);
+    }
+
+    #[test]
+    fn test_fifo_semaphore_grants_in_arrival_order() {
+        let sem = Semaphore::fifo(1);
+        let order = Mutex::new(Vec::new());
+        sem.acquire();
+
+        std::thread::scope(|s| {
+            for i in 0..5 {
+                let (sem, order) = (&sem, &order);
+                s.spawn(move || {
+                    sem.acquire();
+                    order.lock().unwrap().push(i);
+                    sem.release();
+                });
+                // Each thread has queued once it has taken a ticket (ours is 0)
+                while sem.state.lock().unwrap().next_ticket < i + 2 {
+                    std::thread::yield_now();
+                }
+            }
+            sem.release();
+        });
+
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_blocked_status() {
+        assert_eq!(blocked_status("aaa", 3), "waiting on aaa: 3 finished");
+    }
+
+    #[test]
+    fn test_summary_footer_lists_only_seen_categories() {
+        let mut categories = vec![Category::Ok; 45];
+        categories.extend([Category::Error, Category::Error]);
+        assert_eq!(
+            format_summary_footer(&categories),
+            "47 repos: 45 ok, 2 failed"
+        );
+        assert_eq!(
+            format_summary_footer(&[Category::Dirty, Category::Clean, Category::Clean]),
+            "3 repos: 2 clean, 1 dirty"
+        );
+        assert_eq!(
+            format_summary_footer(&[Category::Updated]),
+            "1 repo: 1 updated"
+        );
+    }
+
+    #[test]
+    fn test_format_tally_mixed_results() {
+        let categories = [
+            Category::Clean,
+            Category::Clean,
+            Category::Dirty,
+            Category::Error,
+            Category::Clean,
+        ];
+        assert_eq!(
+            format_tally(&categories),
+            "clean=3 dirty=1 updated=0 ok=0 skipped=0 error=1"
+        );
+    }
+
     /// Test that large output (>64KB) doesn't cause pipe buffer deadlock.
     /// wait_with_output() internally spawns threads to drain pipes, so this should complete.
     #[test]
@@ -0,0 +1,305 @@
+use anyhow::{Result, bail};
+use std::cell::Cell;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::cache;
+use crate::repo::{Repo, ScanDepth, ScanEvent, find_git_repos_with, is_bare_repo};
+
+/// Git config key listing repositories, one value per repo:
+///
+/// ```text
+/// [git-all]
+///     repo = ~/src/api
+///     repo = ~/src/web
+/// ```
+pub const REPO_CONFIG_KEY: &str = "git-all.repo";
+
+/// Where the set of repositories to run against comes from.
+/// Implementations return repos sorted by path, since output follows that order.
+pub trait RepoSource {
+    fn repos(&self) -> Result<Vec<Repo>>;
+
+    /// What the last `repos()` call saw while scanning directories; None when
+    /// the repos came from a list (or the repo cache) rather than a scan
+    fn discovery(&self) -> Option<Discovery> {
+        None
+    }
+}
+
+/// What a directory scan visited, reported by `--stats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Discovery {
+    /// Directories visited, repositories included
+    pub dirs_scanned: usize,
+    /// Bare repositories passed over because `include_bare` is off
+    pub bare_skipped: usize,
+}
+
+/// Repositories found by scanning a directory, optionally through the repo cache
+pub struct ScanSource {
+    pub root: PathBuf,
+    pub scan_depth: ScanDepth,
+    /// Collect bare repositories as well as working trees
+    pub include_bare: bool,
+    /// Cache directory and whether to verify the cache, when caching is on
+    pub cache: Option<(PathBuf, bool)>,
+    /// Filled in by `repos()` when it scans
+    pub discovery: Cell<Option<Discovery>>,
+}
+
+impl ScanSource {
+    fn scan(&self) -> Result<Vec<PathBuf>> {
+        let mut discovery = Discovery::default();
+        let mut on_visit = |path: &Path, _, event| {
+            discovery.dirs_scanned += 1;
+            if event != ScanEvent::Repo && !self.include_bare && is_bare_repo(path) {
+                discovery.bare_skipped += 1;
+            }
+        };
+        let paths = find_git_repos_with(
+            &self.root,
+            self.scan_depth,
+            self.include_bare,
+            &mut on_visit,
+        )?;
+        self.discovery.set(Some(discovery));
+        Ok(paths)
+    }
+}
+
+impl RepoSource for ScanSource {
+    fn repos(&self) -> Result<Vec<Repo>> {
+        let paths = match self.cache {
+            Some((ref cache_dir, verify)) => cache::find_git_repos_cached(
+                cache_dir,
+                &self.root,
+                self.scan_depth,
+                self.include_bare,
+                verify,
+            )?,
+            None => self.scan()?,
+        };
+        Ok(paths.into_iter().map(Repo::new).collect())
+    }
+
+    fn discovery(&self) -> Option<Discovery> {
+        self.discovery.get()
+    }
+}
+
+/// Repositories listed in git config under `git-all.repo` (multi-valued).
+/// Relative paths are resolved against `base`; `~/` is expanded by git.
+pub struct GitConfigSource {
+    pub base: PathBuf,
+    /// Read only this file instead of the usual global/system/local config
+    pub config_file: Option<PathBuf>,
+}
+
+impl GitConfigSource {
+    fn configured_paths(&self) -> Result<Vec<String>> {
+        let mut cmd = Command::new("git");
+        cmd.arg("config");
+        if let Some(ref file) = self.config_file {
+            cmd.arg("--file").arg(file);
+        }
+        let output = cmd
+            .args(["--type=path", "--get-all", REPO_CONFIG_KEY])
+            .current_dir(&self.base)
+            .stdin(Stdio::null())
+            .output()?;
+
+        // Exit code 1 means the key is not set
+        match output.status.code() {
+            Some(0) => Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .map(str::to_string)
+                .collect()),
+            Some(1) => Ok(Vec::new()),
+            _ => bail!(
+                "git config --get-all {} failed: {}",
+                REPO_CONFIG_KEY,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+    }
+}
+
+impl RepoSource for GitConfigSource {
+    fn repos(&self) -> Result<Vec<Repo>> {
+        Ok(existing_repos(
+            &self.base,
+            self.configured_paths()?,
+            REPO_CONFIG_KEY,
+        ))
+    }
+}
+
+/// Repositories listed on standard input, one path per line, or NUL-separated
+/// (as from `find -print0`) so paths may contain newlines.
+/// Relative paths are resolved against `base`.
+pub struct StdinSource {
+    pub base: PathBuf,
+    pub null_delimited: bool,
+}
+
+impl RepoSource for StdinSource {
+    fn repos(&self) -> Result<Vec<Repo>> {
+        let mut input = Vec::new();
+        std::io::stdin().read_to_end(&mut input)?;
+        let paths = split_paths(&input, self.null_delimited);
+        Ok(existing_repos(&self.base, paths, "stdin path"))
+    }
+}
+
+/// Split stdin into paths. Newline-delimited input tolerates CRLF and blank
+/// lines; NUL-delimited input is taken as-is apart from empty entries.
+fn split_paths(input: &[u8], null_delimited: bool) -> Vec<String> {
+    let text = String::from_utf8_lossy(input);
+    if null_delimited {
+        text.split('\0')
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect()
+    } else {
+        text.lines()
+            .map(str::trim_end)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// Resolve `paths` against `base` and keep those that are git repositories,
+/// sorted and deduplicated. Others are reported on stderr as `origin` entries.
+fn existing_repos(base: &Path, paths: Vec<String>, origin: &str) -> Vec<Repo> {
+    let mut repos = Vec::new();
+    for configured in paths {
+        let path = resolve(base, &configured);
+        if path.join(".git").exists() {
+            repos.push(path);
+        } else {
+            eprintln!(
+                "git-all: {} {} is not a git repository, skipped",
+                origin, configured
+            );
+        }
+    }
+    repos.sort();
+    repos.dedup();
+    repos.into_iter().map(Repo::new).collect()
+}
+
+fn resolve(base: &Path, configured: &str) -> PathBuf {
+    let path = Path::new(configured);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        // Collecting components drops `.` segments, so `./api` and `api` dedup
+        base.join(path).components().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn init_repo(path: &Path) {
+        fs::create_dir_all(path).unwrap();
+        let status = Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_git_config_source_reads_multi_valued_key() {
+        let temp = tempfile::tempdir().unwrap();
+        init_repo(&temp.path().join("web"));
+        init_repo(&temp.path().join("api"));
+        fs::create_dir(temp.path().join("not-a-repo")).unwrap();
+
+        let config = temp.path().join("repos.gitconfig");
+        fs::write(
+            &config,
+            format!(
+                "[git-all]\n\trepo = {}\n\trepo = api\n\trepo = not-a-repo\n\trepo = web\n",
+                temp.path().join("web").display()
+            ),
+        )
+        .unwrap();
+
+        let source = GitConfigSource {
+            base: temp.path().to_path_buf(),
+            config_file: Some(config),
+        };
+        let paths: Vec<PathBuf> = source
+            .repos()
+            .unwrap()
+            .iter()
+            .map(|repo| repo.path().to_path_buf())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![temp.path().join("api"), temp.path().join("web")]
+        );
+    }
+
+    #[test]
+    fn test_scan_source_records_discovery() {
+        let temp = tempfile::tempdir().unwrap();
+        init_repo(&temp.path().join("api"));
+        fs::create_dir_all(temp.path().join("docs")).unwrap();
+        let mirror = temp.path().join("mirror.git");
+        fs::create_dir_all(mirror.join("objects")).unwrap();
+        fs::create_dir_all(mirror.join("refs")).unwrap();
+        fs::write(mirror.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+
+        let source = ScanSource {
+            root: temp.path().to_path_buf(),
+            scan_depth: ScanDepth::Depth(1),
+            include_bare: false,
+            cache: None,
+            discovery: Cell::default(),
+        };
+        assert_eq!(source.discovery(), None);
+        assert_eq!(source.repos().unwrap().len(), 1);
+        assert_eq!(
+            source.discovery(),
+            Some(Discovery {
+                dirs_scanned: 3,
+                bare_skipped: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_split_paths_newline_and_null() {
+        assert_eq!(
+            split_paths(b"api\r\n\nweb app\n", false),
+            vec!["api", "web app"]
+        );
+        assert_eq!(
+            split_paths(b"./api\0./web app\0./odd\nname\0", true),
+            vec!["./api", "./web app", "./odd\nname"]
+        );
+    }
+
+    #[test]
+    fn test_git_config_source_unset_key_is_empty() {
+        let temp = tempfile::tempdir().unwrap();
+        let config = temp.path().join("empty.gitconfig");
+        fs::write(&config, "[user]\n\tname = someone\n").unwrap();
+
+        let source = GitConfigSource {
+            base: temp.path().to_path_buf(),
+            config_file: Some(config),
+        };
+        assert!(source.repos().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Shared OpenSSH connections for one git-all run (`--ssh-multiplex`).
+///
+/// Every git child gets a `GIT_SSH_COMMAND` with `ControlMaster=auto`, so the
+/// first connection to a host becomes the master and later ones reuse its
+/// socket. Masters linger for `CONTROL_PERSIST` so repos fetched later in the
+/// run still find them; dropping this stops them and removes the socket dir.
+pub struct SshMultiplex {
+    dir: PathBuf,
+}
+
+/// Seconds an idle master connection stays open
+const CONTROL_PERSIST: u32 = 60;
+
+impl SshMultiplex {
+    /// Create a private socket directory for this run. It lives directly under
+    /// /tmp because unix socket paths are limited to ~104 bytes, which the
+    /// per-user temp dir on macOS would nearly use up on its own. The name is
+    /// predictable, so an existing path (someone else's dir or symlink) is an
+    /// error rather than something to reuse.
+    pub fn new() -> Result<Self> {
+        let dir = Path::new("/tmp").join(format!("git-all-ssh-{}", std::process::id()));
+        let mut builder = fs::DirBuilder::new();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder
+            .create(&dir)
+            .with_context(|| format!("failed to create ssh socket dir {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    /// Value for `GIT_SSH_COMMAND`: `base` (the user's own `GIT_SSH_COMMAND`,
+    /// or plain `ssh`) plus the multiplexing options
+    pub fn ssh_command(&self, base: Option<&str>) -> String {
+        format!(
+            "{} -o ControlMaster=auto -o ControlPath=\"{}/%C\" -o ControlPersist={}",
+            base.unwrap_or("ssh"),
+            self.dir.display(),
+            CONTROL_PERSIST
+        )
+    }
+}
+
+impl Drop for SshMultiplex {
+    fn drop(&mut self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        // `-O exit` only talks to the master behind ControlPath; the host is unused
+        for socket in entries.flatten() {
+            let _ = Command::new("ssh")
+                .arg("-o")
+                .arg(format!("ControlPath={}", socket.path().display()))
+                .args(["-O", "exit", "git-all-multiplex"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        }
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssh_command_extends_base_command() {
+        let multiplex = SshMultiplex {
+            dir: PathBuf::from("/tmp/git-all-ssh-42"),
+        };
+        assert_eq!(
+            multiplex.ssh_command(None),
+            "ssh -o ControlMaster=auto -o ControlPath=\"/tmp/git-all-ssh-42/%C\" -o ControlPersist=60"
+        );
+        assert!(
+            multiplex
+                .ssh_command(Some("ssh -i ~/.ssh/deploy"))
+                .starts_with("ssh -i ~/.ssh/deploy -o ControlMaster=auto")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_dir_is_private_and_never_reused() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let multiplex = SshMultiplex::new().unwrap();
+        let mode = fs::symlink_metadata(&multiplex.dir)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o700);
+        assert!(SshMultiplex::new().is_err());
+
+        let dir = multiplex.dir.clone();
+        drop(multiplex);
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+use crate::runner::Category;
+use crate::source::Discovery;
+
+/// Diagnostics for `--stats`: how discovery and filtering arrived at the
+/// repos that ran, and how they came out. Printed to stderr as one block.
+pub struct RunStats {
+    started: Instant,
+    discovery: Option<Discovery>,
+    repos_found: usize,
+    /// Repos left out before running, by reason, in the order filters apply
+    filtered: Vec<(&'static str, usize)>,
+}
+
+impl RunStats {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            discovery: None,
+            repos_found: 0,
+            filtered: Vec::new(),
+        }
+    }
+
+    /// Record what the repo source returned. Bare repos a scan passed over
+    /// count as found, then filtered out.
+    pub fn discovered(&mut self, discovery: Option<Discovery>, repos: usize) {
+        self.discovery = discovery;
+        self.repos_found = repos;
+        if let Some(discovery) = discovery {
+            self.repos_found += discovery.bare_skipped;
+            self.filter_out("bare", discovery.bare_skipped);
+        }
+    }
+
+    pub fn filter_out(&mut self, reason: &'static str, count: usize) {
+        if count > 0 {
+            self.filtered.push((reason, count));
+        }
+    }
+
+    /// Print the block for a run whose repos finished with `categories`
+    pub fn print(&self, categories: &[Category]) {
+        eprint!("{}", self.render(categories, self.started.elapsed()));
+    }
+
+    fn render(&self, categories: &[Category], elapsed: Duration) -> String {
+        let mut lines = vec!["git-all stats:".to_string()];
+        let mut row = |label: &str, value: String| lines.push(format!("  {:<17}{}", label, value));
+
+        // Repos listed on stdin or in git config weren't found by scanning
+        if let Some(discovery) = self.discovery {
+            row("dirs scanned", discovery.dirs_scanned.to_string());
+        }
+        row("repos found", self.repos_found.to_string());
+        let filtered: usize = self.filtered.iter().map(|(_, count)| count).sum();
+        if filtered == 0 {
+            row("filtered out", "0".to_string());
+        } else {
+            let reasons: Vec<String> = self
+                .filtered
+                .iter()
+                .map(|(reason, count)| format!("{} {}", reason, count))
+                .collect();
+            row(
+                "filtered out",
+                format!("{} ({})", filtered, reasons.join(", ")),
+            );
+        }
+        row("repos processed", categories.len().to_string());
+        let results: Vec<String> = Category::ALL
+            .iter()
+            .filter_map(|cat| {
+                let count = categories.iter().filter(|c| *c == cat).count();
+                (count > 0).then(|| format!("{} {}", cat.key(), count))
+            })
+            .collect();
+        row(
+            "results",
+            if results.is_empty() {
+                "none".to_string()
+            } else {
+                results.join(", ")
+            },
+        );
+        row("total time", format!("{:.2}s", elapsed.as_secs_f64()));
+
+        let mut block = lines.join("\n");
+        block.push('\n');
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_lists_filters_and_results() {
+        let mut stats = RunStats::new(Instant::now());
+        stats.discovered(
+            Some(Discovery {
+                dirs_scanned: 12,
+                bare_skipped: 1,
+            }),
+            6,
+        );
+        stats.filter_out("filter", 2);
+        stats.filter_out("exclude", 0);
+        stats.filter_out("ignore", 1);
+        let categories = [Category::Clean, Category::Clean, Category::Dirty];
+
+        assert_eq!(
+            stats.render(&categories, Duration::from_millis(1234)),
+            "git-all stats:\n  dirs scanned     12\n  repos found      7\n  filtered out     4 (bare 1, filter 2, ignore 1)\n  repos processed  3\n  results          clean 2, dirty 1\n  total time       1.23s\n"
+        );
+    }
+
+    #[test]
+    fn test_render_without_scan_or_results() {
+        let mut stats = RunStats::new(Instant::now());
+        stats.discovered(None, 2);
+        let rendered = stats.render(&[], Duration::ZERO);
+        assert!(!rendered.contains("dirs scanned"), "{rendered}");
+        assert!(rendered.contains("  filtered out     0\n"), "{rendered}");
+        assert!(rendered.contains("  results          none\n"), "{rendered}");
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+use common::{git, git_all};
+
+/// An upstream repo plus a workspace holding a working clone and a mirror of it
+fn mirror_layout(root: &Path) -> (std::path::PathBuf, std::path::PathBuf) {
+    let upstream = root.join("upstream");
+    let workspace = root.join("workspace");
+    std::fs::create_dir_all(&upstream).unwrap();
+    std::fs::create_dir_all(&workspace).unwrap();
+    git(&upstream, &["init", "-q", "-b", "main"]);
+    git(&upstream, &["commit", "-q", "--allow-empty", "-m", "one"]);
+    git(
+        &workspace,
+        &[
+            "clone",
+            "-q",
+            "--mirror",
+            upstream.to_str().unwrap(),
+            "app.git",
+        ],
+    );
+    git(
+        &workspace,
+        &["clone", "-q", upstream.to_str().unwrap(), "web"],
+    );
+    (upstream, workspace)
+}
+
+#[test]
+fn bare_repos_are_skipped_by_default() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let (_, workspace) = mirror_layout(temp.path());
+
+    let output = git_all(&workspace, &["fetch"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("[web ]"), "{stdout}");
+    assert!(!stdout.contains("app.git"), "{stdout}");
+}
+
+#[test]
+fn include_bare_fetches_mirror_with_update_head_ok() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let (upstream, workspace) = mirror_layout(temp.path());
+    git(&upstream, &["commit", "-q", "--allow-empty", "-m", "two"]);
+
+    let dry_run = git_all(&workspace, &["--include-bare", "--dry-run", "fetch"]);
+    let stdout = String::from_utf8_lossy(&dry_run.stdout);
+    assert!(
+        stdout.contains("app.git fetch --update-head-ok"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("web fetch --update-head-ok"), "{stdout}");
+
+    let output = git_all(&workspace, &["--include-bare", "fetch"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("[app.git] 1 branch updated"), "{stdout}");
+
+    let mirror_head = Command::new("git")
+        .args(["rev-parse", "main"])
+        .current_dir(workspace.join("app.git"))
+        .output()
+        .unwrap();
+    let upstream_head = Command::new("git")
+        .args(["rev-parse", "main"])
+        .current_dir(&upstream)
+        .output()
+        .unwrap();
+    assert_eq!(mirror_head.stdout, upstream_head.stdout);
+}
+
+#[test]
+fn mirror_update_syncs_all_refs_and_skips_working_repos() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let (upstream, workspace) = mirror_layout(temp.path());
+    git(&upstream, &["branch", "gone"]);
+    git(
+        &workspace.join("app.git"),
+        &["fetch", "-q", "--prune", "origin", "+refs/*:refs/*"],
+    );
+    git(&upstream, &["commit", "-q", "--allow-empty", "-m", "two"]);
+    git(&upstream, &["branch", "-D", "gone"]);
+    git(&upstream, &["branch", "topic"]);
+    git(&upstream, &["tag", "v1"]);
+
+    // --mirror-update finds bare repos without --include-bare
+    let output = git_all(&workspace, &["fetch", "--mirror-update"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.contains("[app.git] 3 refs updated, 1 pruned"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("[web    ] not a bare mirror (skipped)"),
+        "{stdout}"
+    );
+
+    let refs = Command::new("git")
+        .args(["for-each-ref", "--format=%(refname)"])
+        .current_dir(workspace.join("app.git"))
+        .output()
+        .unwrap();
+    let refs = String::from_utf8_lossy(&refs.stdout);
+    assert!(
+        refs.contains("refs/heads/topic") && refs.contains("refs/tags/v1"),
+        "{refs}"
+    );
+    assert!(!refs.contains("refs/heads/gone"), "{refs}");
+
+    let again = git_all(&workspace, &["fetch", "--mirror-update"]);
+    assert!(String::from_utf8_lossy(&again.stdout).contains("[app.git] up to date"));
+}
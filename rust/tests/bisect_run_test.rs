@@ -0,0 +1,52 @@
+mod common;
+use common::{git_all, init_repo};
+
+/// The test "script" passes in repos containing a PASS marker file
+const TEST_COMMAND: [&str; 4] = ["sh", "-c", "test -f PASS || exit 3", "test"];
+
+#[test]
+fn bisect_run_reports_pass_fail_and_failing_repos() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "web", "worker"] {
+        init_repo(&temp.path().join(name));
+    }
+    std::fs::write(temp.path().join("api").join("PASS"), "").expect("write marker");
+    std::fs::write(temp.path().join("worker").join("PASS"), "").expect("write marker");
+
+    let mut args = vec!["bisect-run", "--"];
+    args.extend(TEST_COMMAND);
+    let output = git_all(temp.path(), &args);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(!output.status.success(), "{stdout}");
+    assert!(
+        stdout
+            .lines()
+            .any(|l| l.starts_with("[api") && l.ends_with("pass")),
+        "{stdout}"
+    );
+    assert!(
+        stdout
+            .lines()
+            .any(|l| l.starts_with("[web") && l.ends_with("FAIL (exit 3)")),
+        "{stdout}"
+    );
+    assert!(stdout.contains("2/3 passed; failing: web"), "{stdout}");
+}
+
+#[test]
+fn bisect_run_succeeds_when_every_repo_passes() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "web"] {
+        init_repo(&temp.path().join(name));
+        std::fs::write(temp.path().join(name).join("PASS"), "").expect("write marker");
+    }
+
+    let mut args = vec!["bisect-run", "--"];
+    args.extend(TEST_COMMAND);
+    let output = git_all(temp.path(), &args);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("2/2 passed"), "{stdout}");
+}
@@ -0,0 +1,43 @@
+mod common;
+use common::{git, git_all};
+
+#[test]
+fn clone_names_dirs_from_urls_and_skips_existing() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let upstream = temp.path().join("upstream");
+    let workspace = temp.path().join("workspace");
+    std::fs::create_dir_all(&workspace).unwrap();
+    for name in ["api", "web"] {
+        let repo = upstream.join(name);
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q"]);
+        git(&repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+    }
+    git(&upstream, &["clone", "-q", "--bare", "web", "web.git"]);
+
+    let manifest = temp.path().join("repos.txt");
+    std::fs::write(
+        &manifest,
+        format!("# services\n{}\n", upstream.join("web.git").display()),
+    )
+    .unwrap();
+    let api_url = format!("file://{}", upstream.join("api").display());
+
+    let output = git_all(
+        &workspace,
+        &["clone", "--manifest", manifest.to_str().unwrap(), &api_url],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("[api ] cloned"), "{stdout}");
+    assert!(stdout.contains("[web ] cloned"), "{stdout}");
+    assert!(workspace.join("api/.git").is_dir());
+    assert!(workspace.join("web/.git").is_dir());
+
+    // Re-running only clones what is missing; the manifest can be given positionally
+    std::fs::remove_dir_all(workspace.join("api")).unwrap();
+    let output = git_all(&workspace, &["clone", manifest.to_str().unwrap(), &api_url]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[api ] cloned"), "{stdout}");
+    assert!(stdout.contains("[web ] exists, skipped"), "{stdout}");
+}
@@ -0,0 +1,39 @@
+//! Helpers shared by the integration tests. Each test file includes this with
+//! `mod common;` and uses only some of it.
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// Run git in `dir` with a fixed identity, failing the test if it fails
+pub fn git(dir: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(["-c", "protocol.file.allow=always"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("failed to run git");
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Create `path` and an empty repository in it, on branch main
+pub fn init_repo(path: &Path) {
+    std::fs::create_dir_all(path).expect("create repo dir");
+    git(path, &["init", "-q", "-b", "main"]);
+}
+
+/// Run git-all in `dir`. stdin is closed, so nothing waits on a prompt.
+pub fn git_all(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_git-all"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to execute")
+}
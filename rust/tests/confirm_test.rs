@@ -0,0 +1,77 @@
+use std::path::Path;
+
+mod common;
+use common::{git, git_all};
+
+/// Two repos, each with a committed README.md that has since been edited
+fn repos_with_edits(root: &Path) {
+    for name in ["api", "web"] {
+        let repo = root.join(name);
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q"]);
+        std::fs::write(repo.join("README.md"), "committed\n").unwrap();
+        git(&repo, &["add", "README.md"]);
+        git(&repo, &["commit", "-q", "-m", "init"]);
+        std::fs::write(repo.join("README.md"), "edited\n").unwrap();
+    }
+}
+
+fn readme(root: &Path, name: &str) -> String {
+    std::fs::read_to_string(root.join(name).join("README.md")).unwrap()
+}
+
+#[test]
+fn destructive_command_refuses_without_confirmation() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    repos_with_edits(temp.path());
+
+    let output = git_all(temp.path(), &["reset", "--hard"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("refusing to run destructive 'reset'"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("--yes"), "{stderr}");
+    assert_eq!(readme(temp.path(), "api"), "edited\n");
+    assert_eq!(readme(temp.path(), "web"), "edited\n");
+
+    std::fs::write(temp.path().join("web").join("scratch.txt"), "").unwrap();
+    let output = git_all(temp.path(), &["clean", "-fd"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(temp.path().join("web").join("scratch.txt").exists());
+}
+
+#[test]
+fn destructive_command_proceeds_with_yes() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    repos_with_edits(temp.path());
+
+    let output = git_all(temp.path(), &["--yes", "restore", "README.md"]);
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(readme(temp.path(), "api"), "committed\n");
+    assert_eq!(readme(temp.path(), "web"), "committed\n");
+}
+
+#[test]
+fn safe_and_dry_run_commands_need_no_confirmation() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    repos_with_edits(temp.path());
+
+    assert_eq!(
+        git_all(temp.path(), &["clean", "-n"]).status.code(),
+        Some(0)
+    );
+    assert_eq!(
+        git_all(temp.path(), &["--dry-run", "reset", "--hard"])
+            .status
+            .code(),
+        Some(0)
+    );
+    assert_eq!(readme(temp.path(), "api"), "edited\n");
+}
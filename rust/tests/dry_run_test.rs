@@ -0,0 +1,206 @@
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+use common::{git_all, init_repo};
+
+#[test]
+fn global_dry_run_prints_plan_without_running() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("repo1"));
+
+    let output = git_all(temp.path(), &["--dry-run", "fetch"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(stdout.contains("dry-run mode"), "{stdout}");
+    assert!(
+        stdout.lines().any(|l| l.ends_with("repo1 fetch")),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn dry_run_reports_repo_count_before_plan() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "web", "docs"] {
+        init_repo(&temp.path().join(name));
+    }
+
+    let output = git_all(temp.path(), &["--dry-run", "pull"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines[1], "[git-all] would run 'pull' on 3 repositories",
+        "{stdout}"
+    );
+    assert!(lines[2].ends_with("api pull"), "{stdout}");
+
+    let json = git_all(temp.path(), &["--dry-run", "--json", "pull"]);
+    let stdout = String::from_utf8_lossy(&json.stdout);
+    assert!(!stdout.contains("would run"), "{stdout}");
+    assert!(
+        stdout.contains(r#"{"dry_run":{"command":"pull","repos":3}}"#),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn only_behind_dry_run_plans_the_pull() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("api"));
+
+    let output = git_all(temp.path(), &["--dry-run", "pull", "--only-behind"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("would run 'pull' on 1 repository"),
+        "{stdout}"
+    );
+    assert!(stdout.lines().any(|l| l.ends_with("api pull")), "{stdout}");
+    assert!(!stdout.contains("symbolic-ref"), "{stdout}");
+}
+
+#[test]
+fn git_dry_run_after_subcommand_runs_git() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("repo1"));
+
+    for args in [
+        &["fetch", "--dry-run"][..],
+        &["fetch", "--", "--dry-run"][..],
+    ] {
+        let output = git_all(temp.path(), args);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert!(output.status.success());
+        assert!(!stdout.contains("dry-run mode"), "{args:?}: {stdout}");
+        assert!(stdout.contains("[repo1]"), "{args:?}: {stdout}");
+    }
+}
+
+#[test]
+fn both_dry_runs_plan_a_git_dry_run() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("repo1"));
+
+    let output = git_all(temp.path(), &["--dry-run", "fetch", "--", "--dry-run"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("dry-run mode"), "{stdout}");
+    assert!(
+        stdout.lines().any(|l| l.ends_with("repo1 fetch --dry-run")),
+        "{stdout}"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn parallelism_cap_from_ulimit_clamps_workers() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("repo1"));
+
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg("ulimit -n 64 && exec \"$0\" -n 100 --parallelism-cap-from-ulimit --dry-run status")
+        .arg(env!("CARGO_BIN_EXE_git-all"))
+        .current_dir(temp.path())
+        .output()
+        .expect("failed to execute");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.status.success(), "{stderr}");
+    assert!(
+        stderr.contains("open file limit is 64; using 5 workers instead of 100"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn host_rewrites_urls_for_each_domain() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("repo1"));
+
+    let output = git_all(temp.path(), &["--dry-run", "--ssh", "fetch"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("git -c url.git@github.com:.insteadOf=https://github.com/ -C "),
+        "{stdout}"
+    );
+
+    let output = git_all(
+        temp.path(),
+        &[
+            "--dry-run",
+            "--https",
+            "--host",
+            "gitlab.example.com",
+            "--host",
+            "git.corp",
+            "fetch",
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.contains(
+            "git -c url.https://gitlab.example.com/.insteadOf=git@gitlab.example.com: \
+             -c url.https://git.corp/.insteadOf=git@git.corp: -C "
+        ),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("github.com"), "{stdout}");
+}
+
+#[test]
+fn ssh_multiplex_injects_ssh_command_and_cleans_up() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("repo1"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-all"))
+        .args(["--dry-run", "--ssh-multiplex", "fetch"])
+        .current_dir(temp.path())
+        .env_remove("GIT_SSH_COMMAND")
+        .output()
+        .expect("failed to execute");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+
+    let line = stdout
+        .lines()
+        .find(|l| l.ends_with("repo1 fetch"))
+        .unwrap_or_else(|| panic!("no fetch command: {stdout}"));
+    assert!(
+        line.starts_with(
+            "GIT_SSH_COMMAND='ssh -o ControlMaster=auto -o ControlPath=\"/tmp/git-all-ssh-"
+        ),
+        "{line}"
+    );
+    assert!(line.contains("ControlPersist=60' git -C "), "{line}");
+
+    // The per-run socket directory is removed when git-all exits
+    let dir = line
+        .split("ControlPath=\"")
+        .nth(1)
+        .and_then(|rest| rest.split("/%C").next())
+        .expect("socket dir in command");
+    assert!(
+        !Path::new(dir).exists(),
+        "socket dir should be removed: {line}"
+    );
+}
+
+#[test]
+fn max_depth_alias_finds_nested_repos_without_descending_into_repos() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("github.com/org/api"));
+    init_repo(&temp.path().join("github.com/org/api/vendor/lib"));
+
+    let shallow = git_all(temp.path(), &["--dry-run", "status"]);
+    assert!(!String::from_utf8_lossy(&shallow.stdout).contains("org/api"));
+
+    let output = git_all(temp.path(), &["--dry-run", "--max-depth", "3", "status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("github.com/org/api status"), "{stdout}");
+    assert!(!stdout.contains("vendor/lib"), "{stdout}");
+}
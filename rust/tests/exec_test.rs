@@ -0,0 +1,162 @@
+use std::process::Command;
+
+mod common;
+use common::{git_all, init_repo};
+
+#[test]
+fn exec_substitutes_repo_and_name_with_spaces_in_paths() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let root = temp.path().join("my projects");
+    init_repo(&root.join("web app"));
+    init_repo(&root.join("api"));
+
+    let output = git_all(&root, &["exec", "--", "touch", "{repo}/{name}.marker"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(root.join("web app").join("web app.marker").exists());
+    assert!(root.join("api").join("api.marker").exists());
+
+    let output = git_all(&root, &["exec", "--", "echo", "{name}"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[web app] web app"), "{stdout}");
+}
+
+#[test]
+fn fair_mode_keeps_later_repos_moving_behind_a_slow_first_repo() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["aaa", "bbb", "ccc", "ddd", "eee", "fff"] {
+        init_repo(&temp.path().join(name));
+    }
+
+    // aaa holds its slot until the five other repos have finished (giving up
+    // after ~10s), so it is only released if they ran through the second slot
+    let script = "if [ {name} = aaa ]; then i=0; \
+                  while [ $(cat ../finished 2>/dev/null | wc -l) -lt 5 ]; do \
+                  i=$((i+1)); [ $i -gt 200 ] && exit 1; sleep 0.05; done; echo released; \
+                  else echo {name} >> ../finished; echo done; fi";
+    let output = git_all(
+        temp.path(),
+        &["-n", "2", "--fair", "exec", "--", "sh", "-c", script],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("[aaa ] released"), "{stdout}");
+    assert_eq!(stdout.matches("done").count(), 5, "{stdout}");
+}
+
+#[test]
+fn exec_shell_runs_pipes_in_each_repo() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("api"));
+    init_repo(&temp.path().join("web app"));
+
+    let output = git_all(
+        temp.path(),
+        &[
+            "exec",
+            "--shell",
+            "--",
+            "printf 'one\\ntwo\\n' | tail -1 > {repo}/last.txt && echo {name}-ok",
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("[api    ] api-ok"), "{stdout}");
+    assert!(stdout.contains("[web app] web app-ok"), "{stdout}");
+    let last = std::fs::read_to_string(temp.path().join("web app").join("last.txt")).unwrap();
+    assert_eq!(last, "two\n");
+
+    // Without --shell the pipe is just an argument to printf
+    let output = git_all(temp.path(), &["exec", "--", "echo", "a", "|", "tail", "-1"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[api    ] a | tail -1"), "{stdout}");
+}
+
+#[test]
+fn timeout_kills_hung_repo_and_lets_others_finish() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["fast", "hung"] {
+        init_repo(&temp.path().join(name));
+    }
+
+    let started = std::time::Instant::now();
+    let output = git_all(
+        temp.path(),
+        &[
+            "--timeout",
+            "1",
+            "exec",
+            "--",
+            "sh",
+            "-c",
+            "if [ {name} = hung ]; then sleep 30; fi; echo done",
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[fast] done"), "{stdout}");
+    assert!(stdout.contains("[hung] TIMEOUT after 1s"), "{stdout}");
+    assert!(
+        started.elapsed().as_secs() < 10,
+        "took {:?}",
+        started.elapsed()
+    );
+}
+
+#[test]
+fn prefix_lines_labels_every_output_line() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("api"));
+    init_repo(&temp.path().join("web"));
+
+    let output = git_all(
+        temp.path(),
+        &["--prefix-lines", "exec", "--", "printf", "one\\ntwo\\n"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "[api ] one\n[api ] two\n[web ] one\n[web ] two\n");
+
+    let output = git_all(temp.path(), &["--prefix-lines", "status"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--prefix-lines applies only to exec and passthrough"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn resume_skips_repos_a_plain_run_completed() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let root = temp.path().join("work");
+    init_repo(&root.join("api"));
+    init_repo(&root.join("web"));
+    std::fs::write(root.join("web").join("fail"), "").unwrap();
+
+    let run = |extra: &[&str]| {
+        let script = "echo ran >> {repo}/runs; test ! -e {repo}/fail";
+        Command::new(env!("CARGO_BIN_EXE_git-all"))
+            .args(extra)
+            .args(["exec", "--", "sh", "-c", script])
+            .current_dir(&root)
+            .env("XDG_CACHE_HOME", temp.path().join("cache"))
+            .output()
+            .expect("failed to execute")
+    };
+    let runs = |name: &str| std::fs::read_to_string(root.join(name).join("runs")).unwrap();
+
+    // No --resume here: the run still records that api completed
+    run(&[]);
+    std::fs::remove_file(root.join("web").join("fail")).unwrap();
+
+    let output = run(&["--resume"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(runs("api"), "ran\n");
+    assert_eq!(runs("web"), "ran\nran\n");
+}
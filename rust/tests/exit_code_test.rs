@@ -0,0 +1,81 @@
+use std::process::Command;
+
+mod common;
+use common::{git_all, init_repo};
+
+#[test]
+fn only_dirty_does_not_change_dirty_exit_code() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("clean"));
+    init_repo(&temp.path().join("dirty"));
+    std::fs::write(temp.path().join("dirty").join("new.txt"), "x").expect("write file");
+
+    let filtered = git_all(
+        temp.path(),
+        &["--only-dirty", "--dirty-exit-code", "status"],
+    );
+    let stdout = String::from_utf8_lossy(&filtered.stdout);
+    assert_eq!(filtered.status.code(), Some(1), "{stdout}");
+    assert!(stdout.contains("dirty"), "{stdout}");
+    assert!(!stdout.contains("[clean"), "{stdout}");
+
+    let unfiltered = git_all(temp.path(), &["--dirty-exit-code", "status"]);
+    assert_eq!(unfiltered.status.code(), filtered.status.code());
+}
+
+#[test]
+fn clean_repos_exit_zero_even_when_nothing_is_printed() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("repo1"));
+    init_repo(&temp.path().join("repo2"));
+
+    let output = git_all(
+        temp.path(),
+        &["--only-dirty", "--dirty-exit-code", "status"],
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert!(
+        output.stdout.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn dirty_exit_code_is_opt_in() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("dirty"));
+    std::fs::write(temp.path().join("dirty").join("new.txt"), "x").expect("write file");
+
+    let output = git_all(temp.path(), &["--only-dirty", "status"]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn strict_exits_one_when_any_repo_fails() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("api"));
+    init_repo(&temp.path().join("web"));
+    let status = Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(["commit", "-q", "--allow-empty", "-m", "init"])
+        .current_dir(temp.path().join("api"))
+        .status()
+        .expect("failed to run git commit");
+    assert!(status.success());
+
+    // rev-parse HEAD fails in web, which has no commits
+    let args = ["rev-parse", "--verify", "-q", "HEAD"];
+    assert_eq!(git_all(temp.path(), &args).status.code(), Some(0));
+
+    let strict = git_all(temp.path(), &[&["--strict"][..], &args].concat());
+    assert_eq!(
+        strict.status.code(),
+        Some(1),
+        "{}",
+        String::from_utf8_lossy(&strict.stdout)
+    );
+
+    let passing = git_all(temp.path(), &["--strict", "status"]);
+    assert_eq!(passing.status.code(), Some(0));
+}
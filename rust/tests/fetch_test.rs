@@ -0,0 +1,38 @@
+mod common;
+use common::{git, git_all};
+
+#[test]
+fn report_new_commits_count_counts_default_branch_commits() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let upstream = temp.path().join("upstream");
+    std::fs::create_dir_all(&upstream).unwrap();
+    git(&upstream, &["init", "-q", "-b", "main"]);
+    git(&upstream, &["commit", "-q", "--allow-empty", "-m", "init"]);
+
+    let root = temp.path().join("work");
+    std::fs::create_dir_all(&root).unwrap();
+    git(&root, &["clone", "-q", upstream.to_str().unwrap(), "api"]);
+    for i in 0..3 {
+        git(
+            &upstream,
+            &[
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                &format!("change {i}"),
+            ],
+        );
+    }
+
+    let output = git_all(
+        &root,
+        &["--no-summary", "fetch", "--report-new-commits-count"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.trim_end(),
+        "[api ] 1 branch updated, main: 3 new commits",
+        "{stdout}"
+    );
+}
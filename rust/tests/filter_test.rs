@@ -0,0 +1,146 @@
+mod common;
+use common::{git_all, init_repo};
+
+#[test]
+fn filter_keeps_repos_matching_any_glob() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["service-auth", "service-billing", "web", "docs"] {
+        init_repo(&temp.path().join(name));
+    }
+
+    let output = git_all(
+        temp.path(),
+        &["--no-summary", "--filter", "service-*", "status"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        [
+            "[service-auth   ] (main) clean",
+            "[service-billing] (main) clean"
+        ],
+        "{stdout}"
+    );
+
+    let output = git_all(
+        temp.path(),
+        &[
+            "--no-summary",
+            "--filter",
+            "service-a*",
+            "--filter",
+            "w?b",
+            "status",
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        ["[service-auth] (main) clean", "[web         ] (main) clean"],
+        "{stdout}"
+    );
+}
+
+#[test]
+fn filter_matching_nothing_runs_nothing() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("web"));
+
+    let output = git_all(temp.path(), &["--filter", "service-*", "status"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+        "No repositories match --filter service-*"
+    );
+}
+
+#[test]
+fn exclude_drops_matching_repos_after_filter() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["service-auth", "service-legacy", "archived-web", "docs"] {
+        init_repo(&temp.path().join(name));
+    }
+
+    let output = git_all(
+        temp.path(),
+        &[
+            "--no-summary",
+            "--exclude",
+            "archived-*",
+            "--exclude",
+            "docs",
+            "status",
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        [
+            "[service-auth  ] (main) clean",
+            "[service-legacy] (main) clean"
+        ],
+        "{stdout}"
+    );
+
+    let output = git_all(
+        temp.path(),
+        &[
+            "--no-summary",
+            "--filter",
+            "service-*",
+            "--exclude",
+            "*-legacy",
+            "status",
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        ["[service-auth] (main) clean"],
+        "{stdout}"
+    );
+}
+
+#[test]
+fn excluding_every_repo_says_so() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("archived-api"));
+    init_repo(&temp.path().join("archived-web"));
+
+    let output = git_all(temp.path(), &["--exclude", "archived-*", "status"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+        "All repositories excluded by --exclude archived-*"
+    );
+}
+
+#[test]
+fn ignore_file_skips_listed_repos_unless_no_ignore() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "archived-web", "scratch"] {
+        init_repo(&temp.path().join(name));
+    }
+    std::fs::write(
+        temp.path().join(".git-all-ignore"),
+        "# retired\narchived-*\n\n  scratch \n",
+    )
+    .unwrap();
+
+    let output = git_all(temp.path(), &["--no-summary", "status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        ["[api ] (main) clean"],
+        "{stdout}"
+    );
+
+    let output = git_all(temp.path(), &["--no-summary", "--no-ignore", "status"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 3);
+
+    let output = git_all(temp.path(), &["--exclude", "api", "status"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+        "All repositories excluded by --exclude api and .git-all-ignore"
+    );
+}
@@ -0,0 +1,61 @@
+#![cfg(unix)]
+
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+mod common;
+use common::init_repo;
+
+#[test]
+fn ctrl_c_terminates_children_and_reports_interrupted_repos() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "web", "docs"] {
+        init_repo(&temp.path().join(name));
+    }
+
+    // -n 1 leaves two repos queued when the interrupt arrives. The shell
+    // ignores SIGINT, so only git-all terminating it stops `touch late`.
+    let script = "trap '' INT; sleep 2 >/dev/null 2>&1; touch late";
+    let child = Command::new(env!("CARGO_BIN_EXE_git-all"))
+        .args(["-n", "1", "exec", "--", "sh", "-c", script])
+        .current_dir(temp.path())
+        .process_group(0)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn");
+    std::thread::sleep(Duration::from_millis(500));
+
+    // Ctrl-C in a terminal signals the whole foreground process group
+    let started = Instant::now();
+    let status = Command::new("kill")
+        .args(["-INT", "--", &format!("-{}", child.id())])
+        .status()
+        .expect("failed to run kill");
+    assert!(status.success());
+    let output = child.wait_with_output().expect("failed to wait");
+    assert!(
+        started.elapsed() < Duration::from_secs(1),
+        "took {:?}",
+        started.elapsed()
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(130), "{stdout}{stderr}");
+    assert_eq!(stdout.matches("INTERRUPTED").count(), 3, "{stdout}");
+    assert!(
+        stderr.contains("interrupted: 0 completed, 3 interrupted"),
+        "{stderr}"
+    );
+
+    // The running shell was terminated before it got past its sleep
+    std::thread::sleep(Duration::from_millis(2500));
+    for name in ["api", "web", "docs"] {
+        assert!(
+            !temp.path().join(name).join("late").exists(),
+            "{name} ran to completion"
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn git_all(dir: &Path, home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_git-all"))
+        .args(args)
+        .current_dir(dir)
+        .env("HOME", home)
+        .env_remove("GIT_CONFIG_GLOBAL")
+        .env_remove("XDG_CONFIG_HOME")
+        .output()
+        .expect("failed to execute")
+}
+
+#[test]
+fn isolated_ignores_user_gitconfig() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let home = temp.path().join("home");
+    let root = temp.path().join("work");
+    std::fs::create_dir_all(&home).unwrap();
+    std::fs::write(home.join(".gitconfig"), "[user]\n\tname = From Home\n").unwrap();
+    let api = root.join("api");
+    std::fs::create_dir_all(&api).unwrap();
+    let status = Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(&api)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let inherited = git_all(&root, &home, &["config", "user.name"]);
+    let stdout = String::from_utf8_lossy(&inherited.stdout);
+    assert!(stdout.contains("From Home"), "{stdout}");
+
+    let isolated = git_all(&root, &home, &["--isolated", "config", "user.name"]);
+    let stdout = String::from_utf8_lossy(&isolated.stdout);
+    assert!(!stdout.contains("From Home"), "{stdout}");
+
+    let dry_run = git_all(
+        &root,
+        &home,
+        &["--isolated", "--dry-run", "config", "user.name"],
+    );
+    let stdout = String::from_utf8_lossy(&dry_run.stdout);
+    assert!(stdout.contains("GIT_CONFIG_NOSYSTEM=1 HOME="), "{stdout}");
+}
@@ -0,0 +1,83 @@
+use std::process::Output;
+
+mod common;
+use common::{git_all, init_repo};
+
+fn json_lines(output: &Output) -> Vec<serde_json::Value> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap_or_else(|e| panic!("{e}: {l}")))
+        .collect()
+}
+
+#[test]
+fn json_lines_one_object_per_repo() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("repo1"));
+    init_repo(&temp.path().join("repo2"));
+
+    let output = git_all(temp.path(), &["--json", "status"]);
+    assert!(output.status.success());
+
+    let records = json_lines(&output);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["repo"], "repo1");
+    assert_eq!(records[0]["category"], "clean");
+    assert!(records[0].get("duration_ms").is_none());
+}
+
+#[test]
+fn json_with_timings_includes_durations() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("repo1"));
+    init_repo(&temp.path().join("repo2"));
+
+    let output = git_all(temp.path(), &["--json", "--timings", "status"]);
+    assert!(output.status.success());
+
+    let records = json_lines(&output);
+    let (total, repos) = records.split_last().expect("records");
+    let total_ms = total["total_ms"].as_u64().expect("total_ms");
+    assert_eq!(repos.len(), 2);
+    for record in repos {
+        let duration_ms = record["duration_ms"].as_u64().expect("duration_ms");
+        assert!(duration_ms <= total_ms, "{record} vs total {total_ms}");
+    }
+    assert!(total_ms < 60_000);
+}
+
+#[test]
+fn status_json_includes_change_counts() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("api"));
+    init_repo(&temp.path().join("web"));
+    std::fs::write(temp.path().join("web").join("notes.txt"), "").unwrap();
+
+    let output = git_all(temp.path(), &["--json", "status"]);
+    let records = json_lines(&output);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["repo"], "api");
+    assert_eq!(records[0]["clean"], true);
+    assert_eq!(records[0]["modified"], 0);
+    assert_eq!(records[1]["repo"], "web");
+    assert_eq!(records[1]["clean"], false);
+    assert_eq!(records[1]["untracked"], 1);
+    assert_eq!(records[1]["branch"], "main");
+    assert_eq!(records[1]["summary"], "(main) 1 untracked");
+}
+
+#[test]
+fn status_json_files_lists_changed_paths() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("api"));
+    std::fs::write(temp.path().join("api").join("notes.txt"), "").unwrap();
+
+    let records = json_lines(&git_all(temp.path(), &["--json", "status", "--files"]));
+    assert_eq!(
+        records[0]["files"],
+        serde_json::json!([{"path": "notes.txt", "index_status": "?", "worktree_status": "?"}])
+    );
+
+    let records = json_lines(&git_all(temp.path(), &["--json", "status"]));
+    assert!(records[0].get("files").is_none());
+}
@@ -9,7 +9,10 @@ fn meta_shows_help_with_version() {
 
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("git-all v"), "should contain git-all version");
+    assert!(
+        stdout.contains("git-all v"),
+        "should contain git-all version"
+    );
     assert!(stdout.contains("git"), "should contain git version");
     assert!(stdout.contains("Usage:"), "should contain usage info");
 }
@@ -42,3 +45,47 @@ fn meta_unknown_subcommand_fails() {
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(stderr.contains("Unknown meta subcommand"));
 }
+
+#[test]
+fn meta_discover_explain_reports_each_directory() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let root = temp.path();
+    std::fs::create_dir_all(root.join("repo1/.git")).unwrap();
+    std::fs::create_dir_all(root.join("org/repo2/.git")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-all"))
+        .args(["meta", "discover", "--explain"])
+        .current_dir(root)
+        .output()
+        .expect("failed to execute");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("depth 1   repo         repo1"), "{stdout}");
+    assert!(stdout.contains("depth 1   depth limit  org"), "{stdout}");
+    assert!(stdout.contains("1 repositories found"), "{stdout}");
+}
+
+#[test]
+fn version_flag_includes_git_version() {
+    // Outside any repository, so --version is not passed through to git
+    let temp = tempfile::tempdir().expect("temp dir");
+    for flag in ["--version", "-V"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_git-all"))
+            .arg(flag)
+            .current_dir(temp.path())
+            .output()
+            .expect("failed to execute");
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.starts_with(&format!("git-all {}\n", env!("CARGO_PKG_VERSION"))),
+            "{stdout}"
+        );
+        assert!(
+            stdout.contains("\ngit 2."),
+            "should contain git version: {stdout}"
+        );
+    }
+}
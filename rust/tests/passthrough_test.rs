@@ -0,0 +1,45 @@
+mod common;
+use common::{git_all, init_repo};
+
+/// A repository that itself contains two child repositories
+fn meta_repo() -> tempfile::TempDir {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(temp.path());
+    init_repo(&temp.path().join("api"));
+    init_repo(&temp.path().join("web"));
+    std::fs::write(temp.path().join("web").join("new.txt"), "x").expect("write file");
+    temp
+}
+
+#[test]
+fn no_passthrough_runs_across_child_repos_inside_a_repo() {
+    let temp = meta_repo();
+
+    let output = git_all(temp.path(), &["--no-passthrough", "status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("[api ] (main) clean"), "{stdout}");
+    assert!(stdout.contains("[web ] (main) 1 untracked"), "{stdout}");
+}
+
+#[test]
+fn known_subcommand_runs_across_repos_inside_a_repo() {
+    let temp = meta_repo();
+
+    let output = git_all(temp.path(), &["status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("[api ] (main) clean"), "{stdout}");
+    assert!(stdout.contains("[web ] (main) 1 untracked"), "{stdout}");
+}
+
+#[test]
+fn other_git_commands_pass_through_inside_a_repo() {
+    let temp = meta_repo();
+
+    let output = git_all(temp.path(), &["rev-parse", "--show-toplevel"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    let toplevel = std::fs::canonicalize(temp.path()).expect("canonicalize");
+    assert_eq!(stdout.trim(), toplevel.to_string_lossy());
+}
@@ -0,0 +1,65 @@
+use std::path::Path;
+
+mod common;
+use common::{git, git_all};
+
+/// A clone of a fresh bare remote in `root/name`, with one pushed commit
+fn clone_with_remote(root: &Path, remotes: &Path, name: &str) -> std::path::PathBuf {
+    let remote = remotes.join(format!("{}.git", name));
+    let work = root.join(name);
+    git(
+        remotes,
+        &[
+            "init",
+            "-q",
+            "--bare",
+            "-b",
+            "main",
+            remote.to_str().unwrap(),
+        ],
+    );
+    git(
+        root,
+        &[
+            "clone",
+            "-q",
+            remote.to_str().unwrap(),
+            work.to_str().unwrap(),
+        ],
+    );
+    git(&work, &["checkout", "-q", "-B", "main"]);
+    git(&work, &["commit", "-q", "--allow-empty", "-m", "init"]);
+    git(&work, &["push", "-q", "-u", "origin", "main"]);
+    work
+}
+
+#[test]
+fn push_only_touches_repos_that_are_ahead() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let root = temp.path().join("work");
+    let remotes = temp.path().join("remotes");
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::create_dir_all(&remotes).unwrap();
+    let api = clone_with_remote(&root, &remotes, "api");
+    clone_with_remote(&root, &remotes, "web");
+    git(&api, &["commit", "-q", "--allow-empty", "-m", "one"]);
+    git(&api, &["commit", "-q", "--allow-empty", "-m", "two"]);
+
+    let dry_run = git_all(&root, &["--dry-run", "push", "--force-with-lease"]);
+    let stdout = String::from_utf8_lossy(&dry_run.stdout);
+    assert!(
+        stdout.contains("would run 'push' on 2 repositories"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("push --force-with-lease"), "{stdout}");
+    assert!(!stdout.contains("rev-list"), "{stdout}");
+
+    let output = git_all(&root, &["push"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[api ] pushed 2 commits"), "{stdout}");
+    assert!(stdout.contains("[web ] nothing to push"), "{stdout}");
+
+    let again = git_all(&root, &["push"]);
+    let stdout = String::from_utf8_lossy(&again.stdout);
+    assert!(stdout.contains("[api ] nothing to push"), "{stdout}");
+}
@@ -0,0 +1,55 @@
+use std::process::Command;
+
+mod common;
+use common::{git_all, init_repo};
+
+#[test]
+fn stats_block_explains_discovery_and_results() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "web", "docs", "sketch", "legacy"] {
+        init_repo(&temp.path().join(name));
+    }
+    std::fs::write(temp.path().join("web/notes.txt"), "draft\n").unwrap();
+    std::fs::create_dir(temp.path().join("notes")).unwrap();
+    let mirror = temp.path().join("mirror.git");
+    let status = Command::new("git")
+        .args(["init", "-q", "--bare"])
+        .arg(&mirror)
+        .status()
+        .expect("failed to run git init");
+    assert!(status.success());
+    std::fs::write(temp.path().join(".git-all-ignore"), "legacy\n").unwrap();
+
+    let output = git_all(
+        temp.path(),
+        &[
+            "--stats",
+            "--filter",
+            "*e*",
+            "--filter",
+            "api",
+            "--exclude",
+            "sketch",
+            "status",
+        ],
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    let block: Vec<&str> = stderr
+        .lines()
+        .skip_while(|l| *l != "git-all stats:")
+        .collect();
+    assert_eq!(
+        &block[..6],
+        [
+            "git-all stats:",
+            "  dirs scanned     7",
+            "  repos found      6",
+            "  filtered out     4 (bare 1, filter 1, exclude 1, ignore 1)",
+            "  repos processed  2",
+            "  results          clean 1, dirty 1",
+        ],
+        "{stderr}"
+    );
+    assert!(block[6].starts_with("  total time       "), "{stderr}");
+}
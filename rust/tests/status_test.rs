@@ -0,0 +1,289 @@
+use std::process::Command;
+
+mod common;
+use common::{git, git_all};
+
+#[test]
+fn ignore_submodules_reports_submodule_dirty_repo_clean() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let lib = temp.path().join("lib");
+    let workspace = temp.path().join("workspace");
+    let app = workspace.join("app");
+
+    std::fs::create_dir_all(&lib).unwrap();
+    git(&lib, &["init", "-q", "-b", "main"]);
+    std::fs::write(lib.join("lib.rs"), "").unwrap();
+    git(&lib, &["add", "."]);
+    git(&lib, &["commit", "-q", "-m", "lib"]);
+
+    std::fs::create_dir_all(&app).unwrap();
+    git(&app, &["init", "-q", "-b", "main"]);
+    git(
+        &app,
+        &["submodule", "add", "-q", lib.to_str().unwrap(), "lib"],
+    );
+    git(&app, &["commit", "-q", "-m", "add lib"]);
+    // Dirty the submodule's working tree only
+    std::fs::write(app.join("lib").join("lib.rs"), "changed").unwrap();
+
+    let plain = git_all(&workspace, &["status"]);
+    let stdout = String::from_utf8_lossy(&plain.stdout);
+    assert!(stdout.contains("[app ] (main) 1 modified"), "{stdout}");
+
+    let ignored = git_all(&workspace, &["status", "--ignore-submodules"]);
+    let stdout = String::from_utf8_lossy(&ignored.stdout);
+    assert!(ignored.status.success(), "{stdout}");
+    assert!(stdout.contains("[app ] (main) clean"), "{stdout}");
+}
+
+#[test]
+fn branch_filters_select_repos_by_current_branch() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for (name, branch) in [("api", "main"), ("web", "feature"), ("docs", "main")] {
+        let repo = temp.path().join(name);
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", branch]);
+    }
+    let detached = temp.path().join("tools");
+    std::fs::create_dir_all(&detached).unwrap();
+    git(&detached, &["init", "-q", "-b", "main"]);
+    git(&detached, &["commit", "-q", "--allow-empty", "-m", "init"]);
+    git(&detached, &["checkout", "-q", "--detach"]);
+
+    let on_main = git_all(temp.path(), &["status", "--on-branch", "main"]);
+    let stdout = String::from_utf8_lossy(&on_main.stdout);
+    assert!(stdout.contains("[api"), "{stdout}");
+    assert!(stdout.contains("[docs"), "{stdout}");
+    assert!(!stdout.contains("[web"), "{stdout}");
+    assert!(!stdout.contains("[tools"), "{stdout}");
+
+    let off_main = git_all(temp.path(), &["status", "--not-on-branch", "main"]);
+    let stdout = String::from_utf8_lossy(&off_main.stdout);
+    assert!(stdout.contains("[web"), "{stdout}");
+    assert!(stdout.contains("[tools"), "{stdout}");
+    assert!(stdout.contains("[web  ] (feature) clean"), "{stdout}");
+    assert!(stdout.contains("[tools] (detached) clean"), "{stdout}");
+    assert!(!stdout.contains("[api"), "{stdout}");
+    assert!(!stdout.contains("[docs"), "{stdout}");
+}
+
+#[test]
+fn staged_and_unstaged_only_split_mixed_changes() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let api = temp.path().join("api");
+    let web = temp.path().join("web");
+    for repo in [&api, &web] {
+        std::fs::create_dir_all(repo).unwrap();
+        git(repo, &["init", "-q", "-b", "main"]);
+        for file in ["a.rs", "b.rs", "c.rs"] {
+            std::fs::write(repo.join(file), "one").unwrap();
+        }
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-q", "-m", "init"]);
+    }
+
+    // api: a.rs staged, b.rs unstaged, c.rs both, plus an untracked file
+    for file in ["a.rs", "c.rs"] {
+        std::fs::write(api.join(file), "two").unwrap();
+    }
+    git(&api, &["add", "a.rs", "c.rs"]);
+    std::fs::write(api.join("b.rs"), "two").unwrap();
+    std::fs::write(api.join("c.rs"), "three").unwrap();
+    std::fs::write(api.join("notes.txt"), "").unwrap();
+    // web: only an unstaged change
+    std::fs::write(web.join("a.rs"), "two").unwrap();
+
+    let all = git_all(temp.path(), &["status"]);
+    let stdout = String::from_utf8_lossy(&all.stdout);
+    assert!(
+        stdout.contains("[api ] (main) 3 modified, 1 untracked"),
+        "{stdout}"
+    );
+
+    let staged = git_all(temp.path(), &["status", "--staged-only"]);
+    let stdout = String::from_utf8_lossy(&staged.stdout);
+    assert!(stdout.contains("[api ] (main) 2 modified\n"), "{stdout}");
+    assert!(stdout.contains("[web ] (main) clean"), "{stdout}");
+
+    let unstaged = git_all(temp.path(), &["status", "--unstaged-only"]);
+    let stdout = String::from_utf8_lossy(&unstaged.stdout);
+    assert!(
+        stdout.contains("[api ] (main) 2 modified, 1 untracked"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("[web ] (main) 1 modified"), "{stdout}");
+}
+
+#[test]
+fn conflicting_output_format_args_are_rejected() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let repo = temp.path().join("api");
+    std::fs::create_dir_all(&repo).unwrap();
+    git(&repo, &["init", "-q", "-b", "main"]);
+    std::fs::write(repo.join("notes.txt"), "").unwrap();
+
+    for format in ["-s", "--short", "--porcelain=v2", "-z"] {
+        let output = git_all(temp.path(), &["status", format]);
+        assert_eq!(output.status.code(), Some(1), "{format}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains(&format!(
+                "status output format is managed by git-all; remove {format}"
+            )),
+            "{stderr}"
+        );
+        assert!(
+            output.stdout.is_empty(),
+            "{}",
+            String::from_utf8_lossy(&output.stdout)
+        );
+    }
+
+    let output = git_all(temp.path(), &["--no-summary", "status", "-uno"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+        "[api ] (main) clean"
+    );
+}
+
+#[test]
+fn status_shows_commits_ahead_and_behind_upstream() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let upstream = temp.path().join("upstream");
+    std::fs::create_dir_all(&upstream).unwrap();
+    git(&upstream, &["init", "-q", "-b", "main"]);
+    git(&upstream, &["commit", "-q", "--allow-empty", "-m", "init"]);
+
+    let root = temp.path().join("work");
+    std::fs::create_dir_all(&root).unwrap();
+    git(&root, &["clone", "-q", upstream.to_str().unwrap(), "api"]);
+    git(&root, &["clone", "-q", upstream.to_str().unwrap(), "web"]);
+    git(&root.join("web"), &["checkout", "-q", "--detach"]);
+    let api = root.join("api");
+    for message in ["one", "two", "three"] {
+        git(&api, &["commit", "-q", "--allow-empty", "-m", message]);
+    }
+    git(
+        &upstream,
+        &["commit", "-q", "--allow-empty", "-m", "upstream"],
+    );
+    git(&api, &["fetch", "-q"]);
+
+    let output = git_all(&root, &["--no-summary", "status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        ["[api ] (main) clean ↑3 ↓1", "[web ] (detached) clean"],
+        "{stdout}"
+    );
+}
+
+#[test]
+fn filenames_with_newlines_count_once() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let api = temp.path().join("api");
+    std::fs::create_dir_all(&api).unwrap();
+    git(&api, &["init", "-q", "-b", "main"]);
+    std::fs::write(api.join("line\nbreak.txt"), "").unwrap();
+
+    let output = git_all(temp.path(), &["status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[api ] (main) 1 untracked\n"), "{stdout}");
+
+    let output = git_all(temp.path(), &["--json", "status", "--files"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(r#""path":"line\nbreak.txt""#), "{stdout}");
+}
+
+#[test]
+fn hidden_changes_reports_modified_assume_unchanged_files() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let api = temp.path().join("api");
+    let web = temp.path().join("web");
+    for repo in [&api, &web] {
+        std::fs::create_dir_all(repo).unwrap();
+        git(repo, &["init", "-q", "-b", "main"]);
+        for file in ["local.yml", "vendored.rs", "other.rs"] {
+            std::fs::write(repo.join(file), "one").unwrap();
+        }
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-q", "-m", "init"]);
+        git(repo, &["update-index", "--assume-unchanged", "local.yml"]);
+        git(repo, &["update-index", "--skip-worktree", "vendored.rs"]);
+    }
+    std::fs::write(api.join("local.yml"), "two").unwrap();
+    std::fs::remove_file(api.join("vendored.rs")).unwrap();
+
+    let plain = git_all(temp.path(), &["status"]);
+    let stdout = String::from_utf8_lossy(&plain.stdout);
+    assert!(stdout.contains("[api ] (main) clean"), "{stdout}");
+
+    let hidden = git_all(temp.path(), &["status", "--hidden-changes"]);
+    let stdout = String::from_utf8_lossy(&hidden.stdout);
+    assert!(stdout.contains("[api ] 2 hidden changes"), "{stdout}");
+    assert!(stdout.contains("[web ] no hidden changes"), "{stdout}");
+}
+
+#[test]
+fn color_flag_paints_results_by_category() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "web"] {
+        std::fs::create_dir_all(temp.path().join(name)).unwrap();
+        git(&temp.path().join(name), &["init", "-q", "-b", "main"]);
+    }
+    std::fs::write(temp.path().join("web/notes.txt"), "draft\n").unwrap();
+
+    // Piped output stays plain by default
+    let plain = git_all(temp.path(), &["--no-summary", "status"]);
+    assert!(!String::from_utf8_lossy(&plain.stdout).contains('\x1b'));
+
+    let colored = Command::new(env!("CARGO_BIN_EXE_git-all"))
+        .args(["--color=always", "--no-summary", "status"])
+        .current_dir(temp.path())
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("failed to execute");
+    let stdout = String::from_utf8_lossy(&colored.stdout);
+    assert!(
+        stdout.contains("[api ] \x1b[32m(main) clean\x1b[0m"),
+        "{stdout:?}"
+    );
+    assert!(
+        stdout.contains("[web ] \x1b[33m(main) 1 untracked\x1b[0m"),
+        "{stdout:?}"
+    );
+
+    let never = git_all(temp.path(), &["--color=never", "--no-summary", "status"]);
+    assert!(!String::from_utf8_lossy(&never.stdout).contains('\x1b'));
+}
+
+#[test]
+fn sort_by_status_puts_dirty_repos_first() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "docs", "web", "zeta"] {
+        std::fs::create_dir_all(temp.path().join(name)).unwrap();
+        git(&temp.path().join(name), &["init", "-q", "-b", "main"]);
+    }
+    std::fs::write(temp.path().join("web/notes.txt"), "draft\n").unwrap();
+    std::fs::write(temp.path().join("docs/notes.txt"), "draft\n").unwrap();
+
+    let by_status = git_all(temp.path(), &["--no-summary", "--sort=status", "status"]);
+    let stdout = String::from_utf8_lossy(&by_status.stdout);
+    assert_eq!(
+        stdout.lines().collect::<Vec<_>>(),
+        [
+            "[docs] (main) 1 untracked",
+            "[web ] (main) 1 untracked",
+            "[api ] (main) clean",
+            "[zeta] (main) clean",
+        ],
+        "{stdout}"
+    );
+
+    let by_name = git_all(temp.path(), &["--no-summary", "status"]);
+    let names: Vec<String> = String::from_utf8_lossy(&by_name.stdout)
+        .lines()
+        .map(|line| line[..6].to_string())
+        .collect();
+    assert_eq!(names, ["[api ]", "[docs]", "[web ]", "[zeta]"]);
+}
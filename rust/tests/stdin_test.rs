@@ -0,0 +1,44 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+mod common;
+use common::init_repo;
+
+fn git_all_with_stdin(dir: &Path, args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_git-all"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to execute");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin)
+        .expect("write stdin");
+    child.wait_with_output().expect("wait for git-all")
+}
+
+#[test]
+fn from_stdin0_reads_nul_delimited_paths() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    init_repo(&temp.path().join("api"));
+    init_repo(&temp.path().join("web app"));
+    init_repo(&temp.path().join("unlisted"));
+
+    let output = git_all_with_stdin(
+        temp.path(),
+        &["--from-stdin0", "status"],
+        b"./api\0./web app\0api\0",
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("[api    ] (main) clean"), "{stdout}");
+    assert!(stdout.contains("[web app] (main) clean"), "{stdout}");
+    assert!(!stdout.contains("unlisted"), "{stdout}");
+    assert_eq!(stdout.matches("[api").count(), 1, "{stdout}");
+}
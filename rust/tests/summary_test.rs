@@ -0,0 +1,111 @@
+mod common;
+use common::{git, git_all};
+
+#[test]
+fn summary_counts_ok_and_failed_for_passthrough() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "web", "empty"] {
+        let repo = temp.path().join(name);
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q"]);
+        if name != "empty" {
+            git(&repo, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        }
+    }
+
+    // rev-parse HEAD fails in the repo without commits
+    let output = git_all(
+        temp.path(),
+        &["--summary", "rev-parse", "--verify", "-q", "HEAD"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.lines().last(),
+        Some("3 repos: 2 ok, 1 failed"),
+        "{stdout}"
+    );
+    // On stdout already, so not repeated on stderr
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("repos:"));
+}
+
+#[test]
+fn summary_ends_every_run_on_stderr_unless_disabled() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "web", "docs"] {
+        let repo = temp.path().join(name);
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q"]);
+    }
+    std::fs::write(temp.path().join("web").join("notes.txt"), "").unwrap();
+
+    let output = git_all(temp.path(), &["status"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.trim_end(), "3 repos: 2 clean, 1 dirty", "{stderr}");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("repos:"));
+
+    let output = git_all(temp.path(), &["--no-summary", "status"]);
+    assert!(
+        output.stderr.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn failures_at_end_lists_only_failed_repos_last() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "broken", "web"] {
+        let repo = temp.path().join(name);
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q"]);
+    }
+    let missing = temp.path().join("missing.git");
+    git(
+        &temp.path().join("broken"),
+        &["remote", "add", "origin", missing.to_str().unwrap()],
+    );
+
+    let output = git_all(temp.path(), &["--failures-at-end", "fetch"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (_, section) = stdout
+        .split_once("Failed repositories:\n")
+        .unwrap_or_else(|| panic!("no failures section: {stdout}"));
+    let failed: Vec<&str> = section.lines().collect();
+    assert_eq!(failed.len(), 1, "{stdout}");
+    assert!(failed[0].starts_with("  [broken] "), "{stdout}");
+    assert!(failed[0].contains("missing.git"), "{stdout}");
+}
+
+#[test]
+fn group_by_dir_prints_repos_under_their_parent_dir() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["org-b/web", "org-a/docs", "org-a/api"] {
+        let repo = temp.path().join(name);
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "main"]);
+    }
+
+    let output = git_all(
+        temp.path(),
+        &[
+            "--scan-depth",
+            "2",
+            "--group-by-dir",
+            "--no-summary",
+            "status",
+        ],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "org-a/",
+            "  [org-a/api ] (main) clean",
+            "  [org-a/docs] (main) clean",
+            "org-b/",
+            "  [org-b/web ] (main) clean",
+        ],
+        "{stdout}"
+    );
+}
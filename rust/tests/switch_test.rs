@@ -0,0 +1,44 @@
+mod common;
+use common::{git, git_all};
+
+#[test]
+fn switch_reports_per_repo_outcome() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    for name in ["api", "tools", "web"] {
+        let repo = temp.path().join(name);
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "main"]);
+        std::fs::write(repo.join("app.rs"), "one").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "init"]);
+    }
+    for name in ["api", "web"] {
+        let repo = temp.path().join(name);
+        git(&repo, &["checkout", "-q", "-b", "release"]);
+        std::fs::write(repo.join("app.rs"), "two").unwrap();
+        git(&repo, &["commit", "-q", "-am", "release"]);
+        git(&repo, &["checkout", "-q", "main"]);
+    }
+    // Would be overwritten by release's app.rs
+    std::fs::write(temp.path().join("web").join("app.rs"), "local").unwrap();
+
+    let output = git_all(temp.path(), &["switch", "release"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[api  ] switched to release"), "{stdout}");
+    assert!(stdout.contains("[tools] no such branch"), "{stdout}");
+    assert!(stdout.contains("[web  ] dirty, skipped"), "{stdout}");
+
+    let output = git_all(temp.path(), &["switch", "release"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[api  ] already on release"), "{stdout}");
+
+    let output = git_all(
+        temp.path(),
+        &["--filter", "tools", "switch", "--create", "release"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("[tools] switched to new branch release"),
+        "{stdout}"
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+use common::{git, git_all};
+
+#[test]
+fn sync_prunes_and_fast_forwards() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let remote = temp.path().join("remote.git");
+    let seed = temp.path().join("seed");
+    let root = temp.path().join("work");
+    std::fs::create_dir_all(&root).unwrap();
+    git(
+        temp.path(),
+        &[
+            "init",
+            "-q",
+            "--bare",
+            "-b",
+            "main",
+            remote.to_str().unwrap(),
+        ],
+    );
+    git(
+        temp.path(),
+        &["init", "-q", "-b", "main", seed.to_str().unwrap()],
+    );
+    git(&seed, &["commit", "-q", "--allow-empty", "-m", "init"]);
+    git(
+        &seed,
+        &["remote", "add", "origin", remote.to_str().unwrap()],
+    );
+    git(&seed, &["push", "-q", "origin", "main", "main:old-feature"]);
+    let api = root.join("api");
+    let web = root.join("web");
+    for clone in [&api, &web] {
+        git(
+            temp.path(),
+            &[
+                "clone",
+                "-q",
+                remote.to_str().unwrap(),
+                clone.to_str().unwrap(),
+            ],
+        );
+    }
+
+    for message in ["one", "two", "three"] {
+        git(&seed, &["commit", "-q", "--allow-empty", "-m", message]);
+    }
+    git(&seed, &["push", "-q", "origin", "main", ":old-feature"]);
+    // web has a local commit, so it can't fast-forward
+    git(&web, &["commit", "-q", "--allow-empty", "-m", "local"]);
+
+    let output = git_all(&root, &["sync"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("[api ] fetched, 1 stale pruned, fast-forwarded 3"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("[web ] fetched, 1 stale pruned, local changes, skipped ff"),
+        "{stdout}"
+    );
+
+    let output = git_all(&root, &["--filter", "api", "sync"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[api ] fetched, up to date"), "{stdout}");
+}
@@ -0,0 +1,40 @@
+mod common;
+use common::{git, git_all};
+
+#[test]
+fn verify_remotes_classifies_each_origin() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let upstream = temp.path().join("upstream");
+    let workspace = temp.path().join("workspace");
+    std::fs::create_dir_all(&upstream).unwrap();
+    std::fs::create_dir_all(&workspace).unwrap();
+    git(&upstream, &["init", "-q", "-b", "main"]);
+    git(&upstream, &["commit", "-q", "--allow-empty", "-m", "init"]);
+    git(&upstream, &["branch", "release"]);
+
+    git(
+        &workspace,
+        &["clone", "-q", upstream.to_str().unwrap(), "api"],
+    );
+    git(
+        &workspace,
+        &["clone", "-q", upstream.to_str().unwrap(), "dead"],
+    );
+    git(
+        &workspace.join("dead"),
+        &[
+            "remote",
+            "set-url",
+            "origin",
+            temp.path().join("gone").to_str().unwrap(),
+        ],
+    );
+    std::fs::create_dir_all(workspace.join("local")).unwrap();
+    git(&workspace.join("local"), &["init", "-q"]);
+
+    let output = git_all(&workspace, &["verify-remotes"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[api  ] ok (2 refs)"), "{stdout}");
+    assert!(stdout.contains("[dead ] UNREACHABLE"), "{stdout}");
+    assert!(stdout.contains("[local] (no origin)"), "{stdout}");
+}
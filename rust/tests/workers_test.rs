@@ -0,0 +1,45 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn git_all_with_nproc(dir: &Path, nproc: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_git-all"))
+        .args(args)
+        .env("NPROC", nproc)
+        .current_dir(dir)
+        .output()
+        .expect("failed to execute")
+}
+
+#[test]
+fn parallel_limit_env_caps_only_the_default_worker_count() {
+    let temp = tempfile::tempdir().expect("temp dir");
+    let repo = temp.path().join("api");
+    std::fs::create_dir_all(&repo).unwrap();
+    let status = Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(&repo)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let args = ["--no-summary", "--parallel-limit-env", "status"];
+    let capped = git_all_with_nproc(temp.path(), "2", &args);
+    assert_eq!(
+        String::from_utf8_lossy(&capped.stderr).trim_end(),
+        "git-all: 2 CPUs available; using 2 workers instead of 8"
+    );
+
+    let explicit = git_all_with_nproc(temp.path(), "2", &[&["-n", "8"][..], &args].concat());
+    assert!(
+        explicit.stderr.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&explicit.stderr)
+    );
+
+    let roomy = git_all_with_nproc(temp.path(), "64", &args);
+    assert!(
+        roomy.stderr.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&roomy.stderr)
+    );
+}